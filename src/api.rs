@@ -0,0 +1,555 @@
+// -------------------------------------------------------------
+// RUST API — pemakaian crate sebagai rlib (server native, CLI) tanpa packet/Option ala JS.
+// -------------------------------------------------------------
+// Registry::add_* menerima spec bertipe; initLayer (packet) hanya decoder di atasnya.
+// Graph dibangun lewat GraphBuilder (slot bertipe), forward memakai burn::Tensor langsung.
+//
+//   let mut reg = Registry::new();
+//   let fc = reg.add_linear(1, LinearSpec { d_in: 3, d_out: 4, bias: true })?;
+//   let act = reg.add_activation(2, ActivationSpec::Relu)?;
+//   let mut g = GraphBuilder::new();
+//   let h = g.unary(fc, g.input());
+//   let y = g.unary(act, h);
+//   let graph = g.build(&reg, y)?;
+//   let out: Tensor4 = graph.forward(&reg, x)?;
+
+use burn::tensor::Tensor;
+use crate::graph::CompiledGraph;
+use crate::plan::{ARITY_BINARY, ARITY_IF, ARITY_LOOP, ARITY_SELECT, ARITY_UNARY};
+use crate::layers::layout::JsonObj;
+use crate::protocol::*;
+use crate::WasmBackend;
+
+pub use crate::es::optimizer::EsOptimizer;
+pub use crate::layers::binary::{Binary, BinaryOp};
+pub use crate::layers::conv::{ConvPadding, ConvSpec};
+pub use crate::layers::custom::cbam::CbamBlockConfig;
+pub use crate::layers::custom::eca::EcaBlockConfig;
+pub use crate::layers::custom::ghost::GhostModuleConfig;
+pub use crate::layers::custom::seblock::SeBlockConfig;
+pub use crate::layers::custom::shift::{Shift, ShiftDirection, ShiftFill};
+pub use crate::layers::custom::spatial_attention::SpatialAttentionConfig;
+pub use crate::layers::resample::{Resample, ResampleSize};
+pub use crate::layers::tensor_op::{Reduce, TensorOp};
+pub use crate::registry::LayerRegistry as Registry;
+
+/// Tensor runtime (backend ndarray, 4D seperti WasmTensor).
+pub type Tensor4 = Tensor<WasmBackend, 4>;
+
+/// Handle layer yang sudah terdaftar: (byte LAYER_*, id).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LayerRef {
+    pub layer_type: u8,
+    pub id: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LinearSpec {
+    pub d_in: usize,
+    pub d_out: usize,
+    pub bias: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct EmbeddingSpec {
+    pub vocab: usize,
+    pub d_model: usize,
+}
+
+/// `epsilon` None -> 1e-5.
+#[derive(Clone, Copy, Debug)]
+pub enum NormSpec {
+    Batch { num_features: usize, epsilon: Option<f64> },
+    Group { num_groups: usize, num_channels: usize, epsilon: Option<f64> },
+    Instance { num_channels: usize, epsilon: Option<f64> },
+    Layer { size: usize, epsilon: Option<f64> },
+    Rms { size: usize, epsilon: Option<f64> },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConvKind {
+    Conv1d,
+    Conv2d,
+    Conv3d,
+    ConvTranspose1d,
+    ConvTranspose2d,
+}
+
+/// Field None -> default yang sama dengan packet/JS.
+#[derive(Clone, Copy, Debug)]
+pub enum ActivationSpec {
+    Gelu,
+    Relu,
+    Sigmoid,
+    Tanh,
+    HardSwish,
+    Mish,
+    LeakyRelu { negative_slope: Option<f64> },
+    PRelu { num_parameters: Option<usize>, alpha: Option<f64> },
+    SwiGlu { d_input: usize, d_output: usize, bias: Option<bool> },
+    HardSigmoid { alpha: Option<f64>, beta: Option<f64> },
+    Softplus { beta: Option<f64> },
+    Softmax { dim: usize },
+    LogSoftmax { dim: usize },
+    Glu { dim: usize },
+}
+
+/// `[h, w]` untuk 2D; stride None -> = kernel, padding None -> 0.
+#[derive(Clone, Copy, Debug)]
+pub enum PoolSpec {
+    MaxPool1d { kernel: usize, stride: Option<usize>, padding: Option<usize>, dilation: Option<usize>, ceil_mode: bool },
+    AvgPool1d { kernel: usize, stride: Option<usize>, padding: Option<usize> },
+    MaxPool2d {
+        kernel: [usize; 2],
+        stride: Option<[usize; 2]>,
+        padding: Option<[usize; 2]>,
+        dilation: Option<[usize; 2]>,
+        ceil_mode: bool,
+    },
+    AvgPool2d { kernel: [usize; 2], stride: Option<[usize; 2]>, padding: Option<[usize; 2]> },
+    AdaptiveAvg1d { output: usize },
+    AdaptiveAvg2d { output: [usize; 2] },
+    AdaptiveMax2d { output: [usize; 2] },
+    GlobalAvg,
+    GlobalMax,
+}
+
+// ------------------------------------------------------------
+// DESCRIBE — (varian, config JSON) per spec untuk listLayers/describeLayer.
+// Field None ditulis null (= default layer, sama seperti packet).
+// ------------------------------------------------------------
+impl LinearSpec {
+    pub(crate) fn config_json(&self) -> String {
+        JsonObj::new().num("d_in", self.d_in).num("d_out", self.d_out).num("bias", self.bias).finish()
+    }
+}
+
+impl EmbeddingSpec {
+    pub(crate) fn config_json(&self) -> String {
+        JsonObj::new().num("vocab", self.vocab).num("d_model", self.d_model).finish()
+    }
+}
+
+impl NormSpec {
+    pub(crate) fn describe(&self) -> (u8, String) {
+        match *self {
+            NormSpec::Batch { num_features, epsilon } => {
+                (NORM_BATCH, JsonObj::new().num("num_features", num_features).opt("epsilon", epsilon).finish())
+            }
+            NormSpec::Group { num_groups, num_channels, epsilon } => (
+                NORM_GROUP,
+                JsonObj::new()
+                    .num("num_groups", num_groups)
+                    .num("num_channels", num_channels)
+                    .opt("epsilon", epsilon)
+                    .finish(),
+            ),
+            NormSpec::Instance { num_channels, epsilon } => {
+                (NORM_INSTANCE, JsonObj::new().num("num_channels", num_channels).opt("epsilon", epsilon).finish())
+            }
+            NormSpec::Layer { size, epsilon } => {
+                (NORM_LAYER, JsonObj::new().num("size", size).opt("epsilon", epsilon).finish())
+            }
+            NormSpec::Rms { size, epsilon } => (NORM_RMS, JsonObj::new().num("size", size).opt("epsilon", epsilon).finish()),
+        }
+    }
+}
+
+impl ConvKind {
+    pub(crate) fn variant(&self) -> u8 {
+        match self {
+            ConvKind::Conv1d => CONV_CONV1D,
+            ConvKind::Conv2d => CONV_CONV2D,
+            ConvKind::Conv3d => CONV_CONV3D,
+            ConvKind::ConvTranspose1d => CONV_CONVTRANSPOSE1D,
+            ConvKind::ConvTranspose2d => CONV_CONVTRANSPOSE2D,
+        }
+    }
+}
+
+impl ActivationSpec {
+    pub(crate) fn describe(&self) -> (u8, String) {
+        let o = JsonObj::new();
+        match *self {
+            ActivationSpec::Gelu => (ACT_GELU, o.finish()),
+            ActivationSpec::Relu => (ACT_RELU, o.finish()),
+            ActivationSpec::Sigmoid => (ACT_SIGMOID, o.finish()),
+            ActivationSpec::Tanh => (ACT_TANH, o.finish()),
+            ActivationSpec::HardSwish => (ACT_HARDSWISH, o.finish()),
+            ActivationSpec::Mish => (ACT_MISH, o.finish()),
+            ActivationSpec::LeakyRelu { negative_slope } => {
+                (ACT_LEAKYRELU, o.opt("negative_slope", negative_slope).finish())
+            }
+            ActivationSpec::PRelu { num_parameters, alpha } => {
+                (ACT_PRELU, o.opt("num_parameters", num_parameters).opt("alpha", alpha).finish())
+            }
+            ActivationSpec::SwiGlu { d_input, d_output, bias } => {
+                (ACT_SWIGLU, o.num("d_input", d_input).num("d_output", d_output).opt("bias", bias).finish())
+            }
+            ActivationSpec::HardSigmoid { alpha, beta } => {
+                (ACT_HARDSIGMOID, o.opt("alpha", alpha).opt("beta", beta).finish())
+            }
+            ActivationSpec::Softplus { beta } => (ACT_SOFTPLUS, o.opt("beta", beta).finish()),
+            ActivationSpec::Softmax { dim } => (ACT_SOFTMAX, o.num("dim", dim).finish()),
+            ActivationSpec::LogSoftmax { dim } => (ACT_LOGSOFTMAX, o.num("dim", dim).finish()),
+            ActivationSpec::Glu { dim } => (ACT_GLU, o.num("dim", dim).finish()),
+        }
+    }
+}
+
+impl PoolSpec {
+    pub(crate) fn describe(&self) -> (u8, String) {
+        let pair = |o: JsonObj, key: &str, v: Option<[usize; 2]>| match v {
+            Some(v) => o.list(key, &v),
+            None => o.raw(key, "null"),
+        };
+        let o = JsonObj::new();
+        match *self {
+            PoolSpec::MaxPool1d { kernel, stride, padding, dilation, ceil_mode } => (
+                POOL_MAXPOOL1D,
+                o.num("kernel", kernel)
+                    .opt("stride", stride)
+                    .opt("padding", padding)
+                    .opt("dilation", dilation)
+                    .num("ceil_mode", ceil_mode)
+                    .finish(),
+            ),
+            PoolSpec::AvgPool1d { kernel, stride, padding } => {
+                (POOL_AVGPOOL1D, o.num("kernel", kernel).opt("stride", stride).opt("padding", padding).finish())
+            }
+            PoolSpec::MaxPool2d { kernel, stride, padding, dilation, ceil_mode } => {
+                let o = pair(o.list("kernel", &kernel), "stride", stride);
+                let o = pair(pair(o, "padding", padding), "dilation", dilation);
+                (POOL_MAXPOOL2D, o.num("ceil_mode", ceil_mode).finish())
+            }
+            PoolSpec::AvgPool2d { kernel, stride, padding } => {
+                let o = pair(o.list("kernel", &kernel), "stride", stride);
+                (POOL_AVGPOOL2D, pair(o, "padding", padding).finish())
+            }
+            PoolSpec::AdaptiveAvg1d { output } => (POOL_ADAPTIVEAVGPOOL1D, o.num("output", output).finish()),
+            PoolSpec::AdaptiveAvg2d { output } => (POOL_ADAPTIVEAVGPOOL2D, o.list("output", &output).finish()),
+            PoolSpec::AdaptiveMax2d { output } => (POOL_ADAPTIVEMAXPOOL2D, o.list("output", &output).finish()),
+            PoolSpec::GlobalAvg => (POOL_GLOBALAVGPOOL, o.finish()),
+            PoolSpec::GlobalMax => (POOL_GLOBALMAXPOOL, o.finish()),
+        }
+    }
+}
+
+// ------------------------------------------------------------
+// INIT FIELDS — payload OP_INIT setelah `id` (kebalikan registry::init_*), supaya layer
+// dari add_* tetap punya paket init untuk snapshot/.bmodel. Tail opsional selalu ditulis.
+// ------------------------------------------------------------
+impl LinearSpec {
+    pub(crate) fn init_fields(&self) -> Vec<u8> {
+        PayloadWriter::new().usize(self.d_in).usize(self.d_out).bool(self.bias).finish()
+    }
+}
+
+impl EmbeddingSpec {
+    pub(crate) fn init_fields(&self) -> Vec<u8> {
+        PayloadWriter::new().usize(self.vocab).usize(self.d_model).finish()
+    }
+}
+
+impl NormSpec {
+    /// Layout v2 (GROUP: num_groups, num_channels, eps?).
+    pub(crate) fn init_fields(&self) -> Vec<u8> {
+        let w = PayloadWriter::new();
+        match *self {
+            NormSpec::Group { num_groups, num_channels, epsilon } => {
+                w.usize(num_groups).usize(num_channels).option_f64(epsilon)
+            }
+            NormSpec::Batch { num_features: size, epsilon }
+            | NormSpec::Instance { num_channels: size, epsilon }
+            | NormSpec::Layer { size, epsilon }
+            | NormSpec::Rms { size, epsilon } => w.usize(size).option_f64(epsilon),
+        }
+        .finish()
+    }
+}
+
+impl ConvKind {
+    pub(crate) fn init_fields(&self, spec: &ConvSpec) -> Vec<u8> {
+        let pad_mode = match spec.padding_mode {
+            ConvPadding::Explicit => CONV_PAD_EXPLICIT,
+            ConvPadding::Same => CONV_PAD_SAME,
+            ConvPadding::Valid => CONV_PAD_VALID,
+        };
+        let w = PayloadWriter::new()
+            .usize(spec.in_channels)
+            .usize(spec.out_channels)
+            .usize(spec.kernel_h)
+            .usize(spec.kernel_w)
+            .option_usize(spec.stride_h)
+            .option_usize(spec.stride_w)
+            .option_usize(spec.padding_h)
+            .option_usize(spec.padding_w)
+            .option_usize(spec.dilation_h)
+            .option_usize(spec.dilation_w)
+            .option_usize(spec.groups)
+            .option_u32(spec.bias.map(u32::from))
+            .u8(pad_mode);
+        let w = match self {
+            ConvKind::Conv3d => w
+                .usize(spec.kernel_d)
+                .option_usize(spec.stride_d)
+                .option_usize(spec.padding_d)
+                .option_usize(spec.dilation_d),
+            _ => w,
+        };
+        w.finish()
+    }
+}
+
+impl ActivationSpec {
+    pub(crate) fn init_fields(&self) -> Vec<u8> {
+        let w = PayloadWriter::new();
+        match *self {
+            ActivationSpec::Gelu
+            | ActivationSpec::Relu
+            | ActivationSpec::Sigmoid
+            | ActivationSpec::Tanh
+            | ActivationSpec::HardSwish
+            | ActivationSpec::Mish => w,
+            ActivationSpec::LeakyRelu { negative_slope } => w.option_f64(negative_slope),
+            ActivationSpec::PRelu { num_parameters, alpha } => w.option_usize(num_parameters).option_f64(alpha),
+            ActivationSpec::SwiGlu { d_input, d_output, bias } => {
+                w.usize(d_input).usize(d_output).option_u32(bias.map(u32::from))
+            }
+            ActivationSpec::HardSigmoid { alpha, beta } => w.option_f64(alpha).option_f64(beta),
+            ActivationSpec::Softplus { beta } => w.option_f64(beta),
+            ActivationSpec::Softmax { dim } | ActivationSpec::LogSoftmax { dim } | ActivationSpec::Glu { dim } => {
+                w.usize(dim)
+            }
+        }
+        .finish()
+    }
+}
+
+impl PoolSpec {
+    pub(crate) fn init_fields(&self) -> Vec<u8> {
+        let pair = |w: PayloadWriter, v: Option<[usize; 2]>| w.option_usize(v.map(|v| v[0])).option_usize(v.map(|v| v[1]));
+        let w = PayloadWriter::new();
+        match *self {
+            PoolSpec::MaxPool1d { kernel, stride, padding, dilation, ceil_mode } => w
+                .usize(kernel)
+                .option_usize(stride)
+                .option_usize(padding)
+                .option_usize(dilation)
+                .bool(ceil_mode),
+            PoolSpec::AvgPool1d { kernel, stride, padding } => w.usize(kernel).option_usize(stride).option_usize(padding),
+            PoolSpec::MaxPool2d { kernel, stride, padding, dilation, ceil_mode } => {
+                let w = pair(w.usize(kernel[0]).usize(kernel[1]), stride);
+                pair(pair(w, padding), dilation).bool(ceil_mode)
+            }
+            PoolSpec::AvgPool2d { kernel, stride, padding } => {
+                pair(pair(w.usize(kernel[0]).usize(kernel[1]), stride), padding)
+            }
+            PoolSpec::AdaptiveAvg1d { output } => w.usize(output),
+            PoolSpec::AdaptiveAvg2d { output } | PoolSpec::AdaptiveMax2d { output } => {
+                w.usize(output[0]).usize(output[1])
+            }
+            PoolSpec::GlobalAvg | PoolSpec::GlobalMax => w,
+        }
+        .finish()
+    }
+}
+
+/// Slot graph bertipe (indeks slot CompiledGraph). Slot 0 = input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Slot(u16);
+
+/// Builder graph bertipe: tiap step menulis slot baru (SSA), lalu di-encode ke plan v2
+/// dan divalidasi oleh `CompiledGraph::build` yang sama dengan compileGraph (yang juga
+/// memakai ulang slot mati, jadi slot SSA tidak memboroskan memori).
+pub struct GraphBuilder {
+    steps: Vec<(u8, LayerRef, Slot, Slot, Slot)>,
+    next: u32,
+}
+
+impl Default for GraphBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GraphBuilder {
+    pub fn new() -> Self {
+        GraphBuilder { steps: Vec::new(), next: 1 }
+    }
+
+    pub fn input(&self) -> Slot {
+        Slot(0)
+    }
+
+    fn fresh(&mut self) -> Slot {
+        // Lewat batas slot -> plan ditolak saat build (num_slots di luar rentang).
+        let s = Slot(self.next.min(u16::MAX as u32) as u16);
+        self.next += 1;
+        s
+    }
+
+    pub fn unary(&mut self, layer: LayerRef, x: Slot) -> Slot {
+        let out = self.fresh();
+        self.steps.push((ARITY_UNARY, layer, x, Slot(0), out));
+        out
+    }
+
+    /// `layer` harus LAYER_BINARY.
+    pub fn binary(&mut self, layer: LayerRef, a: Slot, b: Slot) -> Slot {
+        let out = self.fresh();
+        self.steps.push((ARITY_BINARY, layer, a, b, out));
+        out
+    }
+
+    /// Slot baru tanpa step, mis. tujuan `unary_into` dari kedua cabang `if_else`.
+    pub fn slot(&mut self) -> Slot {
+        self.fresh()
+    }
+
+    /// `unary` yang menulis slot yang sudah ada (state loop-carried, hasil cabang IF).
+    pub fn unary_into(&mut self, layer: LayerRef, x: Slot, out: Slot) {
+        self.steps.push((ARITY_UNARY, layer, x, Slot(0), out));
+    }
+
+    pub fn binary_into(&mut self, layer: LayerRef, a: Slot, b: Slot, out: Slot) {
+        self.steps.push((ARITY_BINARY, layer, a, b, out));
+    }
+
+    /// Loop terbatas: step yang ditambahkan `body` diulang `iters` kali. Slot yang dibaca body
+    /// sebelum ditulis ulang di body membawa nilai iterasi sebelumnya.
+    pub fn repeat(&mut self, iters: u32, body: impl FnOnce(&mut Self)) {
+        let at = self.control(ARITY_LOOP, iters, Slot(0), Slot(0), Slot(0));
+        body(self);
+        // Body > 65535 step -> panjang 0, plan ditolak saat build.
+        let n = self.steps.len() - at - 1;
+        self.steps[at].2 = Slot(u16::try_from(n).unwrap_or(0));
+    }
+
+    /// Cabang bersyarat pada slot skalar `cond` (> 0 = then). Slot yang ingin dipakai
+    /// setelahnya harus ditulis kedua cabang.
+    pub fn if_else(&mut self, cond: Slot, then: impl FnOnce(&mut Self), otherwise: impl FnOnce(&mut Self)) {
+        let at = self.control(ARITY_IF, 0, cond, Slot(0), Slot(0));
+        then(self);
+        let t = self.steps.len() - at - 1;
+        otherwise(self);
+        let e = self.steps.len() - at - 1 - t;
+        self.steps[at].1.id = match (u16::try_from(t), u16::try_from(e)) {
+            (Ok(t), Ok(e)) => t as u32 | (e as u32) << 16,
+            _ => u32::MAX, // cabang > 65535 step -> ditolak saat build
+        };
+    }
+
+    /// `cond > 0 ? a : b` tanpa layer.
+    pub fn select(&mut self, cond: Slot, a: Slot, b: Slot) -> Slot {
+        let out = self.fresh();
+        self.control(ARITY_SELECT, cond.0 as u32, a, b, out);
+        out
+    }
+
+    /// Step control flow v2: operand di id/slot (lihat plan::ARITY_LOOP).
+    fn control(&mut self, kind: u8, arg: u32, a: Slot, b: Slot, out: Slot) -> usize {
+        self.steps.push((kind, LayerRef { layer_type: 0, id: arg }, a, b, out));
+        self.steps.len() - 1
+    }
+
+    fn push_header(&self, plan: &mut Vec<u8>) {
+        plan.extend_from_slice(&PLAN_MAGIC);
+        plan.push(2);
+        plan.extend_from_slice(&(self.steps.len() as u32).to_le_bytes());
+        plan.extend_from_slice(&self.next.to_le_bytes());
+    }
+
+    /// Plan biner v2 (format compileGraph), mis. untuk disimpan/dipakai burn-cli.
+    pub fn to_plan(&self, output: Slot) -> Vec<u8> {
+        let mut plan = Vec::with_capacity(14 + self.steps.len() * 12);
+        self.push_header(&mut plan);
+        for &(arity, layer, a, b, out) in &self.steps {
+            plan.push(arity);
+            plan.push(layer.layer_type);
+            plan.extend_from_slice(&layer.id.to_le_bytes());
+            push_slots(&mut plan, [a, b, out]);
+        }
+        plan.extend_from_slice(&output.0.to_le_bytes());
+        plan
+    }
+
+    /// Plan id-only (format compileGraphIds, 1 byte/step lebih pendek) untuk registry withGlobalIds.
+    pub fn to_id_plan(&self, output: Slot) -> Vec<u8> {
+        let mut plan = Vec::with_capacity(14 + self.steps.len() * 11);
+        self.push_header(&mut plan);
+        for &(arity, layer, a, b, out) in &self.steps {
+            plan.push(arity);
+            if arity > ARITY_BINARY {
+                plan.push(layer.layer_type); // step control flow tetap penuh
+            }
+            plan.extend_from_slice(&layer.id.to_le_bytes());
+            push_slots(&mut plan, [a, b, out]);
+        }
+        plan.extend_from_slice(&output.0.to_le_bytes());
+        plan
+    }
+
+    pub fn build(&self, registry: &Registry, output: Slot) -> Result<CompiledGraph, String> {
+        self.check_binary_steps()?;
+        CompiledGraph::build(registry, &self.to_plan(output))
+    }
+
+    /// `build` + pass fusion (lihat LayerRegistry::compile_graph_fused).
+    pub fn build_fused(&self, registry: &Registry, output: Slot) -> Result<CompiledGraph, String> {
+        self.check_binary_steps()?;
+        CompiledGraph::build_fused(registry, &self.to_plan(output))
+    }
+
+    fn check_binary_steps(&self) -> Result<(), String> {
+        if let Some((_, l, ..)) = self.steps.iter().find(|s| s.0 == ARITY_BINARY && s.1.layer_type != LAYER_BINARY) {
+            return Err(format!("GraphBuilder: binary step needs LAYER_BINARY, got {:?}", l));
+        }
+        Ok(())
+    }
+}
+
+fn push_slots(plan: &mut Vec<u8>, slots: [Slot; 3]) {
+    for s in slots {
+        plan.extend_from_slice(&s.0.to_le_bytes());
+    }
+}
+
+/// Konfigurasi EsOptimizer bertipe (pengganti argumen Option ala JS).
+#[derive(Clone, Copy, Debug)]
+pub struct EsConfig {
+    pub dim: usize,
+    pub strategy: EsKind,
+    pub seed: u32,
+    /// Jumlah pasangan (OpenEs) atau lambda (MuLambda).
+    pub population: usize,
+    pub sigma: f32,
+    pub lr: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EsKind {
+    OpenEs,
+    MuLambda,
+}
+
+impl EsConfig {
+    pub fn new(dim: usize) -> Self {
+        EsConfig { dim, strategy: EsKind::OpenEs, seed: 0, population: 64, sigma: 0.1, lr: 0.05 }
+    }
+
+    pub fn build(&self) -> EsOptimizer {
+        let strategy = match self.strategy {
+            EsKind::OpenEs => 0,
+            EsKind::MuLambda => 1,
+        };
+        EsOptimizer::new(
+            self.dim as u32,
+            strategy,
+            self.seed,
+            Some(self.population as u32),
+            Some(self.sigma),
+            Some(self.lr),
+        )
+    }
+}
@@ -0,0 +1,380 @@
+// burn-cli — runner native untuk registry + graph tanpa build wasm / harness browser.
+//
+//   burn-cli forward  --packets F --plan F --input F [--shape 1,3,8,8] [--batch N] [--trace F]
+//   burn-cli shape    --packets F --plan F --shape 1,3,8,8
+//   burn-cli debug    --packets F --plan F --input F [--shape ..] (statistik per slot, stop di NaN/Inf)
+//   burn-cli es-demo  [--gens 50] [ES opsi]
+//   burn-cli es-graph --packets F --plan F --input F --target F --layers 1:1,3:2 [--gens 50] [ES opsi]
+//                     (--train 1:9 ganti --layers: bekukan semua kecuali layer ini, lihat train_only)
+//                     (--out F: simpan registry dengan bobot terbaik; *.bmodel -> model, selain itu
+//                      stream paket untuk --packets)
+//   burn-cli caps     (versi protokol + layer/varian yang didukung, JSON)
+//   burn-cli pack     --packets F --plan F --out F [--name main] [--shape ..] [--meta k=v,..]
+//                     (dari --model: plan + signature graph asal dipakai ulang kalau --shape tidak ada)
+//
+// ES opsi: --strategy 0|1 --seed S --pop P --sigma X --lr X
+// --packets: stream paket OP_INIT/OP_LOAD_STATE/OP_DESTROY (lihat LayerRegistry::apply_packets).
+// --plan: plan CompiledGraph (biner, format compileGraph).
+// --model F [--graph NAME]: ganti --packets/--plan dengan satu file .bmodel (lihat model.rs);
+//   --graph boleh dilewati kalau model hanya punya satu graph.
+// --input/--target: .npy (<f4/<f8) atau raw f32 LE (butuh --shape untuk input).
+// --trace: tulis profil per step sebagai Chrome trace JSON (chrome://tracing / Perfetto).
+
+mod npy;
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::process::ExitCode;
+
+use burn_research::es::objective::GraphMseObjective;
+use burn_research::es::optimizer::EsOptimizer;
+use burn_research::api::LayerRef;
+use burn_research::graph::CompiledGraph;
+use burn_research::model::{GraphSignature, Model, ModelBuilder};
+use burn_research::registry::LayerRegistry;
+use burn_research::WasmTensor;
+
+const USAGE: &str = "usage: burn-cli <forward|shape|debug|es-demo|es-graph|caps|pack> [--flag value]...
+  forward   --packets F --plan F --input F [--shape d0,d1,..] [--batch N] [--trace F]
+  shape     --packets F --plan F --shape d0,d1,..
+  debug     --packets F --plan F --input F [--shape d0,d1,..]
+  es-demo   [--gens N] [--strategy 0|1] [--seed S] [--pop P] [--sigma X] [--lr X]
+  es-graph  --packets F --plan F --input F --target F --layers|--train type:id,.. [--gens N] [--out F] [ES flags]
+  caps      print protocol version and supported layers as JSON
+  pack      --packets F --plan F --out F [--name NAME] [--shape d0,..] [--meta k=v,..]
+  (--model F [--graph NAME] may replace --packets/--plan)";
+
+struct Args {
+    flags: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse(raw: &[String]) -> Result<Args, String> {
+        let mut flags = HashMap::new();
+        let mut it = raw.iter();
+        while let Some(k) = it.next() {
+            let key = k
+                .strip_prefix("--")
+                .ok_or_else(|| format!("unexpected argument {:?}", k))?;
+            let v = it.next().ok_or_else(|| format!("--{} needs a value", key))?;
+            flags.insert(key.to_string(), v.clone());
+        }
+        Ok(Args { flags })
+    }
+
+    fn req(&self, key: &str) -> Result<&str, String> {
+        self.flags
+            .get(key)
+            .map(String::as_str)
+            .ok_or_else(|| format!("missing --{}", key))
+    }
+
+    fn opt<T: std::str::FromStr>(&self, key: &str) -> Result<Option<T>, String> {
+        self.flags
+            .get(key)
+            .map(|v| v.parse::<T>().map_err(|_| format!("--{}: invalid value {:?}", key, v)))
+            .transpose()
+    }
+
+    fn shape(&self) -> Result<Option<Vec<usize>>, String> {
+        self.flags
+            .get("shape")
+            .map(|s| {
+                s.split(',')
+                    .map(|d| d.trim().parse::<usize>().map_err(|_| format!("--shape: invalid dim {:?}", d)))
+                    .collect()
+            })
+            .transpose()
+    }
+}
+
+fn read(path: &str) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|e| format!("{}: {}", path, e))
+}
+
+/// Plan asal graph yang dimuat, dipakai ulang saat menulis .bmodel.
+struct PlanSource {
+    bytes: Vec<u8>,
+    /// Format compileGraphIds (registry withGlobalIds).
+    ids: bool,
+    /// Signature tersimpan di .bmodel; kosong untuk --plan.
+    signature: GraphSignature,
+}
+
+fn load_graph(args: &Args) -> Result<(LayerRegistry, CompiledGraph), String> {
+    load_graph_source(args).map(|(reg, graph, _)| (reg, graph))
+}
+
+fn load_graph_source(args: &Args) -> Result<(LayerRegistry, CompiledGraph, PlanSource), String> {
+    if let Some(path) = args.flags.get("model") {
+        return load_model(path, args.flags.get("graph").map(String::as_str));
+    }
+    let mut reg = LayerRegistry::new();
+    let n = reg.apply_packets(&read(args.req("packets")?)?)?;
+    let plan = read(args.req("plan")?)?;
+    let graph = reg.compile_graph(&plan)?;
+    eprintln!(
+        "registry: {} packets, {} params; graph: {} steps, {} slots ({} physical)",
+        n,
+        reg.total_params(),
+        graph.step_count(),
+        graph.slot_count(),
+        graph.physical_slot_count()
+    );
+    Ok((reg, graph, PlanSource { bytes: plan, ids: false, signature: GraphSignature::default() }))
+}
+
+fn load_model(path: &str, name: Option<&str>) -> Result<(LayerRegistry, CompiledGraph, PlanSource), String> {
+    let model = Model::load(&read(path)?)?;
+    let name = match (name, model.graph_names().as_slice()) {
+        (Some(n), _) => n.to_string(),
+        (None, [only]) => only.to_string(),
+        (None, names) => return Err(format!("{}: {} graphs, pick one with --graph", path, names.len())),
+    };
+    let (plan, ids) = model.plan(&name).map_err(|e| format!("{}: {}", path, e))?;
+    let source = PlanSource { bytes: plan.to_vec(), ids, signature: model.signature(&name)?.clone() };
+    let (reg, mut graphs) = model.into_parts();
+    let graph = graphs.remove(&name).ok_or_else(|| format!("{}: no graph named {:?}", path, name))?;
+    eprintln!(
+        "model: graph {:?}, {} params; {} steps, {} slots ({} physical)",
+        name,
+        reg.total_params(),
+        graph.step_count(),
+        graph.slot_count(),
+        graph.physical_slot_count()
+    );
+    Ok((reg, graph, source))
+}
+
+fn load_input(args: &Args) -> Result<WasmTensor, String> {
+    let path = args.req("input")?;
+    let shape = args.shape()?;
+    if shape.is_none() && !npy::is_npy(path) {
+        return Err(format!("{}: raw f32 input needs --shape", path));
+    }
+    let t = npy::load(path, shape.as_deref())?;
+    if t.shape.len() > 4 {
+        return Err(format!("input rank {} > 4", t.shape.len()));
+    }
+    Ok(WasmTensor::new(&t.data, &t.shape))
+}
+
+/// Ringkasan output: shape + checksum (sum, mean, min, max, L2, FNV-1a bit f32).
+fn print_summary(label: &str, shape: &[usize], data: &[f32]) {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for v in data {
+        for b in v.to_bits().to_le_bytes() {
+            hash = (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    let sum: f64 = data.iter().map(|&v| v as f64).sum();
+    let l2 = data.iter().map(|&v| (v as f64).powi(2)).sum::<f64>().sqrt();
+    let min = data.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = data.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    println!(
+        "{}: shape={:?} n={} sum={:.6} mean={:.6} min={:.6} max={:.6} l2={:.6} fnv1a={:016x}",
+        label,
+        shape,
+        data.len(),
+        sum,
+        sum / data.len().max(1) as f64,
+        min,
+        max,
+        l2,
+        hash
+    );
+}
+
+fn es_from_args(args: &Args, dim: usize) -> Result<EsOptimizer, String> {
+    Ok(EsOptimizer::new(
+        dim as u32,
+        args.opt("strategy")?.unwrap_or(0),
+        args.opt("seed")?.unwrap_or(0),
+        args.opt("pop")?,
+        args.opt("sigma")?,
+        args.opt("lr")?,
+    ))
+}
+
+fn cmd_forward(args: &Args) -> Result<(), String> {
+    let (reg, mut graph) = load_graph(args)?;
+    let trace = args.flags.get("trace");
+    graph.set_profiling(trace.is_some());
+    let out = match args.opt::<usize>("batch")? {
+        Some(n) => {
+            // --input berisi n input ditumpuk; --shape = shape SATU input
+            let shape = args.shape()?.ok_or("--batch needs --shape (shape of one input)")?;
+            let stacked = npy::load(args.req("input")?, None)?;
+            graph.run_batch_flat(&reg, &stacked.data, &shape, n)?
+        }
+        None => graph.run(&reg, &load_input(args)?)?,
+    };
+    print_summary("output", &out.shape(), &out.to_array());
+    if let Some(path) = trace {
+        std::fs::write(path, graph.chrome_trace()).map_err(|e| format!("{}: {}", path, e))?;
+        eprintln!("profile: {}", graph.last_profile());
+    }
+    Ok(())
+}
+
+fn cmd_shape(args: &Args) -> Result<(), String> {
+    let (reg, graph) = load_graph(args)?;
+    let shape = args.shape()?.ok_or("missing --shape")?;
+    println!("output shape: {:?}", graph.output_shape(&reg, &shape)?);
+    Ok(())
+}
+
+fn cmd_debug(args: &Args) -> Result<(), String> {
+    let (reg, graph) = load_graph(args)?;
+    let dbg = graph.run_debug(&reg, &load_input(args)?, None, None)?;
+    println!("{}", dbg.report());
+    match dbg.stopped_at() {
+        Some(step) => Err(format!("non-finite output at step {}", step)),
+        None => Ok(()),
+    }
+}
+
+fn cmd_es_demo(args: &Args) -> Result<(), String> {
+    let mut es = es_from_args(args, 6)?; // demo: W 3x2
+    println!("{}", es.run_linear_demo(args.opt("gens")?.unwrap_or(50)));
+    Ok(())
+}
+
+fn cmd_es_graph(args: &Args) -> Result<(), String> {
+    let (mut reg, graph, source) = load_graph_source(args)?;
+    let input = load_input(args)?;
+    let probe = input.clone();
+    let target = npy::load(args.req("target")?, None)?;
+    let obj = match (args.flags.get("layers"), args.flags.get("train")) {
+        (Some(layers), _) => GraphMseObjective::new(reg, graph, input, target.data, &parse_layers("layers", layers)?)?,
+        (None, Some(train)) => {
+            let layers: Vec<LayerRef> = parse_layers("train", train)?
+                .into_iter()
+                .map(|(layer_type, id)| LayerRef { layer_type, id })
+                .collect();
+            reg.train_only(&layers)?;
+            GraphMseObjective::trainable(reg, graph, input, target.data)
+        }
+        (None, None) => return Err("es-graph needs --layers or --train".into()),
+    };
+    let mut es = es_from_args(args, obj.dim())?;
+    // mulai dari bobot yang dimuat, bukan mean acak strategi
+    es.set_mean(&obj.initial_params()?)?;
+    let gens: u32 = args.opt("gens")?.unwrap_or(50);
+    eprintln!("es-graph: dim={} gens={} threads={}", obj.dim(), gens, burn_research::thread_count());
+    for g in 0..gens {
+        let report = es.step(&obj);
+        if g + 1 == gens || (g + 1) % 10 == 0 {
+            println!("{}", report);
+        }
+    }
+    let best = es.best();
+    print_summary("best", &[best.len()], &best);
+    let Some(path) = args.flags.get("out") else {
+        return Ok(());
+    };
+    // belum ada kandidat valid (mis. --gens 0) -> simpan bobot awal apa adanya
+    if !best.is_empty() {
+        obj.apply(&best)?;
+    }
+    let reg = obj.into_registry()?;
+    let bytes = if path.ends_with(".bmodel") {
+        let mut signature = source.signature.clone();
+        if signature.input.is_empty() {
+            // signature dari input latih: shape output diambil dari satu forward
+            let graph = if source.ids { reg.compile_graph_ids(&source.bytes)? } else { reg.compile_graph(&source.bytes)? };
+            signature.output = graph.run(&reg, &probe)?.shape();
+            signature.input = probe.shape();
+        }
+        single_graph_model(&source, args.flags.get("graph").map_or("main", String::as_str), &signature)?.build(&reg)?
+    } else {
+        reg.snapshot_packets()?
+    };
+    std::fs::write(path, &bytes).map_err(|e| format!("{}: {}", path, e))?;
+    eprintln!("es-graph: best weights, {} bytes -> {}", bytes.len(), path);
+    Ok(())
+}
+
+fn cmd_pack(args: &Args) -> Result<(), String> {
+    let (reg, graph, source) = load_graph_source(args)?;
+    // tanpa --shape: signature dari --model (kalau ada), selain itu kosong = tidak diketahui
+    let signature = match args.shape()? {
+        Some(input) => {
+            let output = graph.output_shape(&reg, &input).map_err(|e| format!("pack: output shape: {}", e))?;
+            GraphSignature { input, output }
+        }
+        None => source.signature.clone(),
+    };
+    let mut builder = single_graph_model(&source, args.flags.get("name").map_or("main", String::as_str), &signature)?;
+    if let Some(meta) = args.flags.get("meta") {
+        for kv in meta.split(',') {
+            let (k, v) = kv.split_once('=').ok_or_else(|| format!("--meta: expected key=value, got {:?}", kv))?;
+            builder.set_meta(k, v);
+        }
+    }
+    let bytes = builder.build(&reg)?;
+    let path = args.req("out")?;
+    std::fs::write(path, &bytes).map_err(|e| format!("{}: {}", path, e))?;
+    eprintln!("pack: {} bytes -> {}", bytes.len(), path);
+    Ok(())
+}
+
+/// ModelBuilder berisi satu graph dari plan asal (format ids dipertahankan).
+fn single_graph_model(source: &PlanSource, name: &str, signature: &GraphSignature) -> Result<ModelBuilder, String> {
+    let mut builder = ModelBuilder::new();
+    if source.ids {
+        builder.add_graph_ids(name, &source.bytes, &signature.input, &signature.output)?;
+    } else {
+        builder.add_graph(name, &source.bytes, &signature.input, &signature.output)?;
+    }
+    Ok(builder)
+}
+
+/// "1:1,3:2" -> [(tipe, id)].
+fn parse_layers(flag: &str, v: &str) -> Result<Vec<(u8, u32)>, String> {
+    v.split(',')
+        .map(|s| {
+            let (lt, id) = s.split_once(':').ok_or_else(|| format!("--{}: expected type:id, got {:?}", flag, s))?;
+            let lt = parse_u8(lt).ok_or_else(|| format!("--{}: invalid type {:?}", flag, lt))?;
+            let id = id.parse::<u32>().map_err(|_| format!("--{}: invalid id {:?}", flag, id))?;
+            Ok((lt, id))
+        })
+        .collect()
+}
+
+/// "0x11" atau "17".
+fn parse_u8(s: &str) -> Option<u8> {
+    match s.strip_prefix("0x") {
+        Some(hex) => u8::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+fn main() -> ExitCode {
+    let raw: Vec<String> = std::env::args().skip(1).collect();
+    let Some((cmd, rest)) = raw.split_first() else {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    };
+    let result = Args::parse(rest).and_then(|args| match cmd.as_str() {
+        "forward" => cmd_forward(&args),
+        "shape" => cmd_shape(&args),
+        "debug" => cmd_debug(&args),
+        "es-demo" => cmd_es_demo(&args),
+        "es-graph" => cmd_es_graph(&args),
+        "pack" => cmd_pack(&args),
+        "caps" => {
+            println!("{}", burn_research::protocol::capabilities());
+            Ok(())
+        }
+        _ => Err(format!("unknown command {:?}\n{}", cmd, USAGE)),
+    });
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
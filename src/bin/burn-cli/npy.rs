@@ -0,0 +1,96 @@
+// Pembaca tensor dari file: .npy (dtype <f4 / <f8, C-order) atau raw f32 little-endian.
+
+use std::path::Path;
+
+pub struct Loaded {
+    pub data: Vec<f32>,
+    pub shape: Vec<usize>,
+}
+
+pub fn is_npy(path: &str) -> bool {
+    Path::new(path).extension().is_some_and(|e| e == "npy")
+}
+
+/// `.npy` -> shape dari header; file lain -> raw f32 LE ber-shape 1D `[n]`.
+/// `shape` (kalau ada) menimpa shape hasil baca selama numel sama.
+pub fn load(path: &str, shape: Option<&[usize]>) -> Result<Loaded, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+    let mut t = if is_npy(path) {
+        parse_npy(&bytes).map_err(|e| format!("{}: {}", path, e))?
+    } else {
+        if bytes.len() % 4 != 0 {
+            return Err(format!("{}: raw f32 file length {} is not a multiple of 4", path, bytes.len()));
+        }
+        let data: Vec<f32> = bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        let shape = vec![data.len()];
+        Loaded { data, shape }
+    };
+    if let Some(s) = shape {
+        if s.iter().product::<usize>() != t.data.len() {
+            return Err(format!("{}: --shape {:?} does not match {} values", path, s, t.data.len()));
+        }
+        t.shape = s.to_vec();
+    }
+    Ok(t)
+}
+
+pub fn parse_npy(bytes: &[u8]) -> Result<Loaded, String> {
+    if bytes.len() < 10 || &bytes[..6] != b"\x93NUMPY" {
+        return Err("not an .npy file (bad magic)".into());
+    }
+    let (header_len, start) = match bytes[6] {
+        1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10),
+        2 | 3 if bytes.len() >= 12 => {
+            (u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize, 12)
+        }
+        v => return Err(format!("unsupported .npy version {}", v)),
+    };
+    let header = bytes
+        .get(start..start + header_len)
+        .ok_or("truncated .npy header")?;
+    let header = std::str::from_utf8(header).map_err(|e| e.to_string())?;
+    let descr = dict_value(header, "descr").ok_or("missing 'descr'")?;
+    if dict_value(header, "fortran_order").is_some_and(|v| v.starts_with("True")) {
+        return Err("fortran_order arrays are not supported".into());
+    }
+    let shape_src = dict_value(header, "shape").ok_or("missing 'shape'")?;
+    let inner = shape_src
+        .trim_start_matches('(')
+        .split(')')
+        .next()
+        .unwrap_or("");
+    let shape: Vec<usize> = inner
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<usize>().map_err(|e| format!("bad shape {:?}: {}", shape_src, e)))
+        .collect::<Result<_, _>>()?;
+    let numel: usize = shape.iter().product();
+    let body = &bytes[start + header_len..];
+    let data: Vec<f32> = if descr.starts_with("'<f4'") {
+        body.chunks_exact(4)
+            .take(numel)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect()
+    } else if descr.starts_with("'<f8'") {
+        body.chunks_exact(8)
+            .take(numel)
+            .map(|b| f64::from_le_bytes(b.try_into().unwrap()) as f32)
+            .collect()
+    } else {
+        return Err(format!("unsupported dtype {} (need <f4 or <f8)", descr.split(',').next().unwrap_or("")));
+    };
+    if data.len() != numel {
+        return Err(format!("expected {} values for shape {:?}, file has {}", numel, shape, data.len()));
+    }
+    Ok(Loaded { data, shape })
+}
+
+/// Nilai mentah setelah `'key':` di dict header .npy (sisa string, sudah di-trim).
+fn dict_value<'a>(header: &'a str, key: &str) -> Option<&'a str> {
+    let pat = format!("'{}':", key);
+    header.find(&pat).map(|i| header[i + pat.len()..].trim_start())
+}
@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+    use crate::npy::parse_npy;
+
+    /// File .npy v1 dengan dict header apa adanya + body mentah.
+    fn npy_bytes(dict: &str, body: &[u8]) -> Vec<u8> {
+        let mut header = dict.to_string();
+        while (10 + header.len() + 1) % 16 != 0 {
+            header.push(' ');
+        }
+        header.push('\n');
+        let mut out = b"\x93NUMPY\x01\x00".to_vec();
+        out.extend((header.len() as u16).to_le_bytes());
+        out.extend(header.as_bytes());
+        out.extend(body);
+        out
+    }
+
+    fn f32_body(v: &[f32]) -> Vec<u8> {
+        v.iter().flat_map(|x| x.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn npy_parses_c_order_f32() {
+        let vals = [1.0f32, -2.5, 3.0, 0.25, 5.0, 6.0];
+        let bytes = npy_bytes(
+            "{'descr': '<f4', 'fortran_order': False, 'shape': (2, 3), }",
+            &f32_body(&vals),
+        );
+        let t = parse_npy(&bytes).unwrap();
+        assert_eq!(t.shape, vec![2, 3]);
+        assert_eq!(t.data, vals.to_vec());
+    }
+
+    #[test]
+    fn npy_rejects_fortran_order_and_wrong_dtype() {
+        let body = f32_body(&[1.0; 4]);
+        let err = parse_npy(&npy_bytes("{'descr': '<f4', 'fortran_order': True, 'shape': (2, 2), }", &body))
+            .err()
+            .unwrap();
+        assert!(err.contains("fortran_order"), "{}", err);
+        let err = parse_npy(&npy_bytes("{'descr': '<i4', 'fortran_order': False, 'shape': (2, 2), }", &body))
+            .err()
+            .unwrap();
+        assert!(err.contains("unsupported dtype '<i4'"), "{}", err);
+        let short = npy_bytes("{'descr': '<f4', 'fortran_order': False, 'shape': (3, 2), }", &body);
+        assert!(parse_npy(&short).is_err());
+    }
+}
@@ -1,6 +1,11 @@
 // Objective = "seberapa bagus satu vektor bobot". ES tidak peduli objective-nya apa.
 // Slice ini: objective bawaan plain-Rust (MSE linear) untuk proof-of-life & test.
-// Slice compose nanti: impl Objective yang menjalankan graph burn-mu (lihat peta di akhir).
+// GraphMseObjective: objective yang menjalankan CompiledGraph atas LayerRegistry.
+
+use std::sync::Mutex;
+use crate::graph::CompiledGraph;
+use crate::registry::LayerRegistry;
+use crate::WasmTensor;
 
 pub trait Objective {
     /// Lebih besar = lebih baik (ES memaksimalkan).
@@ -45,3 +50,111 @@ impl Objective for LinearMseObjective {
         -(mse / count as f64) // negasi: ES memaksimalkan
     }
               }
+
+/// fitness = -MSE(graph(input), target) dengan bobot kandidat ditulis ke layer `params`
+/// (urutan = urutan flat kandidat; tiap layer lewat setWeightsFlat), atau ke mask
+/// trainable registry (GraphMseObjective::trainable, lewat setTrainableFlat).
+/// Evaluasi serial: satu registry di balik Mutex dikunci selama tulis bobot + forward, jadi
+/// evaluate() (rayon) aman tapi kandidat tetap dihitung satu per satu. Registry tidak Clone,
+/// sehingga tidak ada salinan per worker; paralelisme hanya ada di dalam forward burn.
+pub struct GraphMseObjective {
+    registry: Mutex<LayerRegistry>,
+    graph: CompiledGraph,
+    input: WasmTensor,
+    target: Vec<f32>,
+    params: Vec<(u8, u32, usize)>, // (layer_type, layer_id, panjang flat)
+    /// Some(dim) = kandidat mengikuti trainableLayout, `params` kosong.
+    trainable: Option<usize>,
+}
+
+impl GraphMseObjective {
+    pub fn new(
+        registry: LayerRegistry,
+        graph: CompiledGraph,
+        input: WasmTensor,
+        target: Vec<f32>,
+        layers: &[(u8, u32)],
+    ) -> Result<Self, String> {
+        let mut params = Vec::with_capacity(layers.len());
+        for &(lt, id) in layers {
+            params.push((lt, id, registry.get_weights_flat(id, lt)?.len()));
+        }
+        Ok(Self { registry: Mutex::new(registry), graph, input, target, params, trainable: None })
+    }
+
+    /// Kandidat = semua segmen trainable registry (setTrainable/train_only dulu untuk membekukan backbone).
+    pub fn trainable(registry: LayerRegistry, graph: CompiledGraph, input: WasmTensor, target: Vec<f32>) -> Self {
+        let dim = registry.trainable_len();
+        Self { registry: Mutex::new(registry), graph, input, target, params: Vec::new(), trainable: Some(dim) }
+    }
+
+    /// Dimensi vektor kandidat (total bobot semua layer).
+    pub fn dim(&self) -> usize {
+        self.trainable.unwrap_or_else(|| self.params.iter().map(|p| p.2).sum())
+    }
+
+    /// Bobot registry saat ini, flat (titik awal ES).
+    pub fn initial_params(&self) -> Result<Vec<f32>, String> {
+        let reg = self.registry.lock().map_err(|e| e.to_string())?;
+        if self.trainable.is_some() {
+            return reg.get_trainable_flat();
+        }
+        let mut out = Vec::with_capacity(self.dim());
+        for &(lt, id, _) in &self.params {
+            out.extend(reg.get_weights_flat(id, lt)?);
+        }
+        Ok(out)
+    }
+
+    /// Tulis kandidat `w` ke registry (mis. hasil es.best() sebelum disimpan).
+    pub fn apply(&self, w: &[f32]) -> Result<(), String> {
+        if w.len() != self.dim() {
+            return Err(format!("candidate has {} values, objective dim {}", w.len(), self.dim()));
+        }
+        let mut reg = self.registry.lock().map_err(|e| e.to_string())?;
+        write_candidate(&mut reg, self.trainable.is_some(), &self.params, w)
+    }
+
+    /// Registry beserta bobot terakhir yang ditulis (pakai apply(best) dulu).
+    pub fn into_registry(self) -> Result<LayerRegistry, String> {
+        self.registry.into_inner().map_err(|e| e.to_string())
+    }
+
+    fn try_fitness(&self, w: &[f32]) -> Result<f64, String> {
+        let mut reg = self.registry.lock().map_err(|e| e.to_string())?;
+        write_candidate(&mut reg, self.trainable.is_some(), &self.params, w)?;
+        let pred = self.graph.run(&reg, &self.input)?.to_array();
+        if pred.len() != self.target.len() {
+            return Err(format!("graph output has {} values, target {}", pred.len(), self.target.len()));
+        }
+        let mse = pred
+            .iter()
+            .zip(&self.target)
+            .map(|(&p, &t)| (p as f64 - t as f64).powi(2))
+            .sum::<f64>()
+            / pred.len().max(1) as f64;
+        Ok(-mse)
+    }
+}
+
+fn write_candidate(reg: &mut LayerRegistry, trainable: bool, params: &[(u8, u32, usize)], w: &[f32]) -> Result<(), String> {
+    if trainable {
+        reg.set_trainable_flat(w)?;
+    }
+    let mut pos = 0;
+    for &(lt, id, len) in params {
+        reg.set_weights_flat(id, lt, &w[pos..pos + len])?;
+        pos += len;
+    }
+    Ok(())
+}
+
+impl Objective for GraphMseObjective {
+    fn fitness(&self, w: &[f32]) -> f64 {
+        if w.len() != self.dim() {
+            return f64::NEG_INFINITY;
+        }
+        self.try_fitness(w).unwrap_or(f64::NEG_INFINITY)
+    }
+}
+
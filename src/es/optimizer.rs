@@ -1,3 +1,4 @@
+use rayon::prelude::*;
 use wasm_bindgen::prelude::*;
 use super::diag::{diversity, mean_std, EsReport};
 use super::objective::{LinearMseObjective, Objective};
@@ -55,6 +56,16 @@ impl EsOptimizer {
     #[wasm_bindgen(js_name = dim)]
     pub fn dim(&self) -> u32 { self.dim as u32 }
 
+    /// Mulai dari titik tertentu (mis. getTrainableFlat setelah initWeights), bukan mean acak default.
+    #[wasm_bindgen(js_name = setMean)]
+    pub fn set_mean(&mut self, mean: &[f32]) -> Result<(), String> {
+        if mean.len() != self.dim {
+            return Err(format!("setMean: expected {} floats, got {}", self.dim, mean.len()));
+        }
+        self.strategy.set_mean(mean);
+        Ok(())
+    }
+
     #[wasm_bindgen(js_name = generation)]
     pub fn generation(&self) -> u32 { self.gen }
 
@@ -168,15 +179,27 @@ impl EsOptimizer {
         let obj = LinearMseObjective::new(x, y, n, in_dim, out_dim);
 
         for _ in 0..gens {
-            let flat = self.ask();
-            let nb = self.batch_size() as usize;
-            let mut f = Vec::with_capacity(nb);
-            for i in 0..nb {
-                let cand = &flat[i * self.dim..(i + 1) * self.dim];
-                f.push(obj.fitness(cand) as f32);
-            }
-            let _ = self.tell(&f);
+            let _ = self.step(&obj);
         }
         self.report()
     }
+}
+
+impl EsOptimizer {
+    /// Fitness kandidat `ask()` terakhir, dievaluasi paralel (pool rayon).
+    /// Urutan hasil = urutan kandidat, jadi deterministik terlepas dari jumlah thread.
+    /// Objective yang mengunci state bersama (GraphMseObjective) tetap berjalan serial.
+    pub fn evaluate<O: Objective + Sync + ?Sized>(&self, obj: &O) -> Vec<f32> {
+        self.last_candidates
+            .par_iter()
+            .map(|c| obj.fitness(c) as f32)
+            .collect()
+    }
+
+    /// Satu generasi penuh: ask -> evaluate (paralel) -> tell. Mengembalikan laporan JSON.
+    pub fn step<O: Objective + Sync + ?Sized>(&mut self, obj: &O) -> String {
+        self.ask();
+        let f = self.evaluate(obj);
+        self.tell(&f)
+    }
       }
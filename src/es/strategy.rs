@@ -1,4 +1,13 @@
 use super::rng::Rng;
+use crate::layers::init::{InitScheme, WeightShape};
+
+/// Titik awal default (tanpa setMean): mean OpenEs ~ N(0, 0.1²), tiap parent MuLambda ~ N(0, 0.5²).
+pub const OPENES_MEAN_INIT: InitScheme = InitScheme::Normal { std: 0.1 };
+pub const MU_LAMBDA_PARENT_INIT: InitScheme = InitScheme::Normal { std: 0.5 };
+
+fn init_vec(scheme: InitScheme, dim: usize, rng: &mut Rng) -> Vec<f32> {
+    scheme.fill(rng, &WeightShape::vector(dim)).unwrap_or_else(|| vec![0.0; dim])
+}
 
 pub trait EsStrategy {
     fn name(&self) -> &'static str;
@@ -8,6 +17,8 @@ pub trait EsStrategy {
     fn ask(&mut self, rng: &mut Rng) -> Vec<Vec<f32>>;
     fn tell(&mut self, fitness: &[f64]);
     fn mean(&self) -> Vec<f32>;
+    /// Ganti titik awal (mis. bobot layer hasil initWeights); panjang = dim.
+    fn set_mean(&mut self, mean: &[f32]);
 }
 
 // ---------------- OpenES (antithetic Gaussian, centered fitness shaping) ----------------
@@ -22,7 +33,7 @@ pub struct OpenEs {
 
 impl OpenEs {
     pub fn new(dim: usize, half: usize, sigma: f32, lr: f32, rng: &mut Rng) -> Self {
-        let mean = (0..dim).map(|_| rng.gaussian() * 0.1).collect();
+        let mean = init_vec(OPENES_MEAN_INIT, dim, rng);
         Self { dim, half, sigma, lr, mean, eps: Vec::new() }
     }
 }
@@ -62,6 +73,8 @@ impl EsStrategy for OpenEs {
     }
 
     fn mean(&self) -> Vec<f32> { self.mean.clone() }
+
+    fn set_mean(&mut self, mean: &[f32]) { self.mean = mean.to_vec(); }
 }
 
 // ---------------- (mu, lambda) elitist Gaussian mutation ----------------
@@ -76,9 +89,7 @@ pub struct MuLambda {
 
 impl MuLambda {
     pub fn new(dim: usize, mu: usize, lambda: usize, sigma: f32, rng: &mut Rng) -> Self {
-        let parents = (0..mu)
-            .map(|_| (0..dim).map(|_| rng.gaussian() * 0.5).collect())
-            .collect();
+        let parents = (0..mu).map(|_| init_vec(MU_LAMBDA_PARENT_INIT, dim, rng)).collect();
         Self { dim, mu, lambda, sigma, parents, last_children: Vec::new() }
     }
 }
@@ -115,6 +126,10 @@ impl EsStrategy for MuLambda {
         for d in 0..self.dim { m[d] /= self.parents.len() as f64; }
         m.into_iter().map(|v| v as f32).collect()
     }
+
+    fn set_mean(&mut self, mean: &[f32]) {
+        self.parents = vec![mean.to_vec(); self.mu];
+    }
 }
 
 // ---------------- enum dispatch (wasm-safe, tanpa dyn) ----------------
@@ -140,4 +155,5 @@ impl EsStrategy for Strategy {
     fn ask(&mut self, rng: &mut Rng) -> Vec<Vec<f32>> { match self { Strategy::OpenEs(s) => s.ask(rng), Strategy::MuLambda(s) => s.ask(rng) } }
     fn tell(&mut self, fitness: &[f64]) { match self { Strategy::OpenEs(s) => s.tell(fitness), Strategy::MuLambda(s) => s.tell(fitness) } }
     fn mean(&self) -> Vec<f32> { match self { Strategy::OpenEs(s) => s.mean(), Strategy::MuLambda(s) => s.mean() } }
+    fn set_mean(&mut self, mean: &[f32]) { match self { Strategy::OpenEs(s) => s.set_mean(mean), Strategy::MuLambda(s) => s.set_mean(mean) } }
         }
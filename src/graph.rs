@@ -1,97 +1,271 @@
 use wasm_bindgen::prelude::*;
-use crate::protocol::{PayloadCursor, LAYER_BINARY};
+use crate::protocol::*;
 use crate::registry::LayerRegistry;
-use crate::WasmTensor;
+use crate::memory::{BufferPool, MemoryView};
+use crate::layers::layout::JsonObj;
+use crate::profile::{now_us, StepProfile};
+use crate::protocol::layer_type_name;
+use crate::plan::{Plan, Step, ARITY_BINARY, ARITY_IF, ARITY_LOOP, ARITY_SELECT, ARITY_UNARY};
+use std::sync::Mutex;
+use crate::{TensorView, WasmTensor};
 
-// Satu sumber kebenaran arity untuk graph + registry.
-pub(crate) const ARITY_UNARY: u8 = 1;
-pub(crate) const ARITY_BINARY: u8 = 2;
-
-const CG_MAX_SLOTS: u32 = 64;
-
-#[derive(Clone, Copy)]
-struct CompiledStep {
-    arity: u8,
-    layer_type: u8,
-    layer_id: u32,
-    in_slot: u8,
-    in_slot2: u8,
-    out_slot: u8,
+/// Nilai kondisi IF/SELECT: slot harus berisi tepat satu elemen; true kalau > 0 (NaN = false).
+fn scalar_cond(slots: &[Option<WasmTensor>], slot: u16) -> Result<bool, String> {
+    let t = slots[slot as usize].as_ref().ok_or_else(|| format!("run: empty condition slot {}", slot))?;
+    let v = t.to_array();
+    if v.len() != 1 {
+        return Err(format!("run: condition slot {} must hold a scalar, got shape {:?}", slot, t.shape()));
+    }
+    Ok(v[0] > 0.0)
 }
 
 #[wasm_bindgen]
 pub struct CompiledGraph {
-    steps: Vec<CompiledStep>,
-    num_slots: u32,
-    out_slot: u8,
+    plan: Plan,
+    /// Hasil pass fusion (build_fused), sejajar `plan.steps`; kosong = graph tanpa fusion.
+    fused: Vec<Option<FusedStep>>,
+    fused_count: u32,
+    /// Opt-in (setProfiling): tiap run menimpa `profile` dengan rekaman per step + total µs.
+    profiling: bool,
+    profile: Mutex<(Vec<StepProfile>, f64)>,
 }
 
 impl CompiledGraph {
-    fn read_step(c: &mut PayloadCursor) -> Result<CompiledStep, String> {
-        Ok(CompiledStep {
-            arity: c.read_u8()?,
-            layer_type: c.read_u8()?,
-            layer_id: c.read_u32()?,
-            in_slot: c.read_u8()?,
-            in_slot2: c.read_u8()?,
-            out_slot: c.read_u8()?,
-        })
+    pub(crate) fn build(reg: &LayerRegistry, plan: &[u8]) -> Result<CompiledGraph, String> {
+        Self::compile(reg, plan, false, "compile_graph")
     }
 
-    pub(crate) fn build(reg: &LayerRegistry, plan: &[u8]) -> Result<CompiledGraph, String> {
-        let mut c = PayloadCursor::new(plan);
-        let num_steps = c.read_u32()?;
-        let num_slots = c.read_u32()?;
-        if num_steps == 0 {
-            return Err("compile_graph: plan has no steps".into());
-        }
-        if !(1..=CG_MAX_SLOTS).contains(&num_slots) {
-            return Err(format!("compile_graph: num_slots must be 1..={}, got {}", CG_MAX_SLOTS, num_slots));
-        }
-        let mut steps: Vec<CompiledStep> = Vec::with_capacity(num_steps as usize);
-        let mut filled: u64 = 1;
-        for _ in 0..num_steps {
-            let s = Self::read_step(&mut c)?;
-            let in_slot = s.in_slot as u32;
-            let in_slot2 = s.in_slot2 as u32;
-            let out_slot = s.out_slot as u32;
-            if in_slot >= num_slots || in_slot2 >= num_slots || out_slot >= num_slots {
-                return Err(format!("compile_graph: slot index out of range (num_slots={})", num_slots));
-            }
-            if s.arity == ARITY_BINARY {
-                if s.layer_type != LAYER_BINARY {
-                    return Err(format!("compile_graph: arity 2 requires LAYER_BINARY, got 0x{:02X}", s.layer_type));
+    pub(crate) fn build_ids(reg: &LayerRegistry, plan: &[u8]) -> Result<CompiledGraph, String> {
+        Self::compile(reg, plan, true, "compile_graph")
+    }
+
+    /// Parse + validasi + reuse slot; `ctx` = prefix error entry point (compile_graph/run_graph).
+    pub(crate) fn compile(reg: &LayerRegistry, plan: &[u8], by_id: bool, ctx: &str) -> Result<CompiledGraph, String> {
+        let mut plan = Plan::parse(reg, plan, by_id).map_err(|e| format!("{}: {}", ctx, e))?;
+        plan.reuse_slots();
+        Ok(Self::from_plan(plan))
+    }
+
+    fn from_plan(plan: Plan) -> CompiledGraph {
+        CompiledGraph {
+            plan,
+            fused: Vec::new(),
+            fused_count: 0,
+            profiling: false,
+            profile: Mutex::new((Vec::new(), 0.0)),
+        }
+    }
+
+    /// Eksekusi `steps[lo..hi]` termasuk control flow; step layer diserahkan ke `layer_step`
+    /// (yang menulis slot output-nya). `Ok(false)` = dihentikan oleh `layer_step`.
+    fn walk<F>(&self, lo: usize, hi: usize, slots: &mut [Option<WasmTensor>], layer_step: &mut F) -> Result<bool, String>
+    where
+        F: FnMut(usize, &Step, &mut [Option<WasmTensor>]) -> Result<bool, String>,
+    {
+        let mut i = lo;
+        while i < hi {
+            let s = &self.plan.steps[i];
+            let (a, b) = s.block_lens();
+            let end = i + 1 + a + b;
+            let go_on = match s.arity {
+                ARITY_LOOP => {
+                    let mut go_on = true;
+                    for _ in 0..s.layer_id {
+                        go_on = self.walk(i + 1, end, slots, layer_step)?;
+                        if !go_on {
+                            break;
+                        }
+                    }
+                    go_on
+                }
+                ARITY_IF if scalar_cond(slots, s.cond)? => self.walk(i + 1, i + 1 + a, slots, layer_step)?,
+                ARITY_IF => self.walk(i + 1 + a, end, slots, layer_step)?,
+                ARITY_SELECT => {
+                    let pick = if scalar_cond(slots, s.cond)? { s.in_slot } else { s.in_slot2 };
+                    let t = slots[pick as usize].clone().ok_or_else(|| format!("run: empty input slot {}", pick))?;
+                    slots[s.out_slot as usize] = Some(t);
+                    true
                 }
-                if (filled >> in_slot) & 1 == 0 {
-                    return Err(format!("compile_graph: input slot {} is empty", in_slot));
+                _ => layer_step(i, s, slots)?,
+            };
+            if !go_on {
+                return Ok(false);
+            }
+            i = end;
+        }
+        Ok(true)
+    }
+
+    /// Versi shape dari `walk` atas slot plan (`plan_in`/`plan_out`, sebelum reuse slot): kedua
+    /// cabang IF ditelusuri dan harus sepakat soal shape slot yang ditulis keduanya; SELECT butuh
+    /// a dan b ber-shape sama.
+    fn shape_range(
+        &self,
+        registry: &LayerRegistry,
+        lo: usize,
+        hi: usize,
+        slots: &mut Vec<Option<Vec<usize>>>,
+    ) -> Result<(), String> {
+        let mut i = lo;
+        while i < hi {
+            let s = &self.plan.steps[i];
+            let (a, b) = s.block_lens();
+            let end = i + 1 + a + b;
+            let (in1, in2, _) = s.plan_in;
+            let slot = |slots: &[Option<Vec<usize>>], k: u16| {
+                slots[k as usize].clone().ok_or_else(|| format!("outputShape: empty input slot {}", k))
+            };
+            match s.arity {
+                ARITY_LOOP => {
+                    // Berhenti lebih awal kalau shape semua slot sudah tetap.
+                    for _ in 0..s.layer_id {
+                        let before = slots.clone();
+                        self.shape_range(registry, i + 1, end, slots)?;
+                        if *slots == before {
+                            break;
+                        }
+                    }
                 }
-                if (filled >> in_slot2) & 1 == 0 {
-                    return Err(format!("compile_graph: input slot {} is empty", in_slot2));
+                ARITY_IF => {
+                    let mut other = slots.clone();
+                    self.shape_range(registry, i + 1, i + 1 + a, slots)?;
+                    self.shape_range(registry, i + 1 + a, end, &mut other)?;
+                    for (k, (x, y)) in slots.iter_mut().zip(other).enumerate() {
+                        match (x.as_ref(), y) {
+                            (Some(p), Some(q)) if *p != q => {
+                                return Err(format!(
+                                    "outputShape: step {}: branches disagree on slot {} ({:?} vs {:?})",
+                                    i, k, p, q
+                                ));
+                            }
+                            (Some(_), None) => *x = None,
+                            _ => {}
+                        }
+                    }
                 }
-            } else if s.arity == ARITY_UNARY {
-                if s.layer_type == LAYER_BINARY {
-                    return Err("compile_graph: arity 1 cannot use LAYER_BINARY (needs 2 inputs)".into());
+                ARITY_SELECT => {
+                    let (p, q) = (slot(slots, in1)?, slot(slots, in2)?);
+                    if p != q {
+                        return Err(format!("outputShape: step {}: select inputs differ ({:?} vs {:?})", i, p, q));
+                    }
+                    slots[s.plan_out as usize] = Some(p);
                 }
-                if (filled >> in_slot) & 1 == 0 {
-                    return Err(format!("compile_graph: input slot {} is empty", in_slot));
+                _ => {
+                    let x = slot(slots, in1)?;
+                    let out = if s.arity == ARITY_BINARY {
+                        registry.binary_output_shape(s.layer_id, &x, &slot(slots, in2)?)
+                    } else {
+                        registry.output_shape(s.layer_id, s.layer_type, &x)
+                    }
+                    .map_err(|e| format!("outputShape: step {}: {}", i, e))?;
+                    slots[s.plan_out as usize] = Some(out);
                 }
-            } else {
-                return Err(format!("compile_graph: invalid arity {} (expected 1 or 2)", s.arity));
             }
-            if !reg.layer_exists(s.layer_type, s.layer_id) {
-                return Err(format!("compile_graph: layer type 0x{:02X} id {} not found", s.layer_type, s.layer_id));
+            i = end;
+        }
+        Ok(())
+    }
+
+    fn exec_step(
+        &self,
+        registry: &LayerRegistry,
+        i: usize,
+        s: &Step,
+        slots: &[Option<WasmTensor>],
+    ) -> Result<WasmTensor, String> {
+        let slot = |i: u16| slots[i as usize].as_ref().ok_or_else(|| format!("run: empty input slot {}", i));
+        let Some(f) = self.fused.get(i).and_then(Option::as_ref) else {
+            return if s.arity == ARITY_BINARY {
+                registry.forward_binary_layer(s.layer_id, slot(s.in_slot)?, slot(s.in_slot2)?)
+            } else {
+                registry.forward_layer(s.layer_id, s.layer_type, slot(s.in_slot)?)
+            };
+        };
+        let mut out = match &f.layer {
+            FusedLayer::Registry if s.arity == ARITY_BINARY => {
+                registry.forward_binary_layer(s.layer_id, slot(s.in_slot)?, slot(s.in_slot2)?)?
             }
-            filled |= 1u64 << out_slot;
-            steps.push(s);
+            FusedLayer::Registry => registry.forward_layer(s.layer_id, s.layer_type, slot(s.in_slot)?)?,
+            FusedLayer::Linear(l) => l.lock().unwrap_or_else(|e| e.into_inner()).forward(slot(s.in_slot)?),
+            FusedLayer::Conv(c) => c.lock().unwrap_or_else(|e| e.into_inner()).forward(slot(s.in_slot)?)?,
+        };
+        if let Some(b) = &f.bias {
+            out = WasmTensor { inner: out.inner + b.clone() };
         }
-        let out_slot = c.read_u8()? as u32;
-        if out_slot >= num_slots {
-            return Err(format!("compile_graph: output slot {} out of range", out_slot));
+        for &id in &f.post {
+            out = registry.forward_layer(id, LAYER_ACTIVATION, &out)?;
         }
-        if (filled >> out_slot) & 1 == 0 {
-            return Err(format!("compile_graph: output slot {} is never written", out_slot));
+        Ok(out)
+    }
+
+    /// Mutex (bukan RefCell) supaya graph tetap Sync untuk evaluasi ES paralel.
+    fn profile_lock(&self) -> std::sync::MutexGuard<'_, (Vec<StepProfile>, f64)> {
+        self.profile.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn record_step(
+        &self,
+        registry: &LayerRegistry,
+        step: usize,
+        s: &Step,
+        slots: &[Option<WasmTensor>],
+        out: &WasmTensor,
+        start_us: f64,
+        time_us: f64,
+    ) -> StepProfile {
+        let shape_of = |slot: u16| slots[slot as usize].as_ref().map(|t| t.shape()).unwrap_or_default();
+        let mut inputs = vec![shape_of(s.in_slot)];
+        if s.arity == ARITY_BINARY {
+            inputs.push(shape_of(s.in_slot2));
+        }
+        let output = out.shape();
+        let refs: Vec<&[usize]> = inputs.iter().map(Vec::as_slice).collect();
+        StepProfile {
+            step,
+            layer_type: s.layer_type,
+            layer_id: s.layer_id,
+            flops: registry.estimate_flops(s.layer_type, s.layer_id, &refs, &output),
+            out_bytes: out.byte_length(),
+            inputs,
+            output,
+            start_us,
+            time_us,
+        }
+    }
+
+    /// `run` untuk API Rust: burn::Tensor masuk/keluar.
+    pub fn forward(&self, registry: &LayerRegistry, input: crate::api::Tensor4) -> Result<crate::api::Tensor4, String> {
+        Ok(self.run(registry, &WasmTensor { inner: input })?.inner)
+    }
+
+    /// Inti `runBatch` tanpa JS: `data` berisi `n` input ber-shape `shape` yang ditumpuk
+    /// di dim batch, dijalankan sebagai SATU forward `[n*B, C, H, W]`.
+    /// Syarat: semua step memperlakukan dim 0 sebagai batch (reshape pakai 0/-1 untuk batch).
+    pub fn run_batch_flat(
+        &self,
+        registry: &LayerRegistry,
+        data: &[f32],
+        shape: &[usize],
+        n: usize,
+    ) -> Result<WasmTensor, String> {
+        let [b, c, h, w] = crate::layers::pad_shape4(shape);
+        let per_input = b * c * h * w;
+        if n == 0 || per_input == 0 {
+            return Err(format!("runBatch: n ({}) and input shape {:?} must be non-empty", n, shape));
+        }
+        if data.len() < n * per_input {
+            return Err(format!(
+                "runBatch: need {} floats for {} inputs of shape {:?}, view has {}",
+                n * per_input, n, shape, data.len()
+            ));
+        }
+        let stacked = WasmTensor::new(&data[..n * per_input], &[n * b, c, h, w]);
+        let out = self.run(registry, &stacked)?;
+        if !out.shape()[0].is_multiple_of(n) {
+            return Err(format!("runBatch: output batch {} not divisible by n = {}", out.shape()[0], n));
         }
-        Ok(CompiledGraph { steps, num_slots, out_slot: out_slot as u8 })
+        Ok(out)
     }
 }
 
@@ -103,34 +277,428 @@ impl CompiledGraph {
         registry: &LayerRegistry,
         input: &WasmTensor,
     ) -> Result<WasmTensor, String> {
-        let mut slots: Vec<Option<WasmTensor>> = vec![None; self.num_slots as usize];
+        let mut slots: Vec<Option<WasmTensor>> = vec![None; self.plan.num_slots as usize];
         slots[0] = Some(input.clone());
-        for s in &self.steps {
-            let out = if s.arity == ARITY_BINARY {
-                let a = slots[s.in_slot as usize]
-                    .as_ref()
-                    .ok_or_else(|| format!("run: empty input slot {}", s.in_slot))?;
-                let b = slots[s.in_slot2 as usize]
-                    .as_ref()
-                    .ok_or_else(|| format!("run: empty input slot {}", s.in_slot2))?;
-                registry.forward_binary_layer(s.layer_id, a, b)?
-            } else {
-                let inp = slots[s.in_slot as usize]
-                    .as_ref()
-                    .ok_or_else(|| format!("run: empty input slot {}", s.in_slot))?;
-                registry.forward_layer(s.layer_id, s.layer_type, inp)?
-            };
+        let mut records = Vec::new();
+        let t_run = if self.profiling { now_us() } else { 0.0 };
+        self.walk(0, self.plan.steps.len(), &mut slots, &mut |i, s, slots| {
+            let t0 = if self.profiling { now_us() } else { 0.0 };
+            let out = self.exec_step(registry, i, s, slots)?;
+            if self.profiling {
+                let time_us = now_us() - t0;
+                records.push(self.record_step(registry, i, s, slots, &out, t0 - t_run, time_us));
+            }
             slots[s.out_slot as usize] = Some(out);
+            Ok(true)
+        })?;
+        if self.profiling {
+            *self.profile_lock() = (records, now_us() - t_run);
         }
-        slots[self.out_slot as usize]
+        slots[self.plan.out_slot as usize]
             .take()
-            .ok_or_else(|| format!("run: empty output slot {}", self.out_slot))
+            .ok_or_else(|| format!("run: empty output slot {}", self.plan.out_slot))
+    }
+
+    /// N input ditumpuk dalam satu view (header shape = shape SATU input, isi = N x numel).
+    /// Satu forward batched; hasil semua input di satu view baru dengan satu header
+    /// shape `[N*B', C', H', W']` (output input ke-i = potongan ke-i sepanjang dim 0).
+    #[wasm_bindgen(js_name = runBatch)]
+    pub fn run_batch(&self, registry: &LayerRegistry, inputs: &TensorView, n: usize) -> Result<TensorView, String> {
+        let mut buf = vec![0f32; inputs.len()];
+        inputs.read(&mut buf);
+        let out = self.run_batch_flat(registry, &buf, &inputs.shape(), n)?;
+        let mut view = TensorView::new(out.byte_length() / 4);
+        out.to_tensor_view(&mut view);
+        Ok(view)
+    }
+
+    /// Hot path tanpa salinan: storage `input` dipindah ke tensor (view diisi ulang dari pool),
+    /// hasil dipindah ke `output` (storage lama output kembali ke pool). Untuk batch:
+    /// tumpuk N input di satu view ber-shape `[N*B, C, H, W]`.
+    #[wasm_bindgen(js_name = runView)]
+    pub fn run_view(
+        &self,
+        registry: &LayerRegistry,
+        pool: &mut BufferPool,
+        input: &mut MemoryView,
+        output: &mut MemoryView,
+    ) -> Result<(), String> {
+        let x = input.take_tensor(pool);
+        let out = self.run(registry, &x)?;
+        output.put_tensor(out, pool)
+    }
+
+    /// Shape inference sepanjang plan tanpa forward. Layer yang belum punya
+    /// rumus shape (lihat `LayerRegistry::output_shape`) -> Err dengan indeks step.
+    #[wasm_bindgen(js_name = outputShape)]
+    pub fn output_shape(&self, registry: &LayerRegistry, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        let mut slots: Vec<Option<Vec<usize>>> = vec![None; self.plan.plan_slots as usize];
+        slots[0] = Some(input_shape.to_vec());
+        self.shape_range(registry, 0, self.plan.steps.len(), &mut slots)?;
+        slots[self.plan.plan_out as usize]
+            .take()
+            .ok_or_else(|| format!("outputShape: empty output slot {}", self.plan.plan_out))
+    }
+
+    /// Aktifkan/matikan profiler per step (default mati, tanpa overhead).
+    #[wasm_bindgen(js_name = setProfiling)]
+    pub fn set_profiling(&mut self, enabled: bool) {
+        self.profiling = enabled;
+        if !enabled {
+            *self.profile_lock() = (Vec::new(), 0.0);
+        }
+    }
+
+    /// JSON profil run terakhir (lihat profile::profile_json); steps kosong kalau belum ada.
+    #[wasm_bindgen(js_name = lastProfile)]
+    pub fn last_profile(&self) -> String {
+        let p = self.profile_lock();
+        crate::profile::profile_json(&p.0, p.1)
+    }
+
+    /// Run terakhir dalam Chrome trace event format (chrome://tracing, Perfetto).
+    #[wasm_bindgen(js_name = chromeTrace)]
+    pub fn chrome_trace(&self) -> String {
+        crate::profile::chrome_trace(&self.profile_lock().0)
     }
 
     #[wasm_bindgen(js_name = numSteps)]
-    pub fn step_count(&self) -> u32 { self.steps.len() as u32 }
+    pub fn step_count(&self) -> u32 { self.plan.steps.len() as u32 }
     #[wasm_bindgen(js_name = numSlots)]
-    pub fn slot_count(&self) -> u32 { self.num_slots }
+    pub fn slot_count(&self) -> u32 { self.plan.plan_slots }
+    /// Slot yang benar-benar dialokasikan saat run (setelah reuse berdasarkan liveness).
+    #[wasm_bindgen(js_name = physicalSlotCount)]
+    pub fn physical_slot_count(&self) -> u32 { self.plan.num_slots }
     #[wasm_bindgen(js_name = outSlot)]
-    pub fn output_slot(&self) -> u8 { self.out_slot }
+    pub fn output_slot(&self) -> u16 { self.plan.plan_out }
+}
+
+// ============================================================
+// DEBUG RUN — tangkap tiap slot (statistik, opsional tensor), stop di non-finite pertama.
+// Flag ACTIVATION_NON_FINITE sejalan dengan FITNESS_NON_FINITE di EsOptimizer::tell.
+// ============================================================
+
+/// Statistik satu tensor; min/max/mean/std hanya atas nilai finite (NaN kalau tidak ada).
+#[derive(Clone, Copy, Debug)]
+pub struct SlotStats {
+    pub count: usize,
+    pub non_finite: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub std: f64,
+}
+
+impl SlotStats {
+    pub fn of(data: &[f32]) -> SlotStats {
+        let finite: Vec<f64> = data.iter().filter(|v| v.is_finite()).map(|&v| v as f64).collect();
+        let (mean, std) = crate::es::diag::mean_std(&finite);
+        let min = finite.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = finite.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        SlotStats { count: data.len(), non_finite: data.len() - finite.len(), min, max, mean, std }
+    }
+
+    fn fields(&self, o: JsonObj) -> JsonObj {
+        o.num("count", self.count)
+            .num("non_finite", self.non_finite)
+            .float("min", self.min)
+            .float("max", self.max)
+            .float("mean", self.mean)
+            .float("std", self.std)
+    }
+}
+
+struct DebugStep {
+    step: usize,
+    layer_type: u8,
+    layer_id: u32,
+    out_slot: u16,
+    shape: Vec<usize>,
+    stats: SlotStats,
+    tensor: Option<WasmTensor>,
+}
+
+/// Hasil runDebug: report JSON per step + tensor per step (kalau keepTensors).
+#[wasm_bindgen]
+pub struct GraphDebug {
+    input: SlotStats,
+    steps: Vec<DebugStep>,
+    stopped_at: Option<usize>,
+    output: Option<WasmTensor>,
+}
+
+#[wasm_bindgen]
+impl GraphDebug {
+    /// {"completed","stopped_at","flags","offender":{step,type,name,id}|null,"input":{stats},
+    ///  "steps":[{"step","type","name","id","slot","shape",count,non_finite,min,max,mean,std}]}
+    pub fn report(&self) -> String {
+        let mut flags = Vec::new();
+        if self.input.non_finite > 0 {
+            flags.push("\"INPUT_NON_FINITE\"");
+        }
+        let offender = self.steps.iter().find(|s| s.stats.non_finite > 0);
+        if offender.is_some() {
+            flags.push("\"ACTIVATION_NON_FINITE\"");
+        }
+        let offender = offender.map_or("null".to_string(), |s| {
+            JsonObj::new()
+                .num("step", s.step)
+                .num("type", s.layer_type)
+                .str("name", layer_type_name(s.layer_type))
+                .num("id", s.layer_id)
+                .finish()
+        });
+        let steps: Vec<String> = self
+            .steps
+            .iter()
+            .map(|s| {
+                let o = JsonObj::new()
+                    .num("step", s.step)
+                    .num("type", s.layer_type)
+                    .str("name", layer_type_name(s.layer_type))
+                    .num("id", s.layer_id)
+                    .num("slot", s.out_slot)
+                    .list("shape", &s.shape);
+                s.stats.fields(o).finish()
+            })
+            .collect();
+        JsonObj::new()
+            .num("completed", self.output.is_some())
+            .opt("stopped_at", self.stopped_at)
+            .raw("flags", &format!("[{}]", flags.join(",")))
+            .raw("offender", &offender)
+            .raw("input", &self.input.fields(JsonObj::new()).finish())
+            .raw("steps", &format!("[{}]", steps.join(",")))
+            .finish()
+    }
+
+    /// Output record ke-`step` dalam urutan eksekusi (= indeks step kalau plan tanpa loop/IF;
+    /// hanya kalau runDebug dengan keepTensors).
+    #[wasm_bindgen(js_name = stepTensor)]
+    pub fn step_tensor(&self, step: usize) -> Option<WasmTensor> {
+        self.steps.get(step)?.tensor.clone()
+    }
+
+    /// Output graph; undefined kalau run berhenti di non-finite.
+    pub fn output(&self) -> Option<WasmTensor> {
+        self.output.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = stoppedAt)]
+    pub fn stopped_at(&self) -> Option<usize> {
+        self.stopped_at
+    }
+}
+
+#[wasm_bindgen]
+impl CompiledGraph {
+    /// Run dengan statistik tiap slot yang ditulis. keepTensors (default false) menyimpan
+    /// tensor tiap step; stopOnNonFinite (default true) berhenti di step pertama yang
+    /// menghasilkan NaN/Inf (input non-finite -> berhenti sebelum step 0).
+    #[wasm_bindgen(js_name = runDebug)]
+    pub fn run_debug(
+        &self,
+        registry: &LayerRegistry,
+        input: &WasmTensor,
+        keep_tensors: Option<bool>,
+        stop_on_non_finite: Option<bool>,
+    ) -> Result<GraphDebug, String> {
+        let keep = keep_tensors.unwrap_or(false);
+        let stop = stop_on_non_finite.unwrap_or(true);
+        let mut dbg = GraphDebug {
+            input: SlotStats::of(&input.to_array()),
+            steps: Vec::with_capacity(self.plan.steps.len()),
+            stopped_at: None,
+            output: None,
+        };
+        if stop && dbg.input.non_finite > 0 {
+            return Ok(dbg);
+        }
+        let mut slots: Vec<Option<WasmTensor>> = vec![None; self.plan.num_slots as usize];
+        slots[0] = Some(input.clone());
+        let completed = self.walk(0, self.plan.steps.len(), &mut slots, &mut |i, s, slots| {
+            let out = self.exec_step(registry, i, s, slots).map_err(|e| format!("runDebug: step {}: {}", i, e))?;
+            let stats = SlotStats::of(&out.to_array());
+            dbg.steps.push(DebugStep {
+                step: i,
+                layer_type: s.layer_type,
+                layer_id: s.layer_id,
+                out_slot: s.plan_out,
+                shape: out.shape(),
+                stats,
+                tensor: if keep { Some(out.clone()) } else { None },
+            });
+            slots[s.out_slot as usize] = Some(out);
+            if stop && stats.non_finite > 0 {
+                dbg.stopped_at = Some(i);
+                return Ok(false);
+            }
+            Ok(true)
+        })?;
+        if completed {
+            dbg.output = slots[self.plan.out_slot as usize].take();
+        }
+        Ok(dbg)
+    }
+}
+
+// ============================================================
+// FUSION — pass opsional saat build (compileGraphFused):
+//   conv/linear -> BatchNorm (eval) : BN di-fold ke salinan weight/bias producer
+//   producer    -> aktivasi elementwise : aktivasi dijalankan di step producer (slot antara hilang)
+// Syarat: slot antara ditulis sekali, dibaca tepat satu step, bukan output graph, dan slot
+// tujuan consumer tidak disentuh step di antaranya. Bobot hasil fold = snapshot saat compile
+// -> compile ulang setelah setWeightsFlat/loadLayerState pada layer yang di-fold.
+// ============================================================
+
+/// Aktivasi elementwise tanpa parameter (shape tetap) yang boleh menempel di producer.
+const FUSABLE_ACTIVATIONS: [u8; 9] = [
+    ACT_GELU, ACT_RELU, ACT_SIGMOID, ACT_TANH, ACT_HARDSWISH, ACT_LEAKYRELU, ACT_HARDSIGMOID,
+    ACT_SOFTPLUS, ACT_MISH,
+];
+
+enum FusedLayer {
+    /// Layer registry apa adanya (hanya aktivasi yang ditempel).
+    Registry,
+    // Mutex: Param burn tidak Sync, sedangkan graph harus Sync (evaluasi ES paralel).
+    Linear(Mutex<crate::layers::linear::WasmLinear>),
+    Conv(Mutex<crate::layers::conv::WasmConv>),
+}
+
+struct FusedStep {
+    layer: FusedLayer,
+    /// Shift BN untuk producer tanpa bias, [1, C, 1, 1].
+    bias: Option<crate::api::Tensor4>,
+    folded_bn: Option<u32>,
+    /// Id LAYER_ACTIVATION yang dijalankan berurutan setelah producer.
+    post: Vec<u32>,
+}
+
+impl CompiledGraph {
+    pub(crate) fn build_fused(reg: &LayerRegistry, plan: &[u8]) -> Result<CompiledGraph, String> {
+        // Fusion butuh slot plan asli (satu penulis per slot) -> reuse slot setelahnya.
+        let plan = Plan::parse(reg, plan, false).map_err(|e| format!("compile_graph: {}", e))?;
+        let mut g = Self::from_plan(plan);
+        g.fuse(reg);
+        g.plan.reuse_slots();
+        Ok(g)
+    }
+
+    /// Satu-satunya step (indeks) yang membaca output step `i`, kalau pola fusion aman.
+    fn sole_consumer(&self, i: usize) -> Option<usize> {
+        let p = self.plan.steps[i].out_slot;
+        if p == 0 || p == self.plan.out_slot {
+            return None;
+        }
+        let reads = |s: &Step, slot: u16| s.in_slot == slot || (s.arity == ARITY_BINARY && s.in_slot2 == slot);
+        let readers: Vec<usize> = (0..self.plan.steps.len()).filter(|&k| reads(&self.plan.steps[k], p)).collect();
+        let writers = self.plan.steps.iter().filter(|s| s.out_slot == p).count();
+        let [j] = readers[..] else { return None };
+        let c = &self.plan.steps[j];
+        if writers != 1 || j <= i || c.arity != ARITY_UNARY {
+            return None;
+        }
+        let between = &self.plan.steps[i + 1..j];
+        if between.iter().any(|s| s.out_slot == c.out_slot || reads(s, c.out_slot)) {
+            return None;
+        }
+        Some(j)
+    }
+
+    /// BN eval dari `norm_id` di-fold ke producer linear/conv (belum ada aktivasi/BN lain).
+    /// Hanya Conv1d/Conv2d: Conv3d dan conv transpos tidak pernah di-fold (BN tetap step sendiri).
+    fn fold_bn(reg: &LayerRegistry, p: &Step, f: Option<&FusedStep>, norm_id: u32) -> Option<FusedStep> {
+        if f.is_some() || p.arity != ARITY_UNARY {
+            return None;
+        }
+        let (scale, shift) = reg.norm_ref(norm_id)?.batch_affine()?;
+        let (layer, extra) = match p.layer_type {
+            LAYER_LINEAR => {
+                let (l, extra) = reg.linear_ref(p.layer_id)?.folded(&scale, &shift)?;
+                (FusedLayer::Linear(Mutex::new(l)), extra)
+            }
+            LAYER_CONV => {
+                let (c, extra) = reg.conv_ref(p.layer_id)?.folded(&scale, &shift)?;
+                (FusedLayer::Conv(Mutex::new(c)), extra)
+            }
+            _ => return None,
+        };
+        let bias = extra.map(|t| {
+            let n = t.len();
+            WasmTensor::new(&t, &[1, n, 1, 1]).inner
+        });
+        Some(FusedStep { layer, bias, folded_bn: Some(norm_id), post: Vec::new() })
+    }
+
+    fn fuse(&mut self, reg: &LayerRegistry) {
+        // Menghapus step menggeser panjang blok LOOP/IF -> plan ber-control-flow tidak di-fuse.
+        if self.plan.has_control_flow() {
+            return;
+        }
+        self.fused = (0..self.plan.steps.len()).map(|_| None).collect();
+        let mut i = 0;
+        while i < self.plan.steps.len() {
+            while let Some(j) = self.sole_consumer(i) {
+                let c = self.plan.steps[j];
+                let variant = reg.layer_variant(c.layer_type, c.layer_id);
+                let fused = match (c.layer_type, variant) {
+                    (LAYER_NORM, Some(NORM_BATCH)) => {
+                        Self::fold_bn(reg, &self.plan.steps[i], self.fused[i].as_ref(), c.layer_id)
+                    }
+                    (LAYER_ACTIVATION, Some(v)) if FUSABLE_ACTIVATIONS.contains(&v) => {
+                        let mut f = self.fused[i].take().unwrap_or(FusedStep {
+                            layer: FusedLayer::Registry,
+                            bias: None,
+                            folded_bn: None,
+                            post: Vec::new(),
+                        });
+                        f.post.push(c.layer_id);
+                        Some(f)
+                    }
+                    _ => None,
+                };
+                let Some(f) = fused else { break };
+                self.fused[i] = Some(f);
+                self.plan.steps[i].out_slot = c.out_slot;
+                self.plan.steps.remove(j);
+                self.fused.remove(j);
+                self.fused_count += 1;
+            }
+            i += 1;
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl CompiledGraph {
+    /// Jumlah step plan yang diserap pass fusion (0 untuk compileGraph biasa).
+    #[wasm_bindgen(js_name = fusedStepCount)]
+    pub fn fused_step_count(&self) -> u32 {
+        self.fused_count
+    }
+
+    /// JSON step hasil fusion: [{"step","type","name","id","folded_bn":id|null,"activations":[id..]}].
+    #[wasm_bindgen(js_name = fusionReport)]
+    pub fn fusion_report(&self) -> String {
+        let entries: Vec<String> = self
+            .fused
+            .iter()
+            .enumerate()
+            .filter_map(|(i, f)| {
+                let f = f.as_ref()?;
+                let s = &self.plan.steps[i];
+                Some(
+                    JsonObj::new()
+                        .num("step", i)
+                        .num("type", s.layer_type)
+                        .str("name", layer_type_name(s.layer_type))
+                        .num("id", s.layer_id)
+                        .opt("folded_bn", f.folded_bn)
+                        .list("activations", &f.post)
+                        .finish(),
+                )
+            })
+            .collect();
+        format!("[{}]", entries.join(","))
+    }
 }
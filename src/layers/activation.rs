@@ -125,6 +125,28 @@ impl<B: Backend> Activation<B> {
             Activation::Glu(m) => m.forward(input),
         }
     }
+
+    /// Elementwise -> shape tetap; GLU membelah `dim`, SwiGLU memetakan dim terakhir d_input -> d_output.
+    pub fn output_shape(&self, input: [usize; 4]) -> Result<[usize; 4], String> {
+        let mut out = input;
+        match self {
+            Activation::Glu(m) => {
+                if m.dim >= 4 || !input[m.dim].is_multiple_of(2) {
+                    return Err(format!("glu: dim {} of {:?} must exist and be even", m.dim, input));
+                }
+                out[m.dim] /= 2;
+            }
+            Activation::SwiGlu(m) => {
+                let [d_in, d_out] = m.linear_inner.weight.dims();
+                if input[3] != d_in {
+                    return Err(format!("swiglu: expected last dim {}, got {}", d_in, input[3]));
+                }
+                out[3] = d_out;
+            }
+            _ => {}
+        }
+        Ok(out)
+    }
 }
 
 // --- WASM WRAPPER ---
@@ -251,6 +273,11 @@ impl WasmActivation {
         WasmTensor { inner: out }
     }
 
+    #[wasm_bindgen(js_name = outputShape)]
+    pub fn output_shape(&self, shape: &[usize]) -> Result<Vec<usize>, String> {
+        self.inner.output_shape(crate::layers::pad_shape4(shape)).map(|s| s.to_vec())
+    }
+
     pub fn num_params(&self) -> usize {
         self.inner.num_params()
     }
@@ -1,8 +1,10 @@
 use burn::prelude::*;
 use wasm_bindgen::prelude::*;
+use crate::layers::layout::JsonObj;
+use crate::protocol::*;
 use crate::WasmTensor;
 
-// Parameter-free binary op. `dim` hanya bermakna untuk Concat.
+// Parameter-free binary op. `dim` hanya bermakna untuk Concat, `fill` hanya untuk Where.
 #[derive(Debug, Clone, Copy)]
 pub enum BinaryOp {
     Add,
@@ -10,17 +12,91 @@ pub enum BinaryOp {
     Mul,
     Matmul,
     Concat,
+    Div,
+    Max,
+    Min,
+    Pow,
+    Where,
 }
 
 #[derive(Debug)]
 pub struct Binary {
     op: BinaryOp,
     dim: usize,
+    fill: f32,
+}
+
+/// Broadcast ala NumPy pada rank 4: tiap dim harus sama atau salah satunya 1.
+pub fn broadcast_shape(da: [usize; 4], db: [usize; 4]) -> Option<[usize; 4]> {
+    let mut out = [0usize; 4];
+    for i in 0..4 {
+        out[i] = match (da[i], db[i]) {
+            (x, y) if x == y => x,
+            (1, y) => y,
+            (x, 1) => x,
+            _ => return None,
+        };
+    }
+    Some(out)
 }
 
 impl Binary {
     pub fn new(op: BinaryOp, dim: usize) -> Self {
-        Self { op, dim }
+        Self { op, dim, fill: 0.0 }
+    }
+
+    /// Where: `out = b != 0 ? a : fill` (b = mask, di-broadcast seperti op aritmetika).
+    pub fn new_where(fill: f32) -> Self {
+        Self { op: BinaryOp::Where, dim: 0, fill }
+    }
+
+    fn name(&self) -> &'static str {
+        match self.op {
+            BinaryOp::Add => "add",
+            BinaryOp::Sub => "sub",
+            BinaryOp::Mul => "mul",
+            BinaryOp::Matmul => "matmul",
+            BinaryOp::Concat => "concat",
+            BinaryOp::Div => "div",
+            BinaryOp::Max => "max",
+            BinaryOp::Min => "min",
+            BinaryOp::Pow => "pow",
+            BinaryOp::Where => "where",
+        }
+    }
+
+    /// (varian BINARY_*, config JSON) untuk describeLayer.
+    pub(crate) fn describe(&self) -> (u8, String) {
+        let variant = match self.op {
+            BinaryOp::Add => BINARY_ADD,
+            BinaryOp::Sub => BINARY_SUB,
+            BinaryOp::Mul => BINARY_MUL,
+            BinaryOp::Matmul => BINARY_MATMUL,
+            BinaryOp::Concat => BINARY_CONCAT,
+            BinaryOp::Div => BINARY_DIV,
+            BinaryOp::Max => BINARY_MAX,
+            BinaryOp::Min => BINARY_MIN,
+            BinaryOp::Pow => BINARY_POW,
+            BinaryOp::Where => BINARY_WHERE,
+        };
+        let o = JsonObj::new().str("op", self.name());
+        let o = match self.op {
+            BinaryOp::Concat => o.num("dim", self.dim),
+            BinaryOp::Where => o.float("fill", self.fill as f64),
+            _ => o,
+        };
+        (variant, o.finish())
+    }
+
+    /// Payload OP_INIT v2 setelah `id` (lihat registry::init_binary).
+    pub(crate) fn init_fields(&self) -> Vec<u8> {
+        let w = PayloadWriter::new();
+        match self.op {
+            BinaryOp::Concat => w.usize(self.dim),
+            BinaryOp::Where => w.f64(self.fill as f64),
+            _ => w,
+        }
+        .finish()
     }
 
     // Validasi shape manual -> Err rapi (bukan panic/trap).
@@ -32,24 +108,6 @@ impl Binary {
         let da = a.dims();
         let db = b.dims();
         match self.op {
-            BinaryOp::Add => {
-                if da != db {
-                    return Err(format!("binary add: shape mismatch {:?} vs {:?}", da, db));
-                }
-                Ok(a.add(b))
-            }
-            BinaryOp::Sub => {
-                if da != db {
-                    return Err(format!("binary sub: shape mismatch {:?} vs {:?}", da, db));
-                }
-                Ok(a.sub(b))
-            }
-            BinaryOp::Mul => {
-                if da != db {
-                    return Err(format!("binary mul: shape mismatch {:?} vs {:?}", da, db));
-                }
-                Ok(a.mul(b))
-            }
             BinaryOp::Matmul => {
                 // batched matmul atas 2 dim terakhir: a[*,*,m,k] @ b[*,*,k,n] = [*,*,m,n]
                 if da[0] != db[0] || da[1] != db[1] || da[3] != db[2] {
@@ -78,6 +136,49 @@ impl Binary {
                 }
                 Ok(Tensor::cat(vec![a, b], d))
             }
+            // Elementwise: broadcast eksplisit (expand) supaya semantik sama di semua backend.
+            _ => {
+                let shape = broadcast_shape(da, db).ok_or_else(|| {
+                    format!("binary {}: shapes not broadcastable {:?} vs {:?}", self.name(), da, db)
+                })?;
+                let a = if da == shape { a } else { a.expand(shape) };
+                let b = if db == shape { b } else { b.expand(shape) };
+                Ok(match self.op {
+                    BinaryOp::Add => a.add(b),
+                    BinaryOp::Sub => a.sub(b),
+                    BinaryOp::Mul => a.mul(b),
+                    BinaryOp::Div => a.div(b),
+                    BinaryOp::Max => a.max_pair(b),
+                    BinaryOp::Min => a.min_pair(b),
+                    BinaryOp::Pow => a.powf(b),
+                    BinaryOp::Where => a.mask_fill(b.equal_elem(0.0), self.fill),
+                    BinaryOp::Matmul | BinaryOp::Concat => unreachable!(),
+                })
+            }
+        }
+    }
+
+    /// Shape inference (aturan yang sama dengan `forward`).
+    pub fn output_shape(&self, da: [usize; 4], db: [usize; 4]) -> Result<[usize; 4], String> {
+        match self.op {
+            BinaryOp::Matmul => {
+                if da[0] != db[0] || da[1] != db[1] || da[3] != db[2] {
+                    return Err(format!("binary matmul: incompatible shapes {:?} @ {:?}", da, db));
+                }
+                Ok([da[0], da[1], da[2], db[3]])
+            }
+            BinaryOp::Concat => {
+                let d = self.dim;
+                if d >= 4 || (0..4).any(|i| i != d && da[i] != db[i]) {
+                    return Err(format!("binary concat: incompatible shapes {:?} vs {:?} on dim {}", da, db, d));
+                }
+                let mut out = da;
+                out[d] += db[d];
+                Ok(out)
+            }
+            _ => broadcast_shape(da, db).ok_or_else(|| {
+                format!("binary {}: shapes not broadcastable {:?} vs {:?}", self.name(), da, db)
+            }),
         }
     }
 }
@@ -88,6 +189,18 @@ pub struct WasmBinary {
     inner: Binary,
 }
 
+impl From<Binary> for WasmBinary {
+    fn from(inner: Binary) -> Self {
+        WasmBinary { inner }
+    }
+}
+
+impl WasmBinary {
+    pub(crate) fn op(&self) -> BinaryOp {
+        self.inner.op
+    }
+}
+
 #[wasm_bindgen]
 impl WasmBinary {
     #[wasm_bindgen(js_name = newAdd)]
@@ -110,6 +223,26 @@ impl WasmBinary {
     pub fn new_concat(dim: usize) -> WasmBinary {
         WasmBinary { inner: Binary::new(BinaryOp::Concat, dim) }
     }
+    #[wasm_bindgen(js_name = newDiv)]
+    pub fn new_div() -> WasmBinary {
+        WasmBinary { inner: Binary::new(BinaryOp::Div, 0) }
+    }
+    #[wasm_bindgen(js_name = newMax)]
+    pub fn new_max() -> WasmBinary {
+        WasmBinary { inner: Binary::new(BinaryOp::Max, 0) }
+    }
+    #[wasm_bindgen(js_name = newMin)]
+    pub fn new_min() -> WasmBinary {
+        WasmBinary { inner: Binary::new(BinaryOp::Min, 0) }
+    }
+    #[wasm_bindgen(js_name = newPow)]
+    pub fn new_pow() -> WasmBinary {
+        WasmBinary { inner: Binary::new(BinaryOp::Pow, 0) }
+    }
+    #[wasm_bindgen(js_name = newWhere)]
+    pub fn new_where(fill: Option<f64>) -> WasmBinary {
+        WasmBinary { inner: Binary::new_where(fill.unwrap_or(0.0) as f32) }
+    }
 
     /// Dua input. Shape-mismatch -> thrown string (bukan trap).
     #[wasm_bindgen(js_name = forwardBinary)]
@@ -122,6 +255,13 @@ impl WasmBinary {
         Ok(WasmTensor { inner: out })
     }
 
+    #[wasm_bindgen(js_name = outputShape)]
+    pub fn output_shape(&self, a: &[usize], b: &[usize]) -> Result<Vec<usize>, String> {
+        self.inner
+            .output_shape(crate::layers::pad_shape4(a), crate::layers::pad_shape4(b))
+            .map(|s| s.to_vec())
+    }
+
     // Parameter-free
     pub fn num_params(&self) -> usize {
         0
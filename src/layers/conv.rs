@@ -2,8 +2,11 @@ use burn::prelude::*;
 use burn::nn::conv::{
     Conv1d, Conv1dConfig,
     Conv2d, Conv2dConfig,
+    Conv3d, Conv3dConfig,
+    ConvTranspose1d, ConvTranspose1dConfig,
     ConvTranspose2d, ConvTranspose2dConfig
 };
+use burn::nn::{PaddingConfig1d, PaddingConfig2d, PaddingConfig3d};
 use burn::record::{BinBytesRecorder, FullPrecisionSettings, Recorder};
 use wasm_bindgen::prelude::*;
 use crate::{WasmBackend, WasmTensor};
@@ -14,6 +17,8 @@ pub enum ConvolutionConfig {
     Conv1d(Conv1dConfig),
     Conv2d(Conv2dConfig),
     ConvTranspose2d(ConvTranspose2dConfig),
+    Conv3d(Conv3dConfig),
+    ConvTranspose1d(ConvTranspose1dConfig),
 }
 
 impl ConvolutionConfig {
@@ -22,6 +27,8 @@ impl ConvolutionConfig {
             ConvolutionConfig::Conv1d(c) => Convolution::Conv1d(c.init(device)),
             ConvolutionConfig::Conv2d(c) => Convolution::Conv2d(c.init(device)),
             ConvolutionConfig::ConvTranspose2d(c) => Convolution::ConvTranspose2d(c.init(device)),
+            ConvolutionConfig::Conv3d(c) => Convolution::Conv3d(c.init(device)),
+            ConvolutionConfig::ConvTranspose1d(c) => Convolution::ConvTranspose1d(c.init(device)),
         }
     }
 }
@@ -32,11 +39,14 @@ pub enum Convolution<B: Backend> {
     Conv1d(Conv1d<B>),
     Conv2d(Conv2d<B>),
     ConvTranspose2d(ConvTranspose2d<B>),
+    Conv3d(Conv3d<B>),
+    ConvTranspose1d(ConvTranspose1d<B>),
 }
 
 impl<B: Backend> Convolution<B> {
-    pub fn forward(&self, input: Tensor<B, 4>) -> Tensor<B, 4> {
-        match self {
+    /// Err kalau input tidak cocok dengan konvensi 4D (mis. dim 1 Conv3d bukan C_in x depth).
+    pub fn forward(&self, input: Tensor<B, 4>) -> Result<Tensor<B, 4>, String> {
+        Ok(match self {
             Convolution::Conv2d(layer) => layer.forward(input),
             Convolution::ConvTranspose2d(layer) => layer.forward(input),
             Convolution::Conv1d(layer) => {
@@ -46,8 +56,369 @@ impl<B: Backend> Convolution<B> {
                 let [b_out, c_out, l_out] = out.dims();
                 out.reshape([b_out, c_out, l_out, 1])
             }
+            Convolution::ConvTranspose1d(layer) => {
+                let [b, c, h, _w] = input.dims();
+                let x_3d = input.reshape([b, c, h]);
+                let out = layer.forward(x_3d);
+                let [b_out, c_out, l_out] = out.dims();
+                out.reshape([b_out, c_out, l_out, 1])
+            }
+            // Konvensi 4D untuk volume: [B, C*D, H, W] (channel-major, sama dengan memori NCDHW).
+            // Depth diturunkan dari C_in (weight = [out, in/groups, kd, kh, kw]).
+            Convolution::Conv3d(layer) => {
+                let [b, cd, h, w] = input.dims();
+                let c_in = layer.weight.dims()[1] * layer.groups;
+                if cd == 0 || !cd.is_multiple_of(c_in) {
+                    return Err(format!("conv3d: input dim 1 ({}) must be C_in ({}) x depth", cd, c_in));
+                }
+                let x_5d = input.reshape([b, c_in, cd / c_in, h, w]);
+                let out = layer.forward(x_5d);
+                let [b_out, c_out, d_out, h_out, w_out] = out.dims();
+                out.reshape([b_out, c_out * d_out, h_out, w_out])
+            }
+        })
+    }
+}
+
+/// Panjang output conv: (n + 2p - d(k-1) - 1) / s + 1.
+pub(crate) fn conv_out_len(size_in: usize, kernel: usize, stride: usize, padding: usize, dilation: usize) -> Result<usize, String> {
+    let span = dilation * kernel.saturating_sub(1) + 1;
+    let padded = size_in + 2 * padding;
+    if stride == 0 || span > padded {
+        return Err(format!(
+            "conv: kernel span {} (kernel {}, dilation {}) does not fit input {} + 2*{} padding",
+            span, kernel, dilation, size_in, padding
+        ));
+    }
+    Ok((padded - span) / stride + 1)
+}
+
+/// Panjang output conv transposed: (n - 1)s - 2p + d(k-1) + padding_out + 1.
+fn conv_transpose_out_len(
+    size_in: usize,
+    kernel: usize,
+    stride: usize,
+    padding: usize,
+    dilation: usize,
+    padding_out: usize,
+) -> Result<usize, String> {
+    let full = size_in.saturating_sub(1) * stride + dilation * kernel.saturating_sub(1) + padding_out + 1;
+    full.checked_sub(2 * padding)
+        .filter(|&n| n > 0 && size_in > 0)
+        .ok_or_else(|| format!("conv transpose: padding {} too large for input {}", padding, size_in))
+}
+
+fn explicit_2d(p: &PaddingConfig2d) -> [usize; 2] {
+    match p {
+        PaddingConfig2d::Explicit(a, b) => [*a, *b],
+        _ => [0, 0],
+    }
+}
+
+fn check_channels(name: &str, got: usize, expected: usize) -> Result<(), String> {
+    if got != expected {
+        return Err(format!("{}: expected {} input channels, got {}", name, expected, got));
+    }
+    Ok(())
+}
+
+impl<B: Backend> Convolution<B> {
+    /// Shape inference (konvensi 4D sama dengan forward). Padding dari ConvSpec selalu
+    /// Explicit/Valid ("Same" sudah dihitung jadi Explicit).
+    pub fn output_shape(&self, input: [usize; 4]) -> Result<[usize; 4], String> {
+        let [b, c, h, w] = input;
+        Ok(match self {
+            Convolution::Conv1d(l) => {
+                let [c_out, c_in_g, _] = l.weight.dims();
+                check_channels("conv1d", c, c_in_g * l.groups)?;
+                let p = match &l.padding.0 {
+                    PaddingConfig1d::Explicit(p) => *p,
+                    _ => 0,
+                };
+                [b, c_out, conv_out_len(h, l.kernel_size, l.stride, p, l.dilation)?, 1]
+            }
+            Convolution::Conv2d(l) => {
+                let [c_out, c_in_g, _, _] = l.weight.dims();
+                check_channels("conv2d", c, c_in_g * l.groups)?;
+                let [ph, pw] = explicit_2d(&l.padding.0);
+                [
+                    b,
+                    c_out,
+                    conv_out_len(h, l.kernel_size[0], l.stride[0], ph, l.dilation[0])?,
+                    conv_out_len(w, l.kernel_size[1], l.stride[1], pw, l.dilation[1])?,
+                ]
+            }
+            Convolution::Conv3d(l) => {
+                let [c_out, c_in_g, _, _, _] = l.weight.dims();
+                let c_in = c_in_g * l.groups;
+                if c == 0 || !c.is_multiple_of(c_in) {
+                    return Err(format!("conv3d: input dim 1 ({}) must be C_in ({}) x depth", c, c_in));
+                }
+                let [pd, ph, pw] = match &l.padding.0 {
+                    PaddingConfig3d::Explicit(d, h, w) => [*d, *h, *w],
+                    _ => [0, 0, 0],
+                };
+                let d_out = conv_out_len(c / c_in, l.kernel_size[0], l.stride[0], pd, l.dilation[0])?;
+                [
+                    b,
+                    c_out * d_out,
+                    conv_out_len(h, l.kernel_size[1], l.stride[1], ph, l.dilation[1])?,
+                    conv_out_len(w, l.kernel_size[2], l.stride[2], pw, l.dilation[2])?,
+                ]
+            }
+            Convolution::ConvTranspose1d(l) => {
+                check_channels("conv_transpose1d", c, l.channels[0])?;
+                let len = conv_transpose_out_len(h, l.kernel_size, l.stride, l.padding, l.dilation, l.padding_out)?;
+                [b, l.channels[1], len, 1]
+            }
+            Convolution::ConvTranspose2d(l) => {
+                check_channels("conv_transpose2d", c, l.channels[0])?;
+                let out = |i: usize, n: usize| {
+                    conv_transpose_out_len(n, l.kernel_size[i], l.stride[i], l.padding[i], l.dilation[i], l.padding_out[i])
+                };
+                [b, l.channels[1], out(0, h)?, out(1, w)?]
+            }
+        })
+    }
+}
+
+// --- SPEC (hasil decode payload) ---
+/// Mode padding conv. `Explicit` memakai nilai padding dari payload (None = default Burn).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvPadding {
+    Explicit,
+    Same,
+    Valid,
+}
+
+/// Deskripsi lengkap satu conv. Field `*_d` hanya dipakai Conv3d; `kernel_w`/`*_w`
+/// diabaikan varian 1D (kernel_w wajib 0/1 supaya tidak diam-diam salah tafsir).
+/// Stride/padding/dilation per sumbu hanya diterapkan kalau SEMUA sumbu Some
+/// (sama seperti constructor lama yang menuntut pasangan (h, w)).
+#[derive(Debug, Clone)]
+pub struct ConvSpec {
+    pub in_channels: usize,
+    pub out_channels: usize,
+    pub kernel_h: usize,
+    pub kernel_w: usize,
+    pub kernel_d: usize,
+    pub stride_h: Option<usize>,
+    pub stride_w: Option<usize>,
+    pub stride_d: Option<usize>,
+    pub padding_h: Option<usize>,
+    pub padding_w: Option<usize>,
+    pub padding_d: Option<usize>,
+    pub dilation_h: Option<usize>,
+    pub dilation_w: Option<usize>,
+    pub dilation_d: Option<usize>,
+    pub groups: Option<usize>,
+    pub bias: Option<bool>,
+    pub padding_mode: ConvPadding,
+}
+
+impl ConvSpec {
+    pub fn new(in_channels: usize, out_channels: usize, kernel_h: usize, kernel_w: usize) -> Self {
+        ConvSpec {
+            in_channels,
+            out_channels,
+            kernel_h,
+            kernel_w,
+            kernel_d: 1,
+            stride_h: None,
+            stride_w: None,
+            stride_d: None,
+            padding_h: None,
+            padding_w: None,
+            padding_d: None,
+            dilation_h: None,
+            dilation_w: None,
+            dilation_d: None,
+            groups: None,
+            bias: None,
+            padding_mode: ConvPadding::Explicit,
         }
     }
+
+    fn groups(&self) -> Result<usize, String> {
+        let g = self.groups.unwrap_or(1);
+        if g == 0 {
+            return Err("conv: groups must be >= 1".into());
+        }
+        if !self.in_channels.is_multiple_of(g) || !self.out_channels.is_multiple_of(g) {
+            return Err(format!(
+                "conv: channels ({} -> {}) must be divisible by groups {}",
+                self.in_channels, self.out_channels, g
+            ));
+        }
+        Ok(g)
+    }
+
+    fn check_1d_kernel(&self) -> Result<(), String> {
+        if self.kernel_w > 1 {
+            return Err(format!(
+                "conv1d: kernel_w must be 0 or 1 (1D conv runs along H), got {}",
+                self.kernel_w
+            ));
+        }
+        Ok(())
+    }
+
+    // "Same" dihitung di sini (bukan PaddingConfig::Same Burn) supaya dilation ikut
+    // diperhitungkan dan hasilnya tidak bergantung ukuran input: p = d*(k-1)/2, stride 1.
+    fn same_padding(kernel: usize, stride: usize, dilation: usize) -> Result<usize, String> {
+        if kernel.is_multiple_of(2) {
+            return Err(format!("conv: same padding needs an odd kernel, got {}", kernel));
+        }
+        if stride != 1 {
+            return Err(format!("conv: same padding needs stride 1, got {}", stride));
+        }
+        Ok(dilation * (kernel - 1) / 2)
+    }
+
+    pub fn conv1d(&self) -> Result<ConvolutionConfig, String> {
+        self.check_1d_kernel()?;
+        let mut config = Conv1dConfig::new(self.in_channels, self.out_channels, self.kernel_h);
+        if let Some(s) = self.stride_h {
+            config.stride = s;
+        }
+        if let Some(d) = self.dilation_h {
+            config.dilation = d;
+        }
+        config.groups = self.groups()?;
+        if let Some(b) = self.bias {
+            config.bias = b;
+        }
+        match self.padding_mode {
+            ConvPadding::Explicit => {
+                if let Some(p) = self.padding_h {
+                    config.padding = PaddingConfig1d::Explicit(p);
+                }
+            }
+            ConvPadding::Valid => config.padding = PaddingConfig1d::Valid,
+            ConvPadding::Same => {
+                let p = Self::same_padding(config.kernel_size, config.stride, config.dilation)?;
+                config.padding = PaddingConfig1d::Explicit(p);
+            }
+        }
+        Ok(ConvolutionConfig::Conv1d(config))
+    }
+
+    pub fn conv2d(&self) -> Result<ConvolutionConfig, String> {
+        let mut config = Conv2dConfig::new(
+            [self.in_channels, self.out_channels],
+            [self.kernel_h, self.kernel_w],
+        );
+        if let (Some(sh), Some(sw)) = (self.stride_h, self.stride_w) {
+            config.stride = [sh, sw];
+        }
+        if let (Some(dh), Some(dw)) = (self.dilation_h, self.dilation_w) {
+            config.dilation = [dh, dw];
+        }
+        config.groups = self.groups()?;
+        if let Some(b) = self.bias {
+            config.bias = b;
+        }
+        match self.padding_mode {
+            ConvPadding::Explicit => {
+                if let (Some(ph), Some(pw)) = (self.padding_h, self.padding_w) {
+                    config.padding = PaddingConfig2d::Explicit(ph, pw);
+                }
+            }
+            ConvPadding::Valid => config.padding = PaddingConfig2d::Valid,
+            ConvPadding::Same => {
+                let ph = Self::same_padding(config.kernel_size[0], config.stride[0], config.dilation[0])?;
+                let pw = Self::same_padding(config.kernel_size[1], config.stride[1], config.dilation[1])?;
+                config.padding = PaddingConfig2d::Explicit(ph, pw);
+            }
+        }
+        Ok(ConvolutionConfig::Conv2d(config))
+    }
+
+    pub fn conv3d(&self) -> Result<ConvolutionConfig, String> {
+        let mut config = Conv3dConfig::new(
+            [self.in_channels, self.out_channels],
+            [self.kernel_d, self.kernel_h, self.kernel_w],
+        );
+        if let (Some(sd), Some(sh), Some(sw)) = (self.stride_d, self.stride_h, self.stride_w) {
+            config.stride = [sd, sh, sw];
+        }
+        if let (Some(dd), Some(dh), Some(dw)) = (self.dilation_d, self.dilation_h, self.dilation_w) {
+            config.dilation = [dd, dh, dw];
+        }
+        config.groups = self.groups()?;
+        if let Some(b) = self.bias {
+            config.bias = b;
+        }
+        match self.padding_mode {
+            ConvPadding::Explicit => {
+                if let (Some(pd), Some(ph), Some(pw)) = (self.padding_d, self.padding_h, self.padding_w) {
+                    config.padding = PaddingConfig3d::Explicit(pd, ph, pw);
+                }
+            }
+            ConvPadding::Valid => config.padding = PaddingConfig3d::Valid,
+            ConvPadding::Same => {
+                let mut p = [0usize; 3];
+                for (i, v) in p.iter_mut().enumerate() {
+                    *v = Self::same_padding(config.kernel_size[i], config.stride[i], config.dilation[i])?;
+                }
+                config.padding = PaddingConfig3d::Explicit(p[0], p[1], p[2]);
+            }
+        }
+        Ok(ConvolutionConfig::Conv3d(config))
+    }
+
+    // Transposed: padding Burn selalu eksplisit; Same tidak punya arti yang tunggal -> Err.
+    pub fn conv_transpose1d(&self) -> Result<ConvolutionConfig, String> {
+        self.check_1d_kernel()?;
+        let mut config = ConvTranspose1dConfig::new([self.in_channels, self.out_channels], self.kernel_h);
+        if let Some(s) = self.stride_h {
+            config.stride = s;
+        }
+        if let Some(d) = self.dilation_h {
+            config.dilation = d;
+        }
+        config.groups = self.groups()?;
+        if let Some(b) = self.bias {
+            config.bias = b;
+        }
+        match self.padding_mode {
+            ConvPadding::Explicit => {
+                if let Some(p) = self.padding_h {
+                    config.padding = p;
+                }
+            }
+            ConvPadding::Valid => config.padding = 0,
+            ConvPadding::Same => return Err("conv_transpose1d: same padding is not supported".into()),
+        }
+        Ok(ConvolutionConfig::ConvTranspose1d(config))
+    }
+
+    pub fn conv_transpose2d(&self) -> Result<ConvolutionConfig, String> {
+        let mut config = ConvTranspose2dConfig::new(
+            [self.in_channels, self.out_channels],
+            [self.kernel_h, self.kernel_w],
+        );
+        if let (Some(sh), Some(sw)) = (self.stride_h, self.stride_w) {
+            config.stride = [sh, sw];
+        }
+        if let (Some(dh), Some(dw)) = (self.dilation_h, self.dilation_w) {
+            config.dilation = [dh, dw];
+        }
+        config.groups = self.groups()?;
+        if let Some(b) = self.bias {
+            config.bias = b;
+        }
+        match self.padding_mode {
+            ConvPadding::Explicit => {
+                if let (Some(ph), Some(pw)) = (self.padding_h, self.padding_w) {
+                    config.padding = [ph, pw];
+                }
+            }
+            ConvPadding::Valid => config.padding = [0, 0],
+            ConvPadding::Same => return Err("conv_transpose2d: same padding is not supported".into()),
+        }
+        Ok(ConvolutionConfig::ConvTranspose2d(config))
+    }
 }
 
 // --- WASM WRAPPER ---
@@ -80,6 +451,7 @@ impl WasmConv {
     }
 
     #[wasm_bindgen(js_name = newConv2d)]
+    #[allow(clippy::too_many_arguments)]
     pub fn new_conv2d(
         in_channels: usize,
         out_channels: usize,
@@ -104,6 +476,7 @@ impl WasmConv {
     }
 
     #[wasm_bindgen(js_name = newConvTranspose2d)]
+    #[allow(clippy::too_many_arguments)]
     pub fn new_conv_transpose2d(
         in_channels: usize,
         out_channels: usize,
@@ -127,10 +500,59 @@ impl WasmConv {
         }
     }
 
-    pub fn forward(&self, input: &WasmTensor) -> WasmTensor {
-        let x = input.inner.clone();
-        let out = self.inner.forward(x);
-        WasmTensor { inner: out }
+    #[wasm_bindgen(js_name = newConvTranspose1d)]
+    pub fn new_conv_transpose1d(
+        in_channels: usize,
+        out_channels: usize,
+        kernel_size: usize,
+        stride: Option<usize>,
+        padding: Option<usize>,
+    ) -> WasmConv {
+        let device = Default::default();
+        let mut config = ConvTranspose1dConfig::new([in_channels, out_channels], kernel_size);
+        if let Some(s) = stride {
+            config.stride = s;
+        }
+        if let Some(p) = padding {
+            config.padding = p;
+        }
+        WasmConv {
+            inner: ConvolutionConfig::ConvTranspose1d(config).init(&device),
+        }
+    }
+
+    /// Input 4D `[B, C_in*D, H, W]` -> output `[B, C_out*D_out, H_out, W_out]`.
+    #[wasm_bindgen(js_name = newConv3d)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_conv3d(
+        in_channels: usize,
+        out_channels: usize,
+        kernel_size_d: usize,
+        kernel_size_h: usize,
+        kernel_size_w: usize,
+        stride: Option<usize>,
+        padding: Option<usize>,
+    ) -> WasmConv {
+        let device = Default::default();
+        let mut config = Conv3dConfig::new(
+            [in_channels, out_channels],
+            [kernel_size_d, kernel_size_h, kernel_size_w],
+        );
+        if let Some(s) = stride {
+            config.stride = [s, s, s];
+        }
+        if let Some(p) = padding {
+            config.padding = PaddingConfig3d::Explicit(p, p, p);
+        }
+        WasmConv {
+            inner: ConvolutionConfig::Conv3d(config).init(&device),
+        }
+    }
+
+    /// Input tidak cocok (mis. Conv3d dengan dim 1 bukan kelipatan C_in) -> thrown string (bukan trap).
+    pub fn forward(&self, input: &WasmTensor) -> Result<WasmTensor, String> {
+        let out = self.inner.forward(input.inner.clone())?;
+        Ok(WasmTensor { inner: out })
     }
 
     pub fn num_params(&self) -> usize {
@@ -155,9 +577,40 @@ impl WasmConv {
     }
 }
 
+#[wasm_bindgen]
+impl WasmConv {
+    #[wasm_bindgen(js_name = outputShape)]
+    pub fn output_shape(&self, shape: &[usize]) -> Result<Vec<usize>, String> {
+        self.inner.output_shape(crate::layers::pad_shape4(shape)).map(|s| s.to_vec())
+    }
+}
+
+// Estimasi MAC untuk profiler: conv = numel output x fan-in kernel,
+// transposed = numel input x (out/groups x kernel) (weight = [in, out/groups, k..]).
+impl WasmConv {
+    pub fn macs(&self, in_numel: usize, out_numel: usize) -> u64 {
+        let tail = |d: &[usize]| d[1..].iter().product::<usize>() as u64;
+        match &self.inner {
+            Convolution::Conv1d(l) => out_numel as u64 * tail(&l.weight.dims()),
+            Convolution::Conv2d(l) => out_numel as u64 * tail(&l.weight.dims()),
+            Convolution::Conv3d(l) => out_numel as u64 * tail(&l.weight.dims()),
+            Convolution::ConvTranspose1d(l) => in_numel as u64 * tail(&l.weight.dims()),
+            Convolution::ConvTranspose2d(l) => in_numel as u64 * tail(&l.weight.dims()),
+        }
+    }
+}
+
+// Jalur protokol: config sudah divalidasi ConvSpec (groups/kernel/padding) -> init tidak panic.
+impl WasmConv {
+    pub fn from_config(config: ConvolutionConfig) -> WasmConv {
+        let device = Default::default();
+        WasmConv { inner: config.init(&device) }
+    }
+}
+
 // ============================================================
 // FLOAT-BRIDGE (M1) — conv. Record per variant = { weight: Param<TD>, bias: Option<Param<T1>> }.
-// D = 3 (conv1d) atau 4 (conv2d / transpose2d). Helper generic supaya rank statis & aman.
+// D = 3 (conv1d / transpose1d), 4 (conv2d / transpose2d) atau 5 (conv3d). Helper generic supaya rank statis & aman.
 // ============================================================
 fn push_param<B: Backend, const D: usize>(
     p: &burn::module::Param<Tensor<B, D>>,
@@ -225,6 +678,14 @@ impl WasmConv {
                 push_param::<WasmBackend, 4>(&r.weight, &mut out)?;
                 if let Some(b) = &r.bias { push_param::<WasmBackend, 1>(b, &mut out)?; }
             }
+            ConvolutionRecord::Conv3d(r) => {
+                push_param::<WasmBackend, 5>(&r.weight, &mut out)?;
+                if let Some(b) = &r.bias { push_param::<WasmBackend, 1>(b, &mut out)?; }
+            }
+            ConvolutionRecord::ConvTranspose1d(r) => {
+                push_param::<WasmBackend, 3>(&r.weight, &mut out)?;
+                if let Some(b) = &r.bias { push_param::<WasmBackend, 1>(b, &mut out)?; }
+            }
         }
         Ok(out)
     }
@@ -242,12 +703,47 @@ impl WasmConv {
             ConvolutionRecord::ConvTranspose2d(r) => {
                 set_conv_param::<WasmBackend, 4>(&mut r.weight, &mut r.bias, data)?;
             }
+            ConvolutionRecord::Conv3d(r) => {
+                set_conv_param::<WasmBackend, 5>(&mut r.weight, &mut r.bias, data)?;
+            }
+            ConvolutionRecord::ConvTranspose1d(r) => {
+                set_conv_param::<WasmBackend, 3>(&mut r.weight, &mut r.bias, data)?;
+            }
         }
         self.inner = self.inner.clone().load_record(rec);
         Ok(())
     }
 }
 
+// ============================================================
+// FUSION — fold BatchNorm eval (affine per out-channel) ke salinan conv.
+// ============================================================
+impl WasmConv {
+    /// Salinan dengan weight[o, ..] *= scale[o] dan bias' = bias * scale + shift; conv tanpa
+    /// bias -> shift ikut dikembalikan (ditambahkan setelah conv). Hanya Conv1d/Conv2d
+    /// (weight [out, in/groups, k..]); transposed/3d atau jumlah channel beda -> None.
+    pub(crate) fn folded(&self, scale: &[f32], shift: &[f32]) -> Option<(WasmConv, Option<Vec<f32>>)> {
+        let out_ch = match &self.inner {
+            Convolution::Conv1d(l) => l.weight.dims()[0],
+            Convolution::Conv2d(l) => l.weight.dims()[0],
+            _ => return None,
+        };
+        if out_ch != scale.len() || out_ch != shift.len() {
+            return None;
+        }
+        let wlen = self.weight_segs()[0].1;
+        let mut flat = self.get_weights_flat().ok()?;
+        let per = wlen / out_ch;
+        for (i, w) in flat[..wlen].iter_mut().enumerate() {
+            *w *= scale[i / per];
+        }
+        let extra = crate::layers::fold_bias(&mut flat[wlen..], scale, shift);
+        let mut conv = WasmConv { inner: self.inner.clone() };
+        conv.set_weights_flat(&flat).ok()?;
+        Some((conv, extra))
+    }
+}
+
 // ============================================================
 // WEIGHT LAYOUT (M2) — conv. Mirror urutan getWeightsFlat per variant.
 // ============================================================
@@ -276,6 +772,12 @@ impl WasmConv {
             ConvolutionRecord::ConvTranspose2d(r) => {
                 push_conv_segs::<WasmBackend, 4>(&r.weight, &r.bias, &mut segs);
             }
+            ConvolutionRecord::Conv3d(r) => {
+                push_conv_segs::<WasmBackend, 5>(&r.weight, &r.bias, &mut segs);
+            }
+            ConvolutionRecord::ConvTranspose1d(r) => {
+                push_conv_segs::<WasmBackend, 3>(&r.weight, &r.bias, &mut segs);
+            }
         }
         segs
     }
@@ -283,4 +785,15 @@ impl WasmConv {
     pub fn weight_layout(&self) -> String {
         crate::layers::layout::segs_json(&self.weight_segs())
     }
+
+    /// Dims weight apa adanya ([out, in/groups, k..]; transposed: [in, out/groups, k..]).
+    pub fn weight_dims(&self) -> Vec<usize> {
+        match &self.inner {
+            Convolution::Conv1d(l) => l.weight.dims().to_vec(),
+            Convolution::Conv2d(l) => l.weight.dims().to_vec(),
+            Convolution::ConvTranspose2d(l) => l.weight.dims().to_vec(),
+            Convolution::Conv3d(l) => l.weight.dims().to_vec(),
+            Convolution::ConvTranspose1d(l) => l.weight.dims().to_vec(),
+        }
+    }
 }
@@ -0,0 +1,182 @@
+use burn::prelude::*;
+use burn::nn::{Linear, LinearConfig};
+use burn::record::{BinBytesRecorder, FullPrecisionSettings, Recorder};
+use burn::tensor::activation::{relu, sigmoid};
+use wasm_bindgen::prelude::*;
+use crate::layers::custom::spatial_attention::{SpatialAttention, SpatialAttentionConfig};
+use crate::layers::custom::{check_flat_len, param_dims, push_param, seg_lens, take_param};
+use crate::{WasmBackend, WasmTensor};
+
+// --- CONFIGURATION ---
+// CBAM: channel attention (MLP bersama atas avg- DAN max-pool) lalu spatial attention.
+#[derive(Config, Debug)]
+pub struct CbamBlockConfig {
+    pub channels: usize,
+    #[config(default = 16)]
+    pub reduction: usize,
+    #[config(default = 7)]
+    pub kernel_size: usize,
+}
+
+impl CbamBlockConfig {
+    pub fn init<B: Backend>(&self, device: &B::Device) -> Result<CbamBlock<B>, String> {
+        if self.reduction == 0 || self.channels < self.reduction {
+            return Err(format!(
+                "CBAM: channels ({}) must be >= reduction ({}) > 0",
+                self.channels, self.reduction
+            ));
+        }
+        let reduced = self.channels / self.reduction;
+        Ok(CbamBlock {
+            fc1: LinearConfig::new(self.channels, reduced).init(device),
+            fc2: LinearConfig::new(reduced, self.channels).init(device),
+            spatial: SpatialAttentionConfig::new().with_kernel_size(self.kernel_size).init(device)?,
+            channels: self.channels,
+        })
+    }
+}
+
+// --- MODULE ---
+#[derive(Module, Debug)]
+pub struct CbamBlock<B: Backend> {
+    fc1: Linear<B>,
+    fc2: Linear<B>,
+    spatial: SpatialAttention<B>,
+    channels: usize,
+}
+
+impl<B: Backend> CbamBlock<B> {
+    fn mlp(&self, x: Tensor<B, 2>) -> Tensor<B, 2> {
+        self.fc2.forward(relu(self.fc1.forward(x)))
+    }
+
+    pub fn forward(&self, input: Tensor<B, 4>) -> Tensor<B, 4> {
+        let [b, c, _h, _w] = input.dims();
+
+        // 1. Channel attention: sigmoid(MLP(avg) + MLP(max)) → [B, C, 1, 1]
+        let avg = input.clone().mean_dim(2).mean_dim(3).reshape([b, c]);
+        let max = input.clone().max_dim(2).max_dim(3).reshape([b, c]);
+        let weights = sigmoid(self.mlp(avg) + self.mlp(max)).reshape([b, c, 1, 1]);
+        let x = input * weights;
+
+        // 2. Spatial attention pada hasil channel attention
+        self.spatial.forward(x)
+    }
+}
+
+// --- WASM WRAPPER ---
+#[wasm_bindgen]
+pub struct WasmCbamBlock {
+    inner: CbamBlock<WasmBackend>,
+}
+
+impl WasmCbamBlock {
+    /// Konstruksi dari config (API Rust, lihat `crate::api`).
+    pub fn from_config(config: &CbamBlockConfig) -> Result<WasmCbamBlock, String> {
+        let device = Default::default();
+        Ok(WasmCbamBlock {
+            inner: config.init(&device)?,
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl WasmCbamBlock {
+    #[wasm_bindgen(constructor)]
+    pub fn new(channels: usize, reduction: Option<usize>, kernel_size: Option<usize>) -> Result<WasmCbamBlock, String> {
+        let device = Default::default();
+        let mut config = CbamBlockConfig::new(channels);
+        if let Some(r) = reduction {
+            config.reduction = r;
+        }
+        if let Some(k) = kernel_size {
+            config.kernel_size = k;
+        }
+        Ok(WasmCbamBlock {
+            inner: config.init(&device)?,
+        })
+    }
+
+    pub fn forward(&self, input: &WasmTensor) -> WasmTensor {
+        let x = input.inner.clone();
+        let out = self.inner.forward(x);
+        WasmTensor { inner: out }
+    }
+
+    pub fn num_params(&self) -> usize {
+        self.inner.num_params()
+    }
+
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+        let device = Default::default();
+        let record = BinBytesRecorder::<FullPrecisionSettings>::default()
+            .load(data.to_vec(), &device)
+            .map_err(|e| e.to_string())?;
+        self.inner = self.inner.clone().load_record(record);
+        Ok(())
+    }
+
+    pub fn get_state(&self) -> Result<Vec<u8>, String> {
+        let record = self.inner.clone().into_record();
+        let bytes = BinBytesRecorder::<FullPrecisionSettings>::default()
+            .record(record, ())
+            .map_err(|e| e.to_string())?;
+        Ok(bytes)
+    }
+}
+
+// ============================================================
+// FLOAT-BRIDGE — urutan flat: fc1.weight, fc1.bias, fc2.weight, fc2.bias, spatial.weight.
+// ============================================================
+#[wasm_bindgen]
+impl WasmCbamBlock {
+    #[wasm_bindgen(js_name = getWeightsFlat)]
+    pub fn get_weights_flat(&self) -> Result<Vec<f32>, String> {
+        let rec = self.inner.clone().into_record();
+        let mut out = Vec::new();
+        for fc in [&rec.fc1, &rec.fc2] {
+            push_param(&fc.weight, &mut out)?;
+            if let Some(b) = &fc.bias { push_param(b, &mut out)?; }
+        }
+        push_param(&rec.spatial.conv.weight, &mut out)?;
+        Ok(out)
+    }
+
+    #[wasm_bindgen(js_name = setWeightsFlat)]
+    pub fn set_weights_flat(&mut self, data: &[f32]) -> Result<(), String> {
+        check_flat_len(&self.weight_segs(), data)?;
+        let mut rec = self.inner.clone().into_record();
+        let mut pos = 0;
+        for fc in [&mut rec.fc1, &mut rec.fc2] {
+            take_param(&mut fc.weight, data, &mut pos);
+            if let Some(b) = &mut fc.bias { take_param(b, data, &mut pos); }
+        }
+        take_param(&mut rec.spatial.conv.weight, data, &mut pos);
+        self.inner = self.inner.clone().load_record(rec);
+        Ok(())
+    }
+}
+
+// ============================================================
+// WEIGHT LAYOUT (M2) — cbam. Mirror urutan getWeightsFlat.
+// ============================================================
+impl WasmCbamBlock {
+    pub fn weight_segs(&self) -> Vec<(&'static str, usize)> {
+        seg_lens(self.weight_seg_dims())
+    }
+
+    /// Dims burn per segmen, sejajar weight_segs (linear [in, out], conv [out, in/groups, k..]).
+    pub fn weight_seg_dims(&self) -> Vec<(&'static str, Vec<usize>)> {
+        let rec = self.inner.clone().into_record();
+        let mut segs = vec![("fc1.weight", param_dims(&rec.fc1.weight))];
+        if let Some(b) = &rec.fc1.bias { segs.push(("fc1.bias", param_dims(b))); }
+        segs.push(("fc2.weight", param_dims(&rec.fc2.weight)));
+        if let Some(b) = &rec.fc2.bias { segs.push(("fc2.bias", param_dims(b))); }
+        segs.push(("spatial.weight", param_dims(&rec.spatial.conv.weight)));
+        segs
+    }
+
+    pub fn weight_layout(&self) -> String {
+        crate::layers::layout::segs_json(&self.weight_segs())
+    }
+}
@@ -0,0 +1,164 @@
+use burn::prelude::*;
+use burn::nn::conv::{Conv1d, Conv1dConfig};
+use burn::nn::PaddingConfig1d;
+use burn::record::{BinBytesRecorder, FullPrecisionSettings, Recorder};
+use burn::tensor::activation::sigmoid;
+use wasm_bindgen::prelude::*;
+use crate::layers::custom::{check_flat_len, param_dims, push_param, seg_lens, take_param};
+use crate::{WasmBackend, WasmTensor};
+
+// --- CONFIGURATION ---
+// ECA-Net: channel attention tanpa reduksi dimensi. Descriptor GAP [B, C]
+// diperlakukan sebagai sinyal 1D dan di-conv dengan kernel kecil k (interaksi lokal antar channel).
+#[derive(Config, Debug)]
+pub struct EcaBlockConfig {
+    pub channels: usize,
+    /// None -> adaptif dari paper: k = |(log2(C) + b) / gamma|, dibulatkan ke ganjil.
+    #[config(default = "None")]
+    pub kernel_size: Option<usize>,
+    #[config(default = 2.0)]
+    pub gamma: f64,
+    #[config(default = 1.0)]
+    pub beta: f64,
+}
+
+impl EcaBlockConfig {
+    pub fn resolved_kernel(&self) -> usize {
+        self.kernel_size.unwrap_or_else(|| {
+            let t = ((self.channels.max(1) as f64).log2() + self.beta) / self.gamma;
+            let t = t.abs() as usize;
+            if t % 2 == 1 { t } else { t + 1 }
+        })
+    }
+
+    pub fn init<B: Backend>(&self, device: &B::Device) -> Result<EcaBlock<B>, String> {
+        let k = self.resolved_kernel();
+        if self.channels == 0 || k.is_multiple_of(2) {
+            return Err(format!("ECA: channels ({}) must be > 0 and kernel ({}) odd", self.channels, k));
+        }
+        let conv = Conv1dConfig::new(1, 1, k)
+            .with_padding(PaddingConfig1d::Explicit(k / 2))
+            .with_bias(false)
+            .init(device);
+        Ok(EcaBlock { conv, channels: self.channels })
+    }
+}
+
+// --- MODULE ---
+#[derive(Module, Debug)]
+pub struct EcaBlock<B: Backend> {
+    conv: Conv1d<B>,
+    channels: usize,
+}
+
+impl<B: Backend> EcaBlock<B> {
+    pub fn forward(&self, input: Tensor<B, 4>) -> Tensor<B, 4> {
+        let [b, c, _h, _w] = input.dims();
+
+        // Squeeze: GAP → [B, 1, C] (channel jadi sumbu "panjang" conv 1D)
+        let desc = input.clone().mean_dim(2).mean_dim(3).reshape([b, 1, c]);
+
+        // Conv 1D lintas channel + sigmoid → [B, C, 1, 1]
+        let weights = sigmoid(self.conv.forward(desc)).reshape([b, c, 1, 1]);
+
+        input * weights
+    }
+}
+
+// --- WASM WRAPPER ---
+#[wasm_bindgen]
+pub struct WasmEcaBlock {
+    inner: EcaBlock<WasmBackend>,
+}
+
+impl WasmEcaBlock {
+    /// Konstruksi dari config (API Rust, lihat `crate::api`).
+    pub fn from_config(config: &EcaBlockConfig) -> Result<WasmEcaBlock, String> {
+        let device = Default::default();
+        Ok(WasmEcaBlock {
+            inner: config.init(&device)?,
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl WasmEcaBlock {
+    /// kernel_size None -> adaptif dari jumlah channel.
+    #[wasm_bindgen(constructor)]
+    pub fn new(channels: usize, kernel_size: Option<usize>) -> Result<WasmEcaBlock, String> {
+        let device = Default::default();
+        let config = EcaBlockConfig::new(channels).with_kernel_size(kernel_size);
+        Ok(WasmEcaBlock {
+            inner: config.init(&device)?,
+        })
+    }
+
+    pub fn forward(&self, input: &WasmTensor) -> WasmTensor {
+        let x = input.inner.clone();
+        let out = self.inner.forward(x);
+        WasmTensor { inner: out }
+    }
+
+    pub fn num_params(&self) -> usize {
+        self.inner.num_params()
+    }
+
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+        let device = Default::default();
+        let record = BinBytesRecorder::<FullPrecisionSettings>::default()
+            .load(data.to_vec(), &device)
+            .map_err(|e| e.to_string())?;
+        self.inner = self.inner.clone().load_record(record);
+        Ok(())
+    }
+
+    pub fn get_state(&self) -> Result<Vec<u8>, String> {
+        let record = self.inner.clone().into_record();
+        let bytes = BinBytesRecorder::<FullPrecisionSettings>::default()
+            .record(record, ())
+            .map_err(|e| e.to_string())?;
+        Ok(bytes)
+    }
+}
+
+// ============================================================
+// FLOAT-BRIDGE — urutan flat: conv.weight [1, 1, k] (tanpa bias).
+// ============================================================
+#[wasm_bindgen]
+impl WasmEcaBlock {
+    #[wasm_bindgen(js_name = getWeightsFlat)]
+    pub fn get_weights_flat(&self) -> Result<Vec<f32>, String> {
+        let rec = self.inner.clone().into_record();
+        let mut out = Vec::new();
+        push_param(&rec.conv.weight, &mut out)?;
+        Ok(out)
+    }
+
+    #[wasm_bindgen(js_name = setWeightsFlat)]
+    pub fn set_weights_flat(&mut self, data: &[f32]) -> Result<(), String> {
+        check_flat_len(&self.weight_segs(), data)?;
+        let mut rec = self.inner.clone().into_record();
+        take_param(&mut rec.conv.weight, data, &mut 0);
+        self.inner = self.inner.clone().load_record(rec);
+        Ok(())
+    }
+}
+
+// ============================================================
+// WEIGHT LAYOUT (M2) — eca. Mirror urutan getWeightsFlat.
+// ============================================================
+impl WasmEcaBlock {
+    pub fn weight_segs(&self) -> Vec<(&'static str, usize)> {
+        seg_lens(self.weight_seg_dims())
+    }
+
+    /// Dims burn per segmen, sejajar weight_segs (linear [in, out], conv [out, in/groups, k..]).
+    pub fn weight_seg_dims(&self) -> Vec<(&'static str, Vec<usize>)> {
+        let rec = self.inner.clone().into_record();
+        vec![("conv.weight", param_dims(&rec.conv.weight))]
+    }
+
+    pub fn weight_layout(&self) -> String {
+        crate::layers::layout::segs_json(&self.weight_segs())
+    }
+}
@@ -1,8 +1,11 @@
 use burn::prelude::*;
-use burn::nn::conv::{Conv2d, Conv2dConfig};
-use burn::nn::PaddingConfig2d;
+use burn::nn::conv::{Conv2d, Conv2dConfig, Conv2dRecord};
+use burn::nn::{BatchNorm, BatchNormConfig, PaddingConfig2d};
+use burn::tensor::activation::relu;
 use burn::record::{BinBytesRecorder, FullPrecisionSettings, Recorder};
 use wasm_bindgen::prelude::*;
+use crate::layers::conv::conv_out_len;
+use crate::layers::custom::{check_flat_len, param_dims, push_param, seg_lens, take_param};
 use crate::{WasmBackend, WasmTensor};
 
 // --- CONFIGURATION ---
@@ -17,16 +20,32 @@ pub struct GhostModuleConfig {
     pub stride: [usize; 2],
     #[config(default = "[0, 0]")]
     pub padding: [usize; 2],
+    /// Kernel cheap op depthwise (ganjil, padding same). Paper: 3. Default 1 = perilaku lama.
+    #[config(default = 1)]
+    pub dw_kernel_size: usize,
+    /// BatchNorm setelah primary + cheap conv (paper: selalu).
+    #[config(default = false)]
+    pub batch_norm: bool,
+    /// ReLU setelah (BN) primary + cheap conv (paper: opsional per modul).
+    #[config(default = false)]
+    pub relu: bool,
 }
 
 impl GhostModuleConfig {
-    pub fn init<B: Backend>(&self, device: &B::Device) -> GhostModule<B> {
-        assert!(
-            self.out_channels % self.ratio == 0,
-            "out_channels must be divisible by ratio"
-        );
+    pub fn init<B: Backend>(&self, device: &B::Device) -> Result<GhostModule<B>, String> {
+        if self.ratio == 0 || self.out_channels == 0 {
+            return Err(format!(
+                "GhostModule: ratio ({}) and out_channels ({}) must be > 0",
+                self.ratio, self.out_channels
+            ));
+        }
+        if self.dw_kernel_size.is_multiple_of(2) {
+            return Err(format!("GhostModule: dw_kernel_size must be odd, got {}", self.dw_kernel_size));
+        }
 
-        let primary_ch = self.out_channels / self.ratio;
+        // init = ceil(out / ratio); tiap intrinsic channel menghasilkan (ratio-1) ghost map
+        let primary_ch = self.out_channels.div_ceil(self.ratio);
+        let ghost_ch = primary_ch * (self.ratio - 1);
 
         // Primary conv: full Conv2d biasa
         // Conv2dConfig::new(channels, kernel_size) — channels = [in, out]
@@ -36,24 +55,31 @@ impl GhostModuleConfig {
         );
         primary_cfg.stride = self.stride;
         primary_cfg.padding = PaddingConfig2d::Explicit(self.padding[0], self.padding[1]);
+        primary_cfg.bias = !self.batch_norm;
         let primary = primary_cfg.init(device);
 
-        // Cheap conv: depthwise (groups = primary_ch) pada output primary
-        // Kernel 1x1 untuk efisiensi maksimal, no bias
-        let mut cheap_cfg = Conv2dConfig::new(
-            [primary_ch, primary_ch],
-            [1, 1],
-        );
-        cheap_cfg.groups = primary_ch;
-        cheap_cfg.bias = false;
-        let cheap = cheap_cfg.init(device);
+        // Cheap conv: depthwise (groups = primary_ch) pada output primary, spatial tetap.
+        // ratio 1 -> tanpa ghost map.
+        let cheap = (ghost_ch > 0).then(|| {
+            let k = self.dw_kernel_size;
+            let mut cheap_cfg = Conv2dConfig::new([primary_ch, ghost_ch], [k, k]);
+            cheap_cfg.groups = primary_ch;
+            cheap_cfg.padding = PaddingConfig2d::Explicit(k / 2, k / 2);
+            cheap_cfg.bias = false;
+            cheap_cfg.init(device)
+        });
 
-        GhostModule {
+        let bn = |ch: usize| self.batch_norm.then(|| BatchNormConfig::new(ch).init(device));
+        Ok(GhostModule {
             primary,
+            primary_bn: bn(primary_ch),
+            cheap_bn: if ghost_ch > 0 { bn(ghost_ch) } else { None },
             cheap,
             ratio: self.ratio,
             primary_ch,
-        }
+            out_channels: self.out_channels,
+            relu: self.relu,
+        })
     }
 }
 
@@ -61,21 +87,101 @@ impl GhostModuleConfig {
 #[derive(Module, Debug)]
 pub struct GhostModule<B: Backend> {
     primary: Conv2d<B>,
-    cheap: Conv2d<B>,
+    primary_bn: Option<BatchNorm<B>>,
+    cheap: Option<Conv2d<B>>,
+    cheap_bn: Option<BatchNorm<B>>,
     ratio: usize,
     primary_ch: usize,
+    out_channels: usize,
+    relu: bool,
 }
 
 impl<B: Backend> GhostModule<B> {
+    fn norm_act(&self, x: Tensor<B, 4>, bn: &Option<BatchNorm<B>>) -> Tensor<B, 4> {
+        let x = match bn {
+            Some(bn) => bn.forward(x),
+            None => x,
+        };
+        if self.relu { relu(x) } else { x }
+    }
+
     pub fn forward(&self, input: Tensor<B, 4>) -> Tensor<B, 4> {
-        // 1. Primary conv → intrinsic feature maps
-        let intrinsic = self.primary.forward(input);
+        // 1. Primary conv (+BN/ReLU) → intrinsic feature maps
+        let intrinsic = self.norm_act(self.primary.forward(input), &self.primary_bn);
 
         // 2. Cheap depthwise conv pada intrinsic → ghost feature maps
-        let ghost = self.cheap.forward(intrinsic.clone());
+        let Some(cheap) = &self.cheap else {
+            return intrinsic;
+        };
+        let ghost = self.norm_act(cheap.forward(intrinsic.clone()), &self.cheap_bn);
+
+        // 3. Concat intrinsic + ghost, potong ke out_channels
+        let out = Tensor::cat(vec![intrinsic, ghost], 1);
+        if out.dims()[1] > self.out_channels {
+            out.narrow(1, 0, self.out_channels)
+        } else {
+            out
+        }
+    }
+}
+
+impl<B: Backend> GhostModule<B> {
+    /// Spatial dari primary conv (cheap conv depthwise padding same), channel = out_channels.
+    pub fn output_shape(&self, input: [usize; 4]) -> Result<[usize; 4], String> {
+        let [b, c, h, w] = input;
+        let [_, c_in, kh, kw] = self.primary.weight.dims();
+        if c != c_in {
+            return Err(format!("ghost: expected {} input channels, got {}", c_in, c));
+        }
+        let [ph, pw] = match &self.primary.padding.0 {
+            PaddingConfig2d::Explicit(a, b) => [*a, *b],
+            _ => [0, 0],
+        };
+        let p = &self.primary;
+        Ok([
+            b,
+            self.out_channels,
+            conv_out_len(h, kh, p.stride[0], ph, p.dilation[0])?,
+            conv_out_len(w, kw, p.stride[1], pw, p.dilation[1])?,
+        ])
+    }
+}
+
+// --- STATE LAMA ---
+/// Layout record sebelum opsi dw_kernel_size/BN/ReLU: state tanpa prefix `STATE_MAGIC`.
+/// Hanya record-nya yang dipakai (decode state lama).
+#[allow(dead_code)]
+#[derive(Module, Debug)]
+struct GhostModuleV0<B: Backend> {
+    primary: Conv2d<B>,
+    cheap: Conv2d<B>,
+    ratio: usize,
+    primary_ch: usize,
+}
+
+/// Prefix state versi sekarang. Byte state lama selalu diawali metadata burn (bukan 'G').
+const STATE_MAGIC: &[u8; 4] = b"GHS1";
 
-        // 3. Concat intrinsic + ghost
-        Tensor::cat(vec![intrinsic, ghost], 1)
+fn same_conv<B: Backend>(name: &str, cur: &Conv2dRecord<B>, old: &Conv2dRecord<B>) -> Result<(), String> {
+    let bias_dims = |r: &Conv2dRecord<B>| r.bias.as_ref().map(|b| b.val().dims());
+    if cur.weight.val().dims() != old.weight.val().dims() || bias_dims(cur) != bias_dims(old) {
+        return Err(format!("GhostModule: legacy state {} shape does not match config", name));
+    }
+    Ok(())
+}
+
+impl<B: Backend> GhostModule<B> {
+    /// Migrasi state lama: primary + cheap disalin, BN (kalau ada) tetap dari init.
+    fn load_legacy(self, old: GhostModuleV0Record<B>) -> Result<Self, String> {
+        let mut rec = self.clone().into_record();
+        same_conv("primary", &rec.primary, &old.primary)?;
+        match &rec.cheap {
+            Some(cheap) => same_conv("cheap", cheap, &old.cheap)?,
+            None => return Err("GhostModule: legacy state has a cheap conv but ratio is 1".into()),
+        }
+        rec.primary = old.primary;
+        rec.cheap = Some(old.cheap);
+        Ok(self.load_record(rec))
     }
 }
 
@@ -85,9 +191,21 @@ pub struct WasmGhostModule {
     inner: GhostModule<WasmBackend>,
 }
 
+impl WasmGhostModule {
+    /// Konstruksi dari config (API Rust, lihat `crate::api`).
+    pub fn from_config(config: &GhostModuleConfig) -> Result<WasmGhostModule, String> {
+        let device = Default::default();
+        Ok(WasmGhostModule {
+            inner: config.init(&device)?,
+        })
+    }
+}
+
 #[wasm_bindgen]
 impl WasmGhostModule {
+    /// Tail opsional: dw_kernel_size (default 1), batch_norm, relu (default false).
     #[wasm_bindgen(constructor)]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         in_channels: usize,
         out_channels: usize,
@@ -98,7 +216,10 @@ impl WasmGhostModule {
         stride_w: Option<usize>,
         padding_h: Option<usize>,
         padding_w: Option<usize>,
-    ) -> WasmGhostModule {
+        dw_kernel_size: Option<usize>,
+        batch_norm: Option<bool>,
+        relu: Option<bool>,
+    ) -> Result<WasmGhostModule, String> {
         let device = Default::default();
         let mut config = GhostModuleConfig::new(in_channels, out_channels, [kernel_size_h, kernel_size_w]);
         if let Some(r) = ratio {
@@ -110,9 +231,14 @@ impl WasmGhostModule {
         if let (Some(ph), Some(pw)) = (padding_h, padding_w) {
             config.padding = [ph, pw];
         }
-        WasmGhostModule {
-            inner: config.init(&device),
+        if let Some(k) = dw_kernel_size {
+            config.dw_kernel_size = k;
         }
+        config.batch_norm = batch_norm.unwrap_or(false);
+        config.relu = relu.unwrap_or(false);
+        Ok(WasmGhostModule {
+            inner: config.init(&device)?,
+        })
     }
 
     pub fn forward(&self, input: &WasmTensor) -> WasmTensor {
@@ -121,16 +247,30 @@ impl WasmGhostModule {
         WasmTensor { inner: out }
     }
 
+    #[wasm_bindgen(js_name = outputShape)]
+    pub fn output_shape(&self, shape: &[usize]) -> Result<Vec<usize>, String> {
+        self.inner.output_shape(crate::layers::pad_shape4(shape)).map(|s| s.to_vec())
+    }
+
     pub fn num_params(&self) -> usize {
         self.inner.num_params()
     }
 
+    /// Menerima state versi sekarang (prefix `GHS1`) maupun state lama tanpa prefix.
     pub fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
         let device = Default::default();
-        let record = BinBytesRecorder::<FullPrecisionSettings>::default()
-            .load(data.to_vec(), &device)
-            .map_err(|e| e.to_string())?;
-        self.inner = self.inner.clone().load_record(record);
+        let recorder = BinBytesRecorder::<FullPrecisionSettings>::default();
+        match data.strip_prefix(STATE_MAGIC) {
+            Some(body) => {
+                let record = recorder.load(body.to_vec(), &device).map_err(|e| e.to_string())?;
+                self.inner = self.inner.clone().load_record(record);
+            }
+            None => {
+                let old: GhostModuleV0Record<WasmBackend> =
+                    recorder.load(data.to_vec(), &device).map_err(|e| e.to_string())?;
+                self.inner = self.inner.clone().load_legacy(old)?;
+            }
+        }
         Ok(())
     }
 
@@ -139,6 +279,57 @@ impl WasmGhostModule {
         let bytes = BinBytesRecorder::<FullPrecisionSettings>::default()
             .record(record, ())
             .map_err(|e| e.to_string())?;
-        Ok(bytes)
+        Ok([STATE_MAGIC.as_slice(), &bytes].concat())
+    }
+}
+
+// ============================================================
+// FLOAT-BRIDGE — urutan flat: primary.weight [init, in, kh, kw], primary.bias (tanpa BN),
+// cheap.weight [ghost, 1, k, k] (ratio > 1). Affine BN ikut state (loadState), bukan di sini.
+// ============================================================
+#[wasm_bindgen]
+impl WasmGhostModule {
+    #[wasm_bindgen(js_name = getWeightsFlat)]
+    pub fn get_weights_flat(&self) -> Result<Vec<f32>, String> {
+        let rec = self.inner.clone().into_record();
+        let mut out = Vec::new();
+        push_param(&rec.primary.weight, &mut out)?;
+        if let Some(b) = &rec.primary.bias { push_param(b, &mut out)?; }
+        if let Some(cheap) = &rec.cheap { push_param(&cheap.weight, &mut out)?; }
+        Ok(out)
+    }
+
+    #[wasm_bindgen(js_name = setWeightsFlat)]
+    pub fn set_weights_flat(&mut self, data: &[f32]) -> Result<(), String> {
+        check_flat_len(&self.weight_segs(), data)?;
+        let mut rec = self.inner.clone().into_record();
+        let mut pos = 0;
+        take_param(&mut rec.primary.weight, data, &mut pos);
+        if let Some(b) = &mut rec.primary.bias { take_param(b, data, &mut pos); }
+        if let Some(cheap) = &mut rec.cheap { take_param(&mut cheap.weight, data, &mut pos); }
+        self.inner = self.inner.clone().load_record(rec);
+        Ok(())
+    }
+}
+
+// ============================================================
+// WEIGHT LAYOUT (M2) — ghost. Mirror urutan getWeightsFlat.
+// ============================================================
+impl WasmGhostModule {
+    pub fn weight_segs(&self) -> Vec<(&'static str, usize)> {
+        seg_lens(self.weight_seg_dims())
+    }
+
+    /// Dims burn per segmen, sejajar weight_segs (conv [out, in/groups, k..]).
+    pub fn weight_seg_dims(&self) -> Vec<(&'static str, Vec<usize>)> {
+        let rec = self.inner.clone().into_record();
+        let mut segs = vec![("primary.weight", param_dims(&rec.primary.weight))];
+        if let Some(b) = &rec.primary.bias { segs.push(("primary.bias", param_dims(b))); }
+        if let Some(cheap) = &rec.cheap { segs.push(("cheap.weight", param_dims(&cheap.weight))); }
+        segs
+    }
+
+    pub fn weight_layout(&self) -> String {
+        crate::layers::layout::segs_json(&self.weight_segs())
     }
 }
@@ -1,3 +1,49 @@
 pub mod shift;
 pub mod ghost;
 pub mod seblock;
+pub mod eca;
+pub mod cbam;
+pub mod spatial_attention;
+
+use burn::module::Param;
+use burn::prelude::*;
+
+// ============================================================
+// FLOAT-BRIDGE helper bersama untuk blok custom (ghost/SE/ECA/CBAM/spatial).
+// Pola sama dengan conv: lewat Module Record, urutan flat = urutan weight_segs().
+// ============================================================
+pub(crate) fn push_param<B: Backend, const D: usize>(p: &Param<Tensor<B, D>>, out: &mut Vec<f32>) -> Result<(), String> {
+    let t = <Tensor<B, D> as Clone>::clone(p).into_data();
+    out.extend(
+        t.as_slice::<f32>()
+            .map_err(|_| "getWeightsFlat: param not f32".to_string())?,
+    );
+    Ok(())
+}
+
+/// Ganti `p` dengan potongan `data[*pos..]` seukuran dims-nya, lalu majukan `pos`.
+/// Panjang total sudah dicek pemanggil (`check_flat_len`).
+pub(crate) fn take_param<B: Backend, const D: usize>(p: &mut Param<Tensor<B, D>>, data: &[f32], pos: &mut usize) {
+    let dims = p.dims();
+    let len = dims.iter().product::<usize>();
+    let device: <B as Backend>::Device = Default::default();
+    *p = Param::from_data(burn::tensor::TensorData::new(data[*pos..*pos + len].to_vec(), dims), &device);
+    *pos += len;
+}
+
+pub(crate) fn check_flat_len(segs: &[(&'static str, usize)], data: &[f32]) -> Result<(), String> {
+    let need = segs.iter().map(|(_, l)| l).sum::<usize>();
+    if data.len() != need {
+        return Err(format!("setWeightsFlat: expected {} floats, got {}", need, data.len()));
+    }
+    Ok(())
+}
+
+pub(crate) fn param_dims<B: Backend, const D: usize>(p: &Param<Tensor<B, D>>) -> Vec<usize> {
+    p.dims().to_vec()
+}
+
+/// weight_seg_dims -> weight_segs (nama, panjang flat).
+pub(crate) fn seg_lens(segs: Vec<(&'static str, Vec<usize>)>) -> Vec<(&'static str, usize)> {
+    segs.into_iter().map(|(name, dims)| (name, dims.iter().product())).collect()
+}
@@ -1,8 +1,11 @@
 use burn::prelude::*;
+use burn::module::Ignored;
 use burn::nn::{Linear, LinearConfig, Relu, Sigmoid};
 use burn::nn::pool::{AdaptiveAvgPool2d, AdaptiveAvgPool2dConfig};
 use burn::record::{BinBytesRecorder, FullPrecisionSettings, Recorder};
 use wasm_bindgen::prelude::*;
+use crate::layers::activation::ActivationConfig;
+use crate::layers::custom::{check_flat_len, param_dims, push_param, seg_lens, take_param};
 use crate::{WasmBackend, WasmTensor};
 
 // --- CONFIGURATION ---
@@ -11,36 +14,66 @@ pub struct SeBlockConfig {
     pub channels: usize,
     #[config(default = 16)]
     pub reduction: usize,
+    /// Aktivasi setelah FC1 (harus tanpa parameter).
+    #[config(default = "ActivationConfig::Relu")]
+    pub activation: ActivationConfig,
+    /// Gate setelah FC2, mis. Sigmoid / HardSigmoid (harus tanpa parameter).
+    #[config(default = "ActivationConfig::Sigmoid")]
+    pub gate: ActivationConfig,
+    /// Squeeze pakai global max pool, bukan average.
+    #[config(default = false)]
+    pub max_squeeze: bool,
+}
+
+/// PRelu/SwiGlu punya bobot -> tidak masuk float bridge blok ini, jadi ditolak.
+pub(crate) fn check_param_free(name: &str, act: &ActivationConfig) -> Result<(), String> {
+    match act {
+        ActivationConfig::PRelu(_) | ActivationConfig::SwiGlu(_) => {
+            Err(format!("{}: activation must be parameter-free", name))
+        }
+        _ => Ok(()),
+    }
 }
 
 impl SeBlockConfig {
-    pub fn init<B: Backend>(&self, device: &B::Device) -> SeBlock<B> {
-        assert!(self.channels >= self.reduction, "channels must be >= reduction");
+    pub fn init<B: Backend>(&self, device: &B::Device) -> Result<SeBlock<B>, String> {
+        if self.reduction == 0 || self.channels < self.reduction {
+            return Err(format!(
+                "SEBlock: channels ({}) must be >= reduction ({}) > 0",
+                self.channels, self.reduction
+            ));
+        }
+        check_param_free("SEBlock", &self.activation)?;
+        check_param_free("SEBlock", &self.gate)?;
         let reduced = self.channels / self.reduction;
 
         // Squeeze: Global Average Pooling (AdaptiveAvgPool2d to [1,1])
         let squeeze = AdaptiveAvgPool2dConfig::new([1, 1]).init();
 
-        // Excitation: FC1 (channels → channels/reduction) + ReLU
+        // Excitation: FC1 (channels → channels/reduction) + aktivasi
         let fc1 = LinearConfig::new(self.channels, reduced).init(device);
-        let relu = Relu::new();
 
-        // Excitation: FC2 (channels/reduction → channels) + Sigmoid
+        // Excitation: FC2 (channels/reduction → channels) + gate
         let fc2 = LinearConfig::new(reduced, self.channels).init(device);
-        let sigmoid = Sigmoid::new();
 
-        SeBlock {
+        Ok(SeBlock {
             squeeze,
             fc1,
-            relu,
+            relu: Relu::new(),
             fc2,
-            sigmoid,
+            sigmoid: Sigmoid::new(),
             channels: self.channels,
-        }
+            activation: Ignored(self.activation.clone()),
+            gate: Ignored(self.gate.clone()),
+            max_squeeze: self.max_squeeze,
+        })
     }
 }
 
 // --- MODULE ---
+// Urutan field = layout record state lama (squeeze, fc1, relu, fc2, sigmoid, channels).
+// relu/sigmoid tinggal penanda posisi; opsi baru ditaruh di belakang dan tidak membawa
+// byte state, jadi state lama tetap bisa di-load (field yang hilang -> nilai config).
 #[derive(Module, Debug)]
 pub struct SeBlock<B: Backend> {
     squeeze: AdaptiveAvgPool2d,
@@ -49,25 +82,31 @@ pub struct SeBlock<B: Backend> {
     fc2: Linear<B>,
     sigmoid: Sigmoid,
     channels: usize,
+    activation: Ignored<ActivationConfig>,
+    gate: Ignored<ActivationConfig>,
+    max_squeeze: bool,
 }
 
 impl<B: Backend> SeBlock<B> {
     pub fn forward(&self, input: Tensor<B, 4>) -> Tensor<B, 4> {
         let [b, c, _h, _w] = input.dims();
 
-        // Squeeze: Global Average Pooling → [B, C, 1, 1]
-        let pooled = self.squeeze.forward(input.clone());
+        // Squeeze: global avg/max pool → [B, C, 1, 1]
+        let pooled = if self.max_squeeze {
+            input.clone().max_dim(2).max_dim(3)
+        } else {
+            self.squeeze.forward(input.clone())
+        };
 
-        // Flatten → [B, C]
-        let flat = pooled.reshape([b, c]);
+        // Channel ke dim terakhir → [B, 1, 1, C] (Linear bekerja di dim terakhir, aktivasi 4D)
+        let flat = pooled.reshape([b, 1, 1, c]);
 
-        // Excitation: FC1 + ReLU → [B, C/r]
-        let x = self.fc1.forward(flat);
-        let x = self.relu.forward(x);
+        // Excitation: FC1 + aktivasi → [B, 1, 1, C/r] (aktivasi tanpa parameter, init murah)
+        let device = input.device();
+        let x = self.activation.init::<B>(&device).forward(self.fc1.forward(flat));
 
-        // Excitation: FC2 + Sigmoid → [B, C]
-        let x = self.fc2.forward(x);
-        let weights = self.sigmoid.forward(x);
+        // Excitation: FC2 + gate → [B, 1, 1, C]
+        let weights = self.gate.init::<B>(&device).forward(self.fc2.forward(x));
 
         // Reshape weights → [B, C, 1, 1] untuk broadcast multiply
         let weights_4d = weights.reshape([b, c, 1, 1]);
@@ -85,16 +124,15 @@ pub struct WasmSeBlock {
 
 #[wasm_bindgen]
 impl WasmSeBlock {
+    /// max_squeeze: global max pool sebagai squeeze (default avg).
     #[wasm_bindgen(constructor)]
-    pub fn new(channels: usize, reduction: Option<usize>) -> WasmSeBlock {
-        let device = Default::default();
+    pub fn new(channels: usize, reduction: Option<usize>, max_squeeze: Option<bool>) -> Result<WasmSeBlock, String> {
         let mut config = SeBlockConfig::new(channels);
         if let Some(r) = reduction {
             config.reduction = r;
         }
-        WasmSeBlock {
-            inner: config.init(&device),
-        }
+        config.max_squeeze = max_squeeze.unwrap_or(false);
+        WasmSeBlock::from_config(&config)
     }
 
     pub fn forward(&self, input: &WasmTensor) -> WasmTensor {
@@ -124,3 +162,66 @@ impl WasmSeBlock {
         Ok(bytes)
     }
 }
+
+impl WasmSeBlock {
+    /// Dipakai registry untuk opsi aktivasi/gate (ActivationConfig tidak diekspos ke JS).
+    pub fn from_config(config: &SeBlockConfig) -> Result<WasmSeBlock, String> {
+        let device = Default::default();
+        Ok(WasmSeBlock {
+            inner: config.init(&device)?,
+        })
+    }
+}
+
+// ============================================================
+// FLOAT-BRIDGE — urutan flat: fc1.weight [C, C/r], fc1.bias, fc2.weight [C/r, C], fc2.bias.
+// ============================================================
+#[wasm_bindgen]
+impl WasmSeBlock {
+    #[wasm_bindgen(js_name = getWeightsFlat)]
+    pub fn get_weights_flat(&self) -> Result<Vec<f32>, String> {
+        let rec = self.inner.clone().into_record();
+        let mut out = Vec::new();
+        for fc in [&rec.fc1, &rec.fc2] {
+            push_param(&fc.weight, &mut out)?;
+            if let Some(b) = &fc.bias { push_param(b, &mut out)?; }
+        }
+        Ok(out)
+    }
+
+    #[wasm_bindgen(js_name = setWeightsFlat)]
+    pub fn set_weights_flat(&mut self, data: &[f32]) -> Result<(), String> {
+        check_flat_len(&self.weight_segs(), data)?;
+        let mut rec = self.inner.clone().into_record();
+        let mut pos = 0;
+        for fc in [&mut rec.fc1, &mut rec.fc2] {
+            take_param(&mut fc.weight, data, &mut pos);
+            if let Some(b) = &mut fc.bias { take_param(b, data, &mut pos); }
+        }
+        self.inner = self.inner.clone().load_record(rec);
+        Ok(())
+    }
+}
+
+// ============================================================
+// WEIGHT LAYOUT (M2) — seblock. Mirror urutan getWeightsFlat.
+// ============================================================
+impl WasmSeBlock {
+    pub fn weight_segs(&self) -> Vec<(&'static str, usize)> {
+        seg_lens(self.weight_seg_dims())
+    }
+
+    /// Dims burn per segmen, sejajar weight_segs (linear [in, out], conv [out, in/groups, k..]).
+    pub fn weight_seg_dims(&self) -> Vec<(&'static str, Vec<usize>)> {
+        let rec = self.inner.clone().into_record();
+        let mut segs = vec![("fc1.weight", param_dims(&rec.fc1.weight))];
+        if let Some(b) = &rec.fc1.bias { segs.push(("fc1.bias", param_dims(b))); }
+        segs.push(("fc2.weight", param_dims(&rec.fc2.weight)));
+        if let Some(b) = &rec.fc2.bias { segs.push(("fc2.bias", param_dims(b))); }
+        segs
+    }
+
+    pub fn weight_layout(&self) -> String {
+        crate::layers::layout::segs_json(&self.weight_segs())
+    }
+}
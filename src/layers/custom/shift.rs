@@ -1,6 +1,11 @@
 use burn::prelude::*;
 use burn::tensor::Shape;
 use wasm_bindgen::prelude::*;
+use crate::protocol::{
+    PayloadWriter, SHIFT_DOWN, SHIFT_DOWN_LEFT, SHIFT_DOWN_RIGHT, SHIFT_LEFT, SHIFT_MODE_CIRCULAR, SHIFT_MODE_CONSTANT,
+    SHIFT_MODE_REPLICATE, SHIFT_RIGHT, SHIFT_GROUPED, SHIFT_UP, SHIFT_UP_LEFT, SHIFT_UP_RIGHT,
+};
+use crate::layers::layout::JsonObj;
 use crate::WasmTensor;
 
 // --- SHIFT DIRECTION ---
@@ -10,53 +15,212 @@ pub enum ShiftDirection {
     Down,
     Left,
     Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl ShiftDirection {
+    /// Byte arah sama dengan varian SHIFT_* di protocol.
+    pub fn from_code(code: u8) -> Result<Self, String> {
+        Ok(match code {
+            SHIFT_UP => ShiftDirection::Up,
+            SHIFT_DOWN => ShiftDirection::Down,
+            SHIFT_LEFT => ShiftDirection::Left,
+            SHIFT_RIGHT => ShiftDirection::Right,
+            SHIFT_UP_LEFT => ShiftDirection::UpLeft,
+            SHIFT_UP_RIGHT => ShiftDirection::UpRight,
+            SHIFT_DOWN_LEFT => ShiftDirection::DownLeft,
+            SHIFT_DOWN_RIGHT => ShiftDirection::DownRight,
+            _ => return Err(format!("Unknown shift direction: 0x{:02X}", code)),
+        })
+    }
+
+    pub fn code(self) -> u8 {
+        match self {
+            ShiftDirection::Up => SHIFT_UP,
+            ShiftDirection::Down => SHIFT_DOWN,
+            ShiftDirection::Left => SHIFT_LEFT,
+            ShiftDirection::Right => SHIFT_RIGHT,
+            ShiftDirection::UpLeft => SHIFT_UP_LEFT,
+            ShiftDirection::UpRight => SHIFT_UP_RIGHT,
+            ShiftDirection::DownLeft => SHIFT_DOWN_LEFT,
+            ShiftDirection::DownRight => SHIFT_DOWN_RIGHT,
+        }
+    }
+
+    /// Unit (dy, dx): +y = bawah, +x = kanan.
+    fn offsets(self) -> (isize, isize) {
+        match self {
+            ShiftDirection::Up => (-1, 0),
+            ShiftDirection::Down => (1, 0),
+            ShiftDirection::Left => (0, -1),
+            ShiftDirection::Right => (0, 1),
+            ShiftDirection::UpLeft => (-1, -1),
+            ShiftDirection::UpRight => (-1, 1),
+            ShiftDirection::DownLeft => (1, -1),
+            ShiftDirection::DownRight => (1, 1),
+        }
+    }
+}
+
+// --- ISI AREA KOSONG ---
+#[derive(Debug, Clone, Copy)]
+pub enum ShiftFill {
+    /// Nilai konstan (default 0 = perilaku lama)
+    Constant(f32),
+    /// Wrap-around (torus)
+    Circular,
+    /// Ulangi baris/kolom tepi
+    Replicate,
+}
+
+impl ShiftFill {
+    pub fn from_code(code: u8, value: f32) -> Result<Self, String> {
+        Ok(match code {
+            SHIFT_MODE_CONSTANT => ShiftFill::Constant(value),
+            SHIFT_MODE_CIRCULAR => ShiftFill::Circular,
+            SHIFT_MODE_REPLICATE => ShiftFill::Replicate,
+            _ => return Err(format!("Unknown shift mode: 0x{:02X}", code)),
+        })
+    }
 }
 
 // --- SHIFT LAYER (Parameter-Free) ---
+// Satu arah untuk semua channel, atau grouped: channel group i digeser ke directions[i]
+// (blok "shift conv" klasik). Sisa channel (C % groups) tidak digeser.
 #[derive(Debug)]
 pub struct Shift {
     shift_size: usize,
-    direction: ShiftDirection,
+    directions: Vec<ShiftDirection>,
+    fill: ShiftFill,
 }
 
 impl Shift {
     pub fn new(shift_size: usize, direction: ShiftDirection) -> Self {
-        Self { shift_size, direction }
+        Self { shift_size, directions: vec![direction], fill: ShiftFill::Constant(0.0) }
     }
 
-    pub fn forward<B: Backend>(&self, input: Tensor<B, 4>) -> Tensor<B, 4> {
-        let [b, c, h, w] = input.dims();
-        let s = self.shift_size;
-        let device = input.device();
-
-        // Kalau shift_size >= dimensi target, return zeros
-        match self.direction {
-            ShiftDirection::Left => {
-                if s >= w { return Tensor::zeros(Shape::from([b, c, h, w]), &device); }
-                let main = input.slice([0..b, 0..c, 0..h, s..w]);
-                let pad = Tensor::zeros(Shape::from([b, c, h, s]), &device);
-                Tensor::cat(vec![main, pad], 3)
+    pub fn grouped(shift_size: usize, directions: Vec<ShiftDirection>, fill: ShiftFill) -> Self {
+        Self { shift_size, directions, fill }
+    }
+
+    pub fn with_fill(mut self, fill: ShiftFill) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    /// (varian, config JSON) untuk describeLayer: satu arah -> SHIFT_<arah>, selain itu SHIFT_GROUPED.
+    pub(crate) fn describe(&self) -> (u8, String) {
+        let codes: Vec<u8> = self.directions.iter().map(|d| d.code()).collect();
+        let o = JsonObj::new().num("shift_size", self.shift_size).list("directions", &codes);
+        let o = match self.fill {
+            ShiftFill::Constant(v) => o.str("fill", "constant").float("value", v as f64),
+            ShiftFill::Circular => o.str("fill", "circular"),
+            ShiftFill::Replicate => o.str("fill", "replicate"),
+        };
+        let variant = if codes.len() == 1 { codes[0] } else { SHIFT_GROUPED };
+        (variant, o.finish())
+    }
+
+    /// Payload OP_INIT setelah `id` (lihat registry::init_shift); tail mode + fill selalu ditulis.
+    pub(crate) fn init_fields(&self) -> Vec<u8> {
+        let mut w = PayloadWriter::new().usize(self.shift_size);
+        if self.directions.len() != 1 {
+            w = w.usize(self.directions.len());
+            for d in &self.directions {
+                w = w.u8(d.code());
             }
-            ShiftDirection::Right => {
-                if s >= w { return Tensor::zeros(Shape::from([b, c, h, w]), &device); }
-                let pad = Tensor::zeros(Shape::from([b, c, h, s]), &device);
-                let main = input.slice([0..b, 0..c, 0..h, 0..(w - s)]);
-                Tensor::cat(vec![pad, main], 3)
+        }
+        let (mode, fill) = match self.fill {
+            ShiftFill::Constant(v) => (SHIFT_MODE_CONSTANT, v as f64),
+            ShiftFill::Circular => (SHIFT_MODE_CIRCULAR, 0.0),
+            ShiftFill::Replicate => (SHIFT_MODE_REPLICATE, 0.0),
+        };
+        w.u8(mode).f64(fill).finish()
+    }
+
+    /// Geser sepanjang `dim` sejauh `offset` (tanda = arah).
+    fn shift_dim<B: Backend>(&self, x: Tensor<B, 4>, dim: usize, offset: isize) -> Tensor<B, 4> {
+        let n = x.dims()[dim];
+        let s = offset.unsigned_abs();
+        if s == 0 || n == 0 {
+            return x;
+        }
+        let forward = offset > 0;
+        match self.fill {
+            ShiftFill::Circular => {
+                let s = s % n;
+                if s == 0 {
+                    return x;
+                }
+                // maju s = ambil s elemen terakhir ke depan
+                let cut = if forward { n - s } else { s };
+                let head = x.clone().narrow(dim, 0, cut);
+                let tail = x.narrow(dim, cut, n - cut);
+                Tensor::cat(vec![tail, head], dim)
             }
-            ShiftDirection::Up => {
-                if s >= h { return Tensor::zeros(Shape::from([b, c, h, w]), &device); }
-                let main = input.slice([0..b, 0..c, s..h, 0..w]);
-                let pad = Tensor::zeros(Shape::from([b, c, s, w]), &device);
-                Tensor::cat(vec![main, pad], 2)
+            ShiftFill::Constant(v) => {
+                let mut pad_shape = x.dims();
+                pad_shape[dim] = s.min(n);
+                let pad = Tensor::full(Shape::from(pad_shape), v, &x.device());
+                if s >= n {
+                    return pad;
+                }
+                if forward {
+                    Tensor::cat(vec![pad, x.narrow(dim, 0, n - s)], dim)
+                } else {
+                    Tensor::cat(vec![x.narrow(dim, s, n - s), pad], dim)
+                }
             }
-            ShiftDirection::Down => {
-                if s >= h { return Tensor::zeros(Shape::from([b, c, h, w]), &device); }
-                let pad = Tensor::zeros(Shape::from([b, c, s, w]), &device);
-                let main = input.slice([0..b, 0..c, 0..(h - s), 0..w]);
-                Tensor::cat(vec![pad, main], 2)
+            ShiftFill::Replicate => {
+                let s = s.min(n);
+                if forward {
+                    let edge = x.clone().narrow(dim, 0, 1).repeat_dim(dim, s);
+                    if s == n {
+                        return edge;
+                    }
+                    Tensor::cat(vec![edge, x.narrow(dim, 0, n - s)], dim)
+                } else {
+                    let edge = x.clone().narrow(dim, n - 1, 1).repeat_dim(dim, s);
+                    if s == n {
+                        return edge;
+                    }
+                    Tensor::cat(vec![x.narrow(dim, s, n - s), edge], dim)
+                }
             }
         }
     }
+
+    fn shift_one<B: Backend>(&self, x: Tensor<B, 4>, direction: ShiftDirection) -> Tensor<B, 4> {
+        let (dy, dx) = direction.offsets();
+        let s = self.shift_size as isize;
+        let x = self.shift_dim(x, 2, dy * s);
+        self.shift_dim(x, 3, dx * s)
+    }
+
+    pub fn forward<B: Backend>(&self, input: Tensor<B, 4>) -> Tensor<B, 4> {
+        if let [direction] = self.directions[..] {
+            return self.shift_one(input, direction);
+        }
+        let c = input.dims()[1];
+        let g = c / self.directions.len().max(1);
+        if g == 0 {
+            return input;
+        }
+        let mut parts: Vec<Tensor<B, 4>> = self
+            .directions
+            .iter()
+            .enumerate()
+            .map(|(i, &d)| self.shift_one(input.clone().narrow(1, i * g, g), d))
+            .collect();
+        let used = g * self.directions.len();
+        if used < c {
+            parts.push(input.narrow(1, used, c - used));
+        }
+        Tensor::cat(parts, 1)
+    }
 }
 
 // --- WASM WRAPPER ---
@@ -65,6 +229,12 @@ pub struct WasmShift {
     inner: Shift,
 }
 
+impl From<Shift> for WasmShift {
+    fn from(inner: Shift) -> Self {
+        WasmShift { inner }
+    }
+}
+
 #[wasm_bindgen]
 impl WasmShift {
     #[wasm_bindgen(js_name = newShiftUp)]
@@ -87,6 +257,29 @@ impl WasmShift {
         WasmShift { inner: Shift::new(shift_size, ShiftDirection::Right) }
     }
 
+    /// direction: byte SHIFT_* (termasuk diagonal). mode: 0 = constant(fill), 1 = circular, 2 = replicate.
+    #[wasm_bindgen(js_name = newShift)]
+    pub fn new_shift(shift_size: usize, direction: u8, mode: Option<u8>, fill: Option<f64>) -> Result<WasmShift, String> {
+        let fill = ShiftFill::from_code(mode.unwrap_or(SHIFT_MODE_CONSTANT), fill.unwrap_or(0.0) as f32)?;
+        Ok(WasmShift { inner: Shift::new(shift_size, ShiftDirection::from_code(direction)?).with_fill(fill) })
+    }
+
+    /// Channel group i digeser ke directions[i]; jumlah group = directions.length.
+    #[wasm_bindgen(js_name = newGroupedShift)]
+    pub fn new_grouped_shift(
+        shift_size: usize,
+        directions: &[u8],
+        mode: Option<u8>,
+        fill: Option<f64>,
+    ) -> Result<WasmShift, String> {
+        if directions.is_empty() {
+            return Err("Grouped shift: need at least one direction".into());
+        }
+        let dirs = directions.iter().map(|&d| ShiftDirection::from_code(d)).collect::<Result<Vec<_>, _>>()?;
+        let fill = ShiftFill::from_code(mode.unwrap_or(SHIFT_MODE_CONSTANT), fill.unwrap_or(0.0) as f32)?;
+        Ok(WasmShift { inner: Shift::grouped(shift_size, dirs, fill) })
+    }
+
     pub fn forward(&self, input: &WasmTensor) -> WasmTensor {
         let x = input.inner.clone();
         let out = self.inner.forward(x);
@@ -98,4 +291,3 @@ impl WasmShift {
         0
     }
 }
-
@@ -0,0 +1,149 @@
+use burn::prelude::*;
+use burn::nn::conv::{Conv2d, Conv2dConfig};
+use burn::nn::PaddingConfig2d;
+use burn::record::{BinBytesRecorder, FullPrecisionSettings, Recorder};
+use burn::tensor::activation::sigmoid;
+use wasm_bindgen::prelude::*;
+use crate::layers::custom::{check_flat_len, param_dims, push_param, seg_lens, take_param};
+use crate::{WasmBackend, WasmTensor};
+
+// --- CONFIGURATION ---
+// Spatial attention (CBAM): peta [mean_c, max_c] → conv k x k → sigmoid → skala per piksel.
+#[derive(Config, Debug)]
+pub struct SpatialAttentionConfig {
+    #[config(default = 7)]
+    pub kernel_size: usize,
+}
+
+impl SpatialAttentionConfig {
+    pub fn init<B: Backend>(&self, device: &B::Device) -> Result<SpatialAttention<B>, String> {
+        let k = self.kernel_size;
+        if k.is_multiple_of(2) {
+            return Err(format!("SpatialAttention: kernel_size must be odd, got {}", k));
+        }
+        let conv = Conv2dConfig::new([2, 1], [k, k])
+            .with_padding(PaddingConfig2d::Explicit(k / 2, k / 2))
+            .with_bias(false)
+            .init(device);
+        Ok(SpatialAttention { conv })
+    }
+}
+
+// --- MODULE ---
+#[derive(Module, Debug)]
+pub struct SpatialAttention<B: Backend> {
+    pub(crate) conv: Conv2d<B>,
+}
+
+impl<B: Backend> SpatialAttention<B> {
+    pub fn forward(&self, input: Tensor<B, 4>) -> Tensor<B, 4> {
+        // Descriptor lintas channel → [B, 2, H, W]
+        let avg = input.clone().mean_dim(1);
+        let max = input.clone().max_dim(1);
+        let desc = Tensor::cat(vec![avg, max], 1);
+
+        // Conv + sigmoid → [B, 1, H, W], broadcast ke semua channel
+        let weights = sigmoid(self.conv.forward(desc));
+        input * weights
+    }
+}
+
+// --- WASM WRAPPER ---
+#[wasm_bindgen]
+pub struct WasmSpatialAttention {
+    inner: SpatialAttention<WasmBackend>,
+}
+
+impl WasmSpatialAttention {
+    /// Konstruksi dari config (API Rust, lihat `crate::api`).
+    pub fn from_config(config: &SpatialAttentionConfig) -> Result<WasmSpatialAttention, String> {
+        let device = Default::default();
+        Ok(WasmSpatialAttention {
+            inner: config.init(&device)?,
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl WasmSpatialAttention {
+    #[wasm_bindgen(constructor)]
+    pub fn new(kernel_size: Option<usize>) -> Result<WasmSpatialAttention, String> {
+        let device = Default::default();
+        let mut config = SpatialAttentionConfig::new();
+        if let Some(k) = kernel_size {
+            config.kernel_size = k;
+        }
+        Ok(WasmSpatialAttention {
+            inner: config.init(&device)?,
+        })
+    }
+
+    pub fn forward(&self, input: &WasmTensor) -> WasmTensor {
+        let x = input.inner.clone();
+        let out = self.inner.forward(x);
+        WasmTensor { inner: out }
+    }
+
+    pub fn num_params(&self) -> usize {
+        self.inner.num_params()
+    }
+
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+        let device = Default::default();
+        let record = BinBytesRecorder::<FullPrecisionSettings>::default()
+            .load(data.to_vec(), &device)
+            .map_err(|e| e.to_string())?;
+        self.inner = self.inner.clone().load_record(record);
+        Ok(())
+    }
+
+    pub fn get_state(&self) -> Result<Vec<u8>, String> {
+        let record = self.inner.clone().into_record();
+        let bytes = BinBytesRecorder::<FullPrecisionSettings>::default()
+            .record(record, ())
+            .map_err(|e| e.to_string())?;
+        Ok(bytes)
+    }
+}
+
+// ============================================================
+// FLOAT-BRIDGE — urutan flat: conv.weight [1, 2, k, k] (tanpa bias).
+// ============================================================
+#[wasm_bindgen]
+impl WasmSpatialAttention {
+    #[wasm_bindgen(js_name = getWeightsFlat)]
+    pub fn get_weights_flat(&self) -> Result<Vec<f32>, String> {
+        let rec = self.inner.clone().into_record();
+        let mut out = Vec::new();
+        push_param(&rec.conv.weight, &mut out)?;
+        Ok(out)
+    }
+
+    #[wasm_bindgen(js_name = setWeightsFlat)]
+    pub fn set_weights_flat(&mut self, data: &[f32]) -> Result<(), String> {
+        check_flat_len(&self.weight_segs(), data)?;
+        let mut rec = self.inner.clone().into_record();
+        take_param(&mut rec.conv.weight, data, &mut 0);
+        self.inner = self.inner.clone().load_record(rec);
+        Ok(())
+    }
+}
+
+// ============================================================
+// WEIGHT LAYOUT (M2) — spatial attention. Mirror urutan getWeightsFlat.
+// ============================================================
+impl WasmSpatialAttention {
+    pub fn weight_segs(&self) -> Vec<(&'static str, usize)> {
+        seg_lens(self.weight_seg_dims())
+    }
+
+    /// Dims burn per segmen, sejajar weight_segs (linear [in, out], conv [out, in/groups, k..]).
+    pub fn weight_seg_dims(&self) -> Vec<(&'static str, Vec<usize>)> {
+        let rec = self.inner.clone().into_record();
+        vec![("conv.weight", param_dims(&rec.conv.weight))]
+    }
+
+    pub fn weight_layout(&self) -> String {
+        crate::layers::layout::segs_json(&self.weight_segs())
+    }
+}
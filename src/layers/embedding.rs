@@ -74,6 +74,16 @@ impl WasmEmbedding {
         WasmTensor { inner: out }
     }
 
+    /// Indeks [B, S, 1, 1] -> [B, S, d_model, 1].
+    #[wasm_bindgen(js_name = outputShape)]
+    pub fn output_shape(&self, shape: &[usize]) -> Result<Vec<usize>, String> {
+        let [b, s, h, w] = crate::layers::pad_shape4(shape);
+        if h * w != 1 {
+            return Err(format!("embedding: expected [B, S, 1, 1], got {:?}", shape));
+        }
+        Ok(vec![b, s, self.weight_dims()[1], 1])
+    }
+
     pub fn num_params(&self) -> usize {
         self.inner.num_params()
     }
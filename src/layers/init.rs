@@ -0,0 +1,148 @@
+// ============================================================
+// INIT SCHEME — isi ulang bobot setelah layer dibuat (trailer paket OP_INIT / initWeights).
+// Semua acak lewat es::rng::Rng (mulberry32): seed sama -> bobot identik di host & wasm.
+// ============================================================
+
+use crate::es::rng::Rng;
+use crate::protocol::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InitScheme {
+    /// Biarkan init bawaan burn.
+    Default,
+    Zeros,
+    Constant(f32),
+    Normal { std: f32 },
+    /// U(-bound, bound).
+    Uniform { bound: f32 },
+    /// He: N(0, gain² · 2 / fan_in).
+    Kaiming { gain: f32 },
+    /// Glorot: U(±gain · sqrt(6 / (fan_in + fan_out))).
+    Xavier { gain: f32 },
+    /// Baris (atau kolom, mana yang lebih sedikit) ortonormal, dikali gain.
+    Orthogonal { gain: f32 },
+}
+
+/// Bentuk weight sebagai matriks rows x cols (flat row-major) + fan untuk Kaiming/Xavier.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WeightShape {
+    pub rows: usize,
+    pub cols: usize,
+    pub fan_in: usize,
+    pub fan_out: usize,
+}
+
+impl WeightShape {
+    /// Konvensi PyTorch untuk tensor [out, in, k..]: fan_in = in · Πk, fan_out = out · Πk.
+    pub fn from_dims(dims: &[usize]) -> Self {
+        let rows = dims.first().copied().unwrap_or(1);
+        let cols = dims.iter().skip(1).product::<usize>();
+        let receptive = dims.iter().skip(2).product::<usize>();
+        WeightShape { rows, cols, fan_in: cols, fan_out: rows * receptive }
+    }
+
+    /// Linear burn menyimpan weight [d_in, d_out] (kebalikan PyTorch).
+    pub fn linear(d_in: usize, d_out: usize) -> Self {
+        WeightShape { rows: d_in, cols: d_out, fan_in: d_in, fan_out: d_out }
+    }
+
+    /// Vektor polos (mis. mean awal ES).
+    pub fn vector(n: usize) -> Self {
+        WeightShape { rows: 1, cols: n, fan_in: n, fan_out: 1 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows * self.cols
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl InitScheme {
+    /// Dari kode INIT_* + param trailer; gain 0 -> 1.
+    pub fn from_code(code: u8, param: f32) -> Result<Self, String> {
+        let gain = if param == 0.0 { 1.0 } else { param };
+        Ok(match code {
+            INIT_DEFAULT    => InitScheme::Default,
+            INIT_ZEROS      => InitScheme::Zeros,
+            INIT_CONSTANT   => InitScheme::Constant(param),
+            INIT_NORMAL     => InitScheme::Normal { std: param },
+            INIT_UNIFORM    => InitScheme::Uniform { bound: param },
+            INIT_KAIMING    => InitScheme::Kaiming { gain },
+            INIT_XAVIER     => InitScheme::Xavier { gain },
+            INIT_ORTHOGONAL => InitScheme::Orthogonal { gain },
+            _ => return Err(format!("Unknown init scheme: 0x{:02X}", code)),
+        })
+    }
+
+    /// Trailer OP_INIT (lihat FLAG_INIT_SCHEME) -> (skema, seed).
+    pub fn from_trailer(trailer: &[u8]) -> Result<(Self, u32), String> {
+        let mut c = PayloadCursor::new(trailer);
+        let code = c.read_u8()?;
+        let seed = c.read_u32()?;
+        Ok((InitScheme::from_code(code, c.read_f32()?)?, seed))
+    }
+
+    /// Param harus finite; std/bound/gain tidak boleh negatif.
+    pub fn validate(&self) -> Result<(), String> {
+        let (name, v, signed) = match *self {
+            InitScheme::Default | InitScheme::Zeros => return Ok(()),
+            InitScheme::Constant(v) => ("constant", v, true),
+            InitScheme::Normal { std } => ("normal std", std, false),
+            InitScheme::Uniform { bound } => ("uniform bound", bound, false),
+            InitScheme::Kaiming { gain } | InitScheme::Xavier { gain } | InitScheme::Orthogonal { gain } => ("gain", gain, false),
+        };
+        if !v.is_finite() || (!signed && v < 0.0) {
+            return Err(format!("init scheme: {} must be finite{}, got {}", name, if signed { "" } else { " and >= 0" }, v));
+        }
+        Ok(())
+    }
+
+    /// Nilai bobot baru untuk `shape`; Default -> None (bobot tidak disentuh).
+    pub fn fill(&self, rng: &mut Rng, shape: &WeightShape) -> Option<Vec<f32>> {
+        let n = shape.len();
+        let normal = |rng: &mut Rng, std: f32| (0..n).map(|_| rng.gaussian() * std).collect();
+        let uniform = |rng: &mut Rng, a: f32| (0..n).map(|_| (rng.uniform() * 2.0 - 1.0) * a).collect();
+        Some(match *self {
+            InitScheme::Default => return None,
+            InitScheme::Zeros => vec![0.0; n],
+            InitScheme::Constant(v) => vec![v; n],
+            InitScheme::Normal { std } => normal(rng, std),
+            InitScheme::Uniform { bound } => uniform(rng, bound),
+            InitScheme::Kaiming { gain } => normal(rng, gain * (2.0 / shape.fan_in.max(1) as f32).sqrt()),
+            InitScheme::Xavier { gain } => {
+                uniform(rng, gain * (6.0 / (shape.fan_in + shape.fan_out).max(1) as f32).sqrt())
+            }
+            InitScheme::Orthogonal { gain } => orthogonal(rng, shape.rows, shape.cols, gain),
+        })
+    }
+}
+
+/// Gaussian rows x cols lalu Gram-Schmidt (modified) pada sisi yang lebih pendek.
+fn orthogonal(rng: &mut Rng, rows: usize, cols: usize, gain: f32) -> Vec<f32> {
+    let (k, len) = if rows <= cols { (rows, cols) } else { (cols, rows) };
+    let mut vecs: Vec<Vec<f64>> = (0..k).map(|_| (0..len).map(|_| rng.gaussian() as f64).collect()).collect();
+    for i in 0..k {
+        let (done, rest) = vecs.split_at_mut(i);
+        let v = &mut rest[0];
+        for u in done.iter() {
+            let dot: f64 = u.iter().zip(v.iter()).map(|(a, b)| a * b).sum();
+            v.iter_mut().zip(u).for_each(|(x, a)| *x -= dot * a);
+        }
+        let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if norm > 1e-12 {
+            v.iter_mut().for_each(|x| *x /= norm);
+        }
+    }
+    let mut out = vec![0.0f32; rows * cols];
+    for (i, v) in vecs.iter().enumerate() {
+        for (j, &x) in v.iter().enumerate() {
+            // rows <= cols: vecs = baris; selain itu vecs = kolom
+            let idx = if rows <= cols { i * cols + j } else { j * cols + i };
+            out[idx] = x as f32 * gain;
+        }
+    }
+    out
+}
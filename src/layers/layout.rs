@@ -26,3 +26,86 @@ pub fn segs_json(segs: &[(&'static str, usize)]) -> String {
     s.push(']');
     s
 }
+
+/// Builder objek JSON satu level untuk config/describe layer (gaya sama dengan segs_json):
+/// nilai angka/bool apa adanya, Option None -> null, `raw` untuk sub-JSON yang sudah jadi.
+/// Key & string varian alfanumerik -> tidak butuh escape.
+pub struct JsonObj(String);
+
+impl Default for JsonObj {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonObj {
+    pub fn new() -> Self {
+        JsonObj(String::from("{"))
+    }
+
+    pub fn raw(mut self, key: &str, json: &str) -> Self {
+        if self.0.len() > 1 {
+            self.0.push(',');
+        }
+        self.0.push('"');
+        self.0.push_str(key);
+        self.0.push_str("\":");
+        self.0.push_str(json);
+        self
+    }
+
+    pub fn num<T: std::fmt::Display>(self, key: &str, v: T) -> Self {
+        self.raw(key, &v.to_string())
+    }
+
+    /// Angka float: non-finite -> null (JSON tidak punya NaN/inf).
+    pub fn float(self, key: &str, v: f64) -> Self {
+        if v.is_finite() { self.num(key, v) } else { self.raw(key, "null") }
+    }
+
+    pub fn opt<T: std::fmt::Display>(self, key: &str, v: Option<T>) -> Self {
+        match v {
+            Some(v) => self.num(key, v),
+            None => self.raw(key, "null"),
+        }
+    }
+
+    pub fn str(self, key: &str, v: &str) -> Self {
+        self.raw(key, &format!("\"{}\"", v))
+    }
+
+    pub fn list<T: std::fmt::Display>(self, key: &str, v: &[T]) -> Self {
+        let items = v.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(",");
+        self.raw(key, &format!("[{}]", items))
+    }
+
+    pub fn finish(mut self) -> String {
+        self.0.push('}');
+        self.0
+    }
+}
+
+/// JSON pretty dari burn `Config` (config_to_json) dipadatkan jadi satu baris.
+pub fn config_json<C: burn::config::Config>(config: &C) -> String {
+    let pretty = burn::config::config_to_json(config);
+    let mut out = String::with_capacity(pretty.len());
+    let mut in_str = false;
+    let mut escaped = false;
+    for ch in pretty.chars() {
+        if in_str {
+            out.push(ch);
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_str = false,
+                _ => {}
+            }
+        } else if ch == '"' {
+            in_str = true;
+            out.push(ch);
+        } else if !ch.is_whitespace() {
+            out.push(ch);
+        }
+    }
+    out
+}
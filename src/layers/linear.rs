@@ -62,6 +62,17 @@ impl WasmLinear {
         WasmTensor { inner: out_4d }
     }
 
+    /// [B, d_in, 1, 1] -> [B, d_out, 1, 1] (forward me-reshape ke [B, d_in]).
+    #[wasm_bindgen(js_name = outputShape)]
+    pub fn output_shape(&self, shape: &[usize]) -> Result<Vec<usize>, String> {
+        let [b, d, h, w] = crate::layers::pad_shape4(shape);
+        let dims = self.weight_dims();
+        if d != dims[0] || h * w != 1 {
+            return Err(format!("linear: expected [B, {}, 1, 1], got {:?}", dims[0], shape));
+        }
+        Ok(vec![b, dims[1], 1, 1])
+    }
+
     pub fn num_params(&self) -> usize {
         self.inner.num_params()
     }
@@ -172,4 +183,26 @@ impl WasmLinear {
     pub fn weight_layout(&self) -> String {
         crate::layers::layout::segs_json(&self.weight_segs())
     }
+
+    /// Salinan dengan kolom weight[.., j] *= scale[j] dan bias' = bias * scale + shift
+    /// (fold BatchNorm eval); tanpa bias -> shift dikembalikan. d_out beda -> None.
+    pub(crate) fn folded(&self, scale: &[f32], shift: &[f32]) -> Option<(WasmLinear, Option<Vec<f32>>)> {
+        let [d_in, d_out] = self.inner.inner.weight.dims();
+        if d_out != scale.len() || d_out != shift.len() {
+            return None;
+        }
+        let mut flat = self.get_weights_flat().ok()?;
+        for (i, w) in flat[..d_in * d_out].iter_mut().enumerate() {
+            *w *= scale[i % d_out];
+        }
+        let extra = crate::layers::fold_bias(&mut flat[d_in * d_out..], scale, shift);
+        let mut linear = WasmLinear { inner: self.inner.clone() };
+        linear.set_weights_flat(&flat).ok()?;
+        Some((linear, extra))
+    }
+
+    /// Estimasi MAC untuk profiler: tiap elemen output = dot product sepanjang d_in.
+    pub fn macs(&self, out_numel: usize) -> u64 {
+        out_numel as u64 * self.inner.inner.weight.dims()[0] as u64
+    }
 }
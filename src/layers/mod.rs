@@ -4,6 +4,30 @@ pub mod conv;
 pub mod norm;
 pub mod embedding;
 pub mod pool;
+pub mod resample;
 pub mod binary;
+pub mod tensor_op;
 pub mod custom;
 pub mod layout;
+pub mod init;
+
+/// Konvensi WasmTensor: shape apa pun dipad ke rank 4 dengan trailing 1.
+pub(crate) fn pad_shape4(shape: &[usize]) -> [usize; 4] {
+    let mut dims = [1usize; 4];
+    for (i, &d) in shape.iter().enumerate().take(4) {
+        dims[i] = d;
+    }
+    dims
+}
+
+/// Fold affine per channel (y = x * scale + shift) ke bias flat: bias' = bias * scale + shift.
+/// Layer tanpa bias (slice kosong) -> shift dikembalikan untuk ditambahkan setelah layer.
+pub(crate) fn fold_bias(bias: &mut [f32], scale: &[f32], shift: &[f32]) -> Option<Vec<f32>> {
+    if bias.is_empty() {
+        return Some(shift.to_vec());
+    }
+    for ((b, s), t) in bias.iter_mut().zip(scale).zip(shift) {
+        *b = *b * s + t;
+    }
+    None
+}
@@ -281,3 +281,27 @@ impl WasmNorm {
         crate::layers::layout::segs_json(&self.weight_segs())
     }
 }
+
+// ============================================================
+// FUSION — BatchNorm eval (backend tanpa autodiff = running stats) sebagai affine per channel.
+// ============================================================
+impl WasmNorm {
+    /// (scale, shift) dengan y = x * scale + shift per channel; None untuk norm selain Batch.
+    pub(crate) fn batch_affine(&self) -> Option<(Vec<f32>, Vec<f32>)> {
+        let Normalization::Batch(bn) = &self.inner else {
+            return None;
+        };
+        let to_vec = |t: Tensor<WasmBackend, 1>| t.into_data().to_vec::<f32>().ok();
+        let gamma = to_vec(bn.gamma.val())?;
+        let beta = to_vec(bn.beta.val())?;
+        let mean = to_vec(bn.running_mean.value())?;
+        let var = to_vec(bn.running_var.value())?;
+        let scale: Vec<f32> = gamma
+            .iter()
+            .zip(&var)
+            .map(|(&g, &v)| (g as f64 / (v as f64 + bn.epsilon).sqrt()) as f32)
+            .collect();
+        let shift = beta.iter().zip(&mean).zip(&scale).map(|((&b, &m), &s)| b - m * s).collect();
+        Some((scale, shift))
+    }
+}
@@ -4,12 +4,89 @@ use burn::nn::pool::{
     MaxPool2d, MaxPool2dConfig,
     AvgPool1d, AvgPool1dConfig,
     AvgPool2d, AvgPool2dConfig,
+    AdaptiveAvgPool1d, AdaptiveAvgPool1dConfig,
     AdaptiveAvgPool2d, AdaptiveAvgPool2dConfig,
 };
 use burn::nn::{PaddingConfig1d, PaddingConfig2d};
 use wasm_bindgen::prelude::*;
 use crate::WasmTensor;
 
+// --- HELPER POOLS (tidak ada padanannya di Burn 0.20) ---
+/// Adaptive max pool 2D: bin i = [floor(i*H/oh), ceil((i+1)*H/oh)), sama seperti PyTorch.
+#[derive(Debug, Clone)]
+pub struct AdaptiveMaxPool2d {
+    pub output_size: [usize; 2],
+}
+impl AdaptiveMaxPool2d {
+    pub fn forward<B: Backend>(&self, input: Tensor<B, 4>) -> Tensor<B, 4> {
+        let [b, c, h, w] = input.dims();
+        let [oh, ow] = self.output_size;
+        let mut rows = Vec::with_capacity(oh);
+        for i in 0..oh {
+            let (h0, h1) = (i * h / oh, ((i + 1) * h).div_ceil(oh));
+            let mut cols = Vec::with_capacity(ow);
+            for j in 0..ow {
+                let (w0, w1) = (j * w / ow, ((j + 1) * w).div_ceil(ow));
+                let cell = input.clone().slice([0..b, 0..c, h0..h1, w0..w1]);
+                cols.push(cell.max_dim(2).max_dim(3));
+            }
+            rows.push(Tensor::cat(cols, 3));
+        }
+        Tensor::cat(rows, 2)
+    }
+}
+
+/// Global pool: [B, C, H, W] -> [B, C, 1, 1] (reduksi atas H dan W).
+#[derive(Debug, Clone, Copy)]
+pub enum GlobalPool {
+    Avg,
+    Max,
+}
+impl GlobalPool {
+    pub fn forward<B: Backend>(&self, input: Tensor<B, 4>) -> Tensor<B, 4> {
+        match self {
+            GlobalPool::Avg => input.mean_dim(2).mean_dim(3),
+            GlobalPool::Max => input.max_dim(2).max_dim(3),
+        }
+    }
+}
+
+// Rumus panjang output pool (identik calculate_pool_output_size Burn), tapi Err kalau kernel
+// efektif lebih besar dari input (Burn underflow/panic di kasus itu).
+fn pool_out_len(
+    size_in: usize,
+    kernel: usize,
+    stride: usize,
+    padding: usize,
+    dilation: usize,
+    ceil_mode: bool,
+) -> Result<usize, String> {
+    let span = dilation * kernel.saturating_sub(1) + 1;
+    let padded = size_in + 2 * padding;
+    if kernel == 0 || stride == 0 || span > padded {
+        return Err(format!(
+            "pool: window {} (kernel {}, dilation {}) does not fit input {} + 2*{} padding",
+            span, kernel, dilation, size_in, padding
+        ));
+    }
+    let num = padded - span;
+    Ok(if ceil_mode { num.div_ceil(stride) + 1 } else { num / stride + 1 })
+}
+
+fn explicit_1d(p: &PaddingConfig1d) -> usize {
+    match p {
+        PaddingConfig1d::Explicit(v) => *v,
+        _ => 0,
+    }
+}
+
+fn explicit_2d(p: &PaddingConfig2d) -> [usize; 2] {
+    match p {
+        PaddingConfig2d::Explicit(a, b) => [*a, *b],
+        _ => [0, 0],
+    }
+}
+
 // --- CONFIGURATION ENUM ---
 #[derive(Debug)]
 pub enum PoolingConfig {
@@ -18,6 +95,9 @@ pub enum PoolingConfig {
     AvgPool1d(AvgPool1dConfig),
     AvgPool2d(AvgPool2dConfig),
     AdaptiveAvgPool2d(AdaptiveAvgPool2dConfig),
+    AdaptiveMaxPool2d([usize; 2]),
+    AdaptiveAvgPool1d(AdaptiveAvgPool1dConfig),
+    Global(GlobalPool),
 }
 
 impl PoolingConfig {
@@ -29,6 +109,11 @@ impl PoolingConfig {
             PoolingConfig::AvgPool1d(c) => Pooling::AvgPool1d(c.init()),
             PoolingConfig::AvgPool2d(c) => Pooling::AvgPool2d(c.init()),
             PoolingConfig::AdaptiveAvgPool2d(c) => Pooling::AdaptiveAvgPool2d(c.init()),
+            PoolingConfig::AdaptiveMaxPool2d(size) => {
+                Pooling::AdaptiveMaxPool2d(AdaptiveMaxPool2d { output_size: *size })
+            }
+            PoolingConfig::AdaptiveAvgPool1d(c) => Pooling::AdaptiveAvgPool1d(c.init()),
+            PoolingConfig::Global(g) => Pooling::Global(*g),
         }
     }
 }
@@ -42,6 +127,9 @@ pub enum Pooling {
     AvgPool1d(AvgPool1d),
     AvgPool2d(AvgPool2d),
     AdaptiveAvgPool2d(AdaptiveAvgPool2d),
+    AdaptiveMaxPool2d(AdaptiveMaxPool2d),
+    AdaptiveAvgPool1d(AdaptiveAvgPool1d),
+    Global(GlobalPool),
 }
 
 impl Pooling {
@@ -52,6 +140,8 @@ impl Pooling {
             Pooling::MaxPool2d(layer) => layer.forward(input),
             Pooling::AvgPool2d(layer) => layer.forward(input),
             Pooling::AdaptiveAvgPool2d(layer) => layer.forward(input),
+            Pooling::AdaptiveMaxPool2d(layer) => layer.forward(input),
+            Pooling::Global(layer) => layer.forward(input),
 
             // 1D pooling butuh 3D [Batch, Channel, Length]
             // Squeeze dimensi terakhir (width), pool, lalu unsqueeze balik
@@ -69,8 +159,52 @@ impl Pooling {
                 let [b_out, c_out, l_out] = out.dims();
                 out.reshape([b_out, c_out, l_out, 1])
             }
+            Pooling::AdaptiveAvgPool1d(layer) => {
+                let [b, c, h, _w] = input.dims();
+                let x_3d = input.reshape([b, c, h]);
+                let out = layer.forward(x_3d);
+                let [b_out, c_out, l_out] = out.dims();
+                out.reshape([b_out, c_out, l_out, 1])
+            }
         }
     }
+
+    /// Shape inference tanpa menjalankan forward. 1D memakai konvensi [B, C, L, 1].
+    pub fn output_shape(&self, input: [usize; 4]) -> Result<[usize; 4], String> {
+        let [b, c, h, w] = input;
+        Ok(match self {
+            Pooling::MaxPool1d(l) => {
+                let p = explicit_1d(&l.padding);
+                [b, c, pool_out_len(h, l.kernel_size, l.stride, p, l.dilation, l.ceil_mode)?, 1]
+            }
+            Pooling::AvgPool1d(l) => {
+                let p = explicit_1d(&l.padding);
+                [b, c, pool_out_len(h, l.kernel_size, l.stride, p, 1, l.ceil_mode)?, 1]
+            }
+            Pooling::MaxPool2d(l) => {
+                let [ph, pw] = explicit_2d(&l.padding);
+                [
+                    b,
+                    c,
+                    pool_out_len(h, l.kernel_size[0], l.stride[0], ph, l.dilation[0], l.ceil_mode)?,
+                    pool_out_len(w, l.kernel_size[1], l.stride[1], pw, l.dilation[1], l.ceil_mode)?,
+                ]
+            }
+            Pooling::AvgPool2d(l) => {
+                let [ph, pw] = explicit_2d(&l.padding);
+                [
+                    b,
+                    c,
+                    pool_out_len(h, l.kernel_size[0], l.stride[0], ph, 1, l.ceil_mode)?,
+                    pool_out_len(w, l.kernel_size[1], l.stride[1], pw, 1, l.ceil_mode)?,
+                ]
+            }
+            Pooling::AdaptiveAvgPool2d(l) => [b, c, l.output_size[0], l.output_size[1]],
+            Pooling::AdaptiveMaxPool2d(l) => [b, c, l.output_size[0], l.output_size[1]],
+            Pooling::AdaptiveAvgPool1d(l) => [b, c, l.output_size, 1],
+            Pooling::Global(_) => [b, c, 1, 1],
+        })
+    }
 }
 
 // --- WASM WRAPPER ---
@@ -86,6 +220,8 @@ impl WasmPool {
         kernel_size: usize,
         stride: Option<usize>,
         padding: Option<usize>,
+        dilation: Option<usize>,
+        ceil_mode: Option<bool>,
     ) -> WasmPool {
         let mut config = MaxPool1dConfig::new(kernel_size);
         if let Some(s) = stride {
@@ -94,6 +230,12 @@ impl WasmPool {
         if let Some(p) = padding {
             config = config.with_padding(PaddingConfig1d::Explicit(p));
         }
+        if let Some(d) = dilation {
+            config = config.with_dilation(d);
+        }
+        if let Some(cm) = ceil_mode {
+            config = config.with_ceil_mode(cm);
+        }
         WasmPool {
             inner: PoolingConfig::MaxPool1d(config).init(),
         }
@@ -101,6 +243,7 @@ impl WasmPool {
 
     // [usize; 2] dipecah jadi 2 parameter karena wasm_bindgen tidak support array
     #[wasm_bindgen(js_name = newMaxPool2d)]
+    #[allow(clippy::too_many_arguments)]
     pub fn new_max_pool2d(
         kernel_size_h: usize,
         kernel_size_w: usize,
@@ -108,6 +251,9 @@ impl WasmPool {
         stride_w: Option<usize>,
         padding_h: Option<usize>,
         padding_w: Option<usize>,
+        dilation_h: Option<usize>,
+        dilation_w: Option<usize>,
+        ceil_mode: Option<bool>,
     ) -> WasmPool {
         let mut config = MaxPool2dConfig::new([kernel_size_h, kernel_size_w]);
         if let (Some(sh), Some(sw)) = (stride_h, stride_w) {
@@ -116,6 +262,12 @@ impl WasmPool {
         if let (Some(ph), Some(pw)) = (padding_h, padding_w) {
             config = config.with_padding(PaddingConfig2d::Explicit(ph, pw));
         }
+        if let (Some(dh), Some(dw)) = (dilation_h, dilation_w) {
+            config = config.with_dilation([dh, dw]);
+        }
+        if let Some(cm) = ceil_mode {
+            config = config.with_ceil_mode(cm);
+        }
         WasmPool {
             inner: PoolingConfig::MaxPool2d(config).init(),
         }
@@ -171,12 +323,50 @@ impl WasmPool {
         }
     }
 
+    #[wasm_bindgen(js_name = newAdaptiveMaxPool2d)]
+    pub fn new_adaptive_max_pool2d(
+        output_size_h: usize,
+        output_size_w: usize,
+    ) -> WasmPool {
+        WasmPool {
+            inner: PoolingConfig::AdaptiveMaxPool2d([output_size_h, output_size_w]).init(),
+        }
+    }
+
+    #[wasm_bindgen(js_name = newAdaptiveAvgPool1d)]
+    pub fn new_adaptive_avg_pool1d(output_size: usize) -> WasmPool {
+        let config = AdaptiveAvgPool1dConfig::new(output_size);
+        WasmPool {
+            inner: PoolingConfig::AdaptiveAvgPool1d(config).init(),
+        }
+    }
+
+    #[wasm_bindgen(js_name = newGlobalAvgPool)]
+    pub fn new_global_avg_pool() -> WasmPool {
+        WasmPool {
+            inner: PoolingConfig::Global(GlobalPool::Avg).init(),
+        }
+    }
+
+    #[wasm_bindgen(js_name = newGlobalMaxPool)]
+    pub fn new_global_max_pool() -> WasmPool {
+        WasmPool {
+            inner: PoolingConfig::Global(GlobalPool::Max).init(),
+        }
+    }
+
     pub fn forward(&self, input: &WasmTensor) -> WasmTensor {
         let x = input.inner.clone();
         let out = self.inner.forward(x);
         WasmTensor { inner: out }
     }
 
+    /// Shape output untuk input `shape` (dipad ke 4D seperti WasmTensor). Err kalau window tidak muat.
+    #[wasm_bindgen(js_name = outputShape)]
+    pub fn output_shape(&self, shape: &[usize]) -> Result<Vec<usize>, String> {
+        self.inner.output_shape(crate::layers::pad_shape4(shape)).map(|s| s.to_vec())
+    }
+
     // Pooling tidak punya trainable params
     pub fn num_params(&self) -> usize {
         0
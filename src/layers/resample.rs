@@ -0,0 +1,166 @@
+use burn::prelude::*;
+use burn::tensor::module::interpolate;
+use burn::tensor::ops::{InterpolateMode, InterpolateOptions};
+use wasm_bindgen::prelude::*;
+use crate::layers::layout::JsonObj;
+use crate::protocol::{PayloadWriter, RESAMPLE_BILINEAR, RESAMPLE_NEAREST, RESAMPLE_PIXEL_SHUFFLE};
+use crate::WasmTensor;
+
+// --- TARGET UKURAN ---
+#[derive(Debug, Clone, Copy)]
+pub enum ResampleSize {
+    /// out = floor(in * scale) per sumbu (H, W)
+    Scale(f64, f64),
+    /// out = (H, W) tetap
+    Size(usize, usize),
+}
+
+// --- RESAMPLE LAYER (Parameter-Free) ---
+// Upsampling/downsampling spasial untuk decoder: nearest, bilinear, pixel-shuffle.
+#[derive(Debug, Clone)]
+pub enum Resample {
+    Nearest(ResampleSize),
+    Bilinear(ResampleSize),
+    /// [B, C*r*r, H, W] -> [B, C, H*r, W*r]
+    PixelShuffle(usize),
+}
+
+impl ResampleSize {
+    fn resolve(&self, h: usize, w: usize) -> Result<[usize; 2], String> {
+        let (oh, ow) = match *self {
+            ResampleSize::Scale(sh, sw) => {
+                if !(sh.is_finite() && sw.is_finite() && sh > 0.0 && sw > 0.0) {
+                    return Err(format!("resample: scale must be finite and > 0, got ({}, {})", sh, sw));
+                }
+                ((h as f64 * sh).floor() as usize, (w as f64 * sw).floor() as usize)
+            }
+            ResampleSize::Size(oh, ow) => (oh, ow),
+        };
+        if oh == 0 || ow == 0 {
+            return Err(format!("resample: output size ({}, {}) must be > 0", oh, ow));
+        }
+        Ok([oh, ow])
+    }
+}
+
+impl Resample {
+    /// (varian RESAMPLE_*, config JSON) untuk describeLayer.
+    pub(crate) fn describe(&self) -> (u8, String) {
+        let size = |o: JsonObj, s: &ResampleSize| match *s {
+            ResampleSize::Scale(sh, sw) => o.float("scale_h", sh).float("scale_w", sw),
+            ResampleSize::Size(oh, ow) => o.num("out_h", oh).num("out_w", ow),
+        };
+        match self {
+            Resample::Nearest(s) => (RESAMPLE_NEAREST, size(JsonObj::new(), s).finish()),
+            Resample::Bilinear(s) => (RESAMPLE_BILINEAR, size(JsonObj::new(), s).finish()),
+            Resample::PixelShuffle(r) => (RESAMPLE_PIXEL_SHUFFLE, JsonObj::new().num("upscale_factor", r).finish()),
+        }
+    }
+
+    /// Payload OP_INIT setelah `id` (lihat registry::init_resample).
+    pub(crate) fn init_fields(&self) -> Vec<u8> {
+        let w = PayloadWriter::new();
+        match self {
+            Resample::Nearest(s) | Resample::Bilinear(s) => match *s {
+                ResampleSize::Size(oh, ow) => w.bool(true).usize(oh).usize(ow),
+                ResampleSize::Scale(sh, sw) => w.bool(false).f64(sh).f64(sw),
+            },
+            Resample::PixelShuffle(r) => w.usize(*r),
+        }
+        .finish()
+    }
+
+    /// Shape inference + validasi (dipakai forward dan compiled graph).
+    pub fn output_shape(&self, input: [usize; 4]) -> Result<[usize; 4], String> {
+        let [b, c, h, w] = input;
+        match self {
+            Resample::Nearest(size) | Resample::Bilinear(size) => {
+                let [oh, ow] = size.resolve(h, w)?;
+                Ok([b, c, oh, ow])
+            }
+            Resample::PixelShuffle(r) => {
+                let rr = r * r;
+                if *r == 0 || !c.is_multiple_of(rr) {
+                    return Err(format!("pixel_shuffle: channels {} not divisible by factor^2 ({})", c, rr));
+                }
+                Ok([b, c / rr, h * r, w * r])
+            }
+        }
+    }
+
+    pub fn forward<B: Backend>(&self, input: Tensor<B, 4>) -> Result<Tensor<B, 4>, String> {
+        let [b, c, oh, ow] = self.output_shape(input.dims())?;
+        Ok(match self {
+            Resample::Nearest(_) => {
+                interpolate(input, [oh, ow], InterpolateOptions::new(InterpolateMode::Nearest))
+            }
+            Resample::Bilinear(_) => {
+                interpolate(input, [oh, ow], InterpolateOptions::new(InterpolateMode::Bilinear))
+            }
+            Resample::PixelShuffle(r) => {
+                let [_, _, h, w] = input.dims();
+                input
+                    .reshape([b, c, *r, *r, h, w])
+                    .permute([0, 1, 4, 2, 5, 3])
+                    .reshape([b, c, oh, ow])
+            }
+        })
+    }
+}
+
+// --- WASM WRAPPER ---
+#[wasm_bindgen]
+pub struct WasmResample {
+    inner: Resample,
+}
+
+impl From<Resample> for WasmResample {
+    fn from(inner: Resample) -> Self {
+        WasmResample { inner }
+    }
+}
+
+#[wasm_bindgen]
+impl WasmResample {
+    /// scale_w default = scale_h.
+    #[wasm_bindgen(js_name = newUpsampleNearest)]
+    pub fn new_upsample_nearest(scale_h: f64, scale_w: Option<f64>) -> WasmResample {
+        WasmResample { inner: Resample::Nearest(ResampleSize::Scale(scale_h, scale_w.unwrap_or(scale_h))) }
+    }
+
+    #[wasm_bindgen(js_name = newUpsampleBilinear)]
+    pub fn new_upsample_bilinear(scale_h: f64, scale_w: Option<f64>) -> WasmResample {
+        WasmResample { inner: Resample::Bilinear(ResampleSize::Scale(scale_h, scale_w.unwrap_or(scale_h))) }
+    }
+
+    #[wasm_bindgen(js_name = newResizeNearest)]
+    pub fn new_resize_nearest(out_h: usize, out_w: usize) -> WasmResample {
+        WasmResample { inner: Resample::Nearest(ResampleSize::Size(out_h, out_w)) }
+    }
+
+    #[wasm_bindgen(js_name = newResizeBilinear)]
+    pub fn new_resize_bilinear(out_h: usize, out_w: usize) -> WasmResample {
+        WasmResample { inner: Resample::Bilinear(ResampleSize::Size(out_h, out_w)) }
+    }
+
+    #[wasm_bindgen(js_name = newPixelShuffle)]
+    pub fn new_pixel_shuffle(upscale_factor: usize) -> WasmResample {
+        WasmResample { inner: Resample::PixelShuffle(upscale_factor) }
+    }
+
+    /// Ukuran/channel tidak valid -> thrown string (bukan trap).
+    pub fn forward(&self, input: &WasmTensor) -> Result<WasmTensor, String> {
+        let out = self.inner.forward(input.inner.clone())?;
+        Ok(WasmTensor { inner: out })
+    }
+
+    #[wasm_bindgen(js_name = outputShape)]
+    pub fn output_shape(&self, shape: &[usize]) -> Result<Vec<usize>, String> {
+        self.inner.output_shape(crate::layers::pad_shape4(shape)).map(|s| s.to_vec())
+    }
+
+    // Parameter-free: selalu 0
+    pub fn num_params(&self) -> usize {
+        0
+    }
+}
@@ -0,0 +1,298 @@
+use burn::prelude::*;
+use burn::tensor::ops::PadMode;
+use wasm_bindgen::prelude::*;
+use crate::layers::layout::JsonObj;
+use crate::protocol::*;
+use crate::WasmTensor;
+
+// --- REDUKSI (keepdim -> rank tetap 4) ---
+#[derive(Debug, Clone, Copy)]
+pub enum Reduce {
+    Mean,
+    Sum,
+    Max,
+}
+
+// --- TENSOR OP (Parameter-Free, 1 input) ---
+// "Lem" model: ubah bentuk/isi tensor tanpa bobot, supaya plan tidak perlu bolak-balik ke JS.
+// Semua hasil tetap 4D (konvensi WasmTensor: trailing 1).
+#[derive(Debug, Clone)]
+pub enum TensorOp {
+    /// Target 4D; -1 = infer (maks satu), 0 = pertahankan dim input.
+    Reshape([i32; 4]),
+    Permute([usize; 4]),
+    Transpose(usize, usize),
+    /// Gabung dim start..=end jadi satu, lalu pad trailing 1.
+    Flatten(usize, usize),
+    Slice { dim: usize, start: usize, end: usize },
+    /// (left, right, top, bottom) atas 2 dim terakhir (H, W).
+    Pad { pads: [usize; 4], mode: PadMode },
+    Reduce(Reduce, usize),
+    /// y = x * scale + bias
+    ScaleBias { scale: f32, bias: f32 },
+}
+
+impl TensorOp {
+    /// (varian TENSOR_*, config JSON) untuk describeLayer.
+    pub(crate) fn describe(&self) -> (u8, String) {
+        let o = JsonObj::new();
+        match self {
+            TensorOp::Reshape(s) => (TENSOR_RESHAPE, o.list("shape", s).finish()),
+            TensorOp::Permute(p) => (TENSOR_PERMUTE, o.list("axes", p).finish()),
+            TensorOp::Transpose(a, b) => (TENSOR_TRANSPOSE, o.num("dim0", a).num("dim1", b).finish()),
+            TensorOp::Flatten(a, b) => (TENSOR_FLATTEN, o.num("start", a).num("end", b).finish()),
+            TensorOp::Slice { dim, start, end } => {
+                (TENSOR_SLICE, o.num("dim", dim).num("start", start).num("end", end).finish())
+            }
+            TensorOp::Pad { pads, mode } => {
+                let o = o.list("pads", pads);
+                let o = match mode {
+                    PadMode::Constant(v) => o.str("mode", "constant").float("value", *v as f64),
+                    PadMode::Reflect => o.str("mode", "reflect"),
+                    _ => o.str("mode", "edge"),
+                };
+                (TENSOR_PAD, o.finish())
+            }
+            TensorOp::Reduce(r, dim) => {
+                let variant = match r {
+                    Reduce::Mean => TENSOR_MEAN,
+                    Reduce::Sum => TENSOR_SUM,
+                    Reduce::Max => TENSOR_MAX,
+                };
+                (variant, o.num("dim", dim).finish())
+            }
+            TensorOp::ScaleBias { scale, bias } => {
+                (TENSOR_SCALE_BIAS, o.float("scale", *scale as f64).float("bias", *bias as f64).finish())
+            }
+        }
+    }
+
+    /// Payload OP_INIT setelah `id` (lihat registry::init_tensor_op).
+    pub(crate) fn init_fields(&self) -> Vec<u8> {
+        let w = PayloadWriter::new();
+        match self {
+            TensorOp::Reshape(s) => s.iter().fold(w, |w, &d| w.i32(d)),
+            TensorOp::Permute(p) => p.iter().fold(w, |w, &a| w.usize(a)),
+            TensorOp::Transpose(a, b) | TensorOp::Flatten(a, b) => w.usize(*a).usize(*b),
+            TensorOp::Slice { dim, start, end } => w.usize(*dim).usize(*start).usize(*end),
+            TensorOp::Pad { pads, mode } => {
+                let (code, value) = match mode {
+                    PadMode::Constant(v) => (PAD_CONSTANT, *v as f64),
+                    PadMode::Reflect => (PAD_REFLECT, 0.0),
+                    _ => (PAD_EDGE, 0.0),
+                };
+                pads.iter().fold(w, |w, &p| w.usize(p)).u8(code).f64(value)
+            }
+            TensorOp::Reduce(_, dim) => w.usize(*dim),
+            TensorOp::ScaleBias { scale, bias } => w.f64(*scale as f64).f64(*bias as f64),
+        }
+        .finish()
+    }
+
+    fn check_dim(name: &str, d: usize) -> Result<(), String> {
+        if d >= 4 {
+            return Err(format!("tensor_op {}: dim {} out of range (rank 4)", name, d));
+        }
+        Ok(())
+    }
+
+    /// Shape inference + validasi. `forward` memanggil ini dulu -> shape salah jadi Err, bukan panic.
+    pub fn output_shape(&self, input: [usize; 4]) -> Result<[usize; 4], String> {
+        match self {
+            TensorOp::Reshape(target) => {
+                let numel: usize = input.iter().product();
+                let mut out = [0usize; 4];
+                let mut infer = None;
+                for (i, &t) in target.iter().enumerate() {
+                    out[i] = match t {
+                        -1 => {
+                            if infer.replace(i).is_some() {
+                                return Err("tensor_op reshape: more than one -1".into());
+                            }
+                            1
+                        }
+                        0 => input[i],
+                        t if t > 0 => t as usize,
+                        t => return Err(format!("tensor_op reshape: invalid dim {}", t)),
+                    };
+                }
+                let known: usize = out.iter().product();
+                if let Some(i) = infer {
+                    if known == 0 || !numel.is_multiple_of(known) {
+                        return Err(format!("tensor_op reshape: cannot infer {:?} from {:?}", target, input));
+                    }
+                    out[i] = numel / known;
+                } else if known != numel {
+                    return Err(format!("tensor_op reshape: {:?} -> {:?} changes element count", input, out));
+                }
+                Ok(out)
+            }
+            TensorOp::Permute(axes) => {
+                let mut seen = [false; 4];
+                for &a in axes {
+                    Self::check_dim("permute", a)?;
+                    if seen[a] {
+                        return Err(format!("tensor_op permute: {:?} is not a permutation", axes));
+                    }
+                    seen[a] = true;
+                }
+                Ok([input[axes[0]], input[axes[1]], input[axes[2]], input[axes[3]]])
+            }
+            TensorOp::Transpose(d0, d1) => {
+                Self::check_dim("transpose", *d0)?;
+                Self::check_dim("transpose", *d1)?;
+                let mut out = input;
+                out.swap(*d0, *d1);
+                Ok(out)
+            }
+            TensorOp::Flatten(start, end) => {
+                Self::check_dim("flatten", *end)?;
+                if start > end {
+                    return Err(format!("tensor_op flatten: start {} > end {}", start, end));
+                }
+                let mut out = [1usize; 4];
+                out[..*start].copy_from_slice(&input[..*start]);
+                out[*start] = input[*start..=*end].iter().product();
+                let rest = &input[end + 1..];
+                out[start + 1..start + 1 + rest.len()].copy_from_slice(rest);
+                Ok(out)
+            }
+            TensorOp::Slice { dim, start, end } => {
+                Self::check_dim("slice", *dim)?;
+                if start >= end || *end > input[*dim] {
+                    return Err(format!(
+                        "tensor_op slice: range {}..{} invalid for dim {} of size {}",
+                        start, end, dim, input[*dim]
+                    ));
+                }
+                let mut out = input;
+                out[*dim] = end - start;
+                Ok(out)
+            }
+            TensorOp::Pad { pads, mode } => {
+                let [l, r, t, b] = *pads;
+                let [_, _, h, w] = input;
+                if *mode == PadMode::Reflect && (l.max(r) >= w || t.max(b) >= h) {
+                    return Err(format!("tensor_op pad: reflect padding {:?} must be < size {:?}", pads, [h, w]));
+                }
+                if *mode == PadMode::Edge && (h == 0 || w == 0) {
+                    return Err("tensor_op pad: edge padding on empty dim".into());
+                }
+                Ok([input[0], input[1], h + t + b, w + l + r])
+            }
+            TensorOp::Reduce(_, dim) => {
+                Self::check_dim("reduce", *dim)?;
+                let mut out = input;
+                out[*dim] = 1;
+                Ok(out)
+            }
+            TensorOp::ScaleBias { .. } => Ok(input),
+        }
+    }
+
+    pub fn forward<B: Backend>(&self, input: Tensor<B, 4>) -> Result<Tensor<B, 4>, String> {
+        let out_shape = self.output_shape(input.dims())?;
+        Ok(match self {
+            TensorOp::Reshape(_) | TensorOp::Flatten(..) => input.reshape(out_shape),
+            TensorOp::Permute(axes) => input.permute(*axes),
+            TensorOp::Transpose(d0, d1) => input.swap_dims(*d0, *d1),
+            TensorOp::Slice { dim, start, end } => {
+                let mut ranges = input.dims().map(|d| 0..d);
+                ranges[*dim] = *start..*end;
+                input.slice(ranges)
+            }
+            TensorOp::Pad { pads, mode } => input.pad((pads[0], pads[1], pads[2], pads[3]), *mode),
+            TensorOp::Reduce(Reduce::Mean, d) => input.mean_dim(*d),
+            TensorOp::Reduce(Reduce::Sum, d) => input.sum_dim(*d),
+            TensorOp::Reduce(Reduce::Max, d) => input.max_dim(*d),
+            TensorOp::ScaleBias { scale, bias } => input.mul_scalar(*scale).add_scalar(*bias),
+        })
+    }
+}
+
+// --- WASM WRAPPER (stateless; named constructors infallible, validasi saat forward) ---
+#[wasm_bindgen]
+pub struct WasmTensorOp {
+    inner: TensorOp,
+}
+
+impl From<TensorOp> for WasmTensorOp {
+    fn from(inner: TensorOp) -> Self {
+        WasmTensorOp { inner }
+    }
+}
+
+#[wasm_bindgen]
+impl WasmTensorOp {
+    #[wasm_bindgen(js_name = newReshape)]
+    pub fn new_reshape(d0: i32, d1: i32, d2: i32, d3: i32) -> WasmTensorOp {
+        WasmTensorOp { inner: TensorOp::Reshape([d0, d1, d2, d3]) }
+    }
+
+    #[wasm_bindgen(js_name = newPermute)]
+    pub fn new_permute(a0: usize, a1: usize, a2: usize, a3: usize) -> WasmTensorOp {
+        WasmTensorOp { inner: TensorOp::Permute([a0, a1, a2, a3]) }
+    }
+
+    #[wasm_bindgen(js_name = newTranspose)]
+    pub fn new_transpose(dim0: usize, dim1: usize) -> WasmTensorOp {
+        WasmTensorOp { inner: TensorOp::Transpose(dim0, dim1) }
+    }
+
+    #[wasm_bindgen(js_name = newFlatten)]
+    pub fn new_flatten(start_dim: usize, end_dim: usize) -> WasmTensorOp {
+        WasmTensorOp { inner: TensorOp::Flatten(start_dim, end_dim) }
+    }
+
+    #[wasm_bindgen(js_name = newSlice)]
+    pub fn new_slice(dim: usize, start: usize, end: usize) -> WasmTensorOp {
+        WasmTensorOp { inner: TensorOp::Slice { dim, start, end } }
+    }
+
+    /// mode: 0 = constant(value), 1 = reflect, 2 = edge.
+    #[wasm_bindgen(js_name = newPad)]
+    pub fn new_pad(left: usize, right: usize, top: usize, bottom: usize, mode: u8, value: Option<f64>) -> WasmTensorOp {
+        let mode = match mode {
+            1 => PadMode::Reflect,
+            2 => PadMode::Edge,
+            _ => PadMode::Constant(value.unwrap_or(0.0) as f32),
+        };
+        WasmTensorOp { inner: TensorOp::Pad { pads: [left, right, top, bottom], mode } }
+    }
+
+    #[wasm_bindgen(js_name = newMean)]
+    pub fn new_mean(dim: usize) -> WasmTensorOp {
+        WasmTensorOp { inner: TensorOp::Reduce(Reduce::Mean, dim) }
+    }
+
+    #[wasm_bindgen(js_name = newSum)]
+    pub fn new_sum(dim: usize) -> WasmTensorOp {
+        WasmTensorOp { inner: TensorOp::Reduce(Reduce::Sum, dim) }
+    }
+
+    #[wasm_bindgen(js_name = newMax)]
+    pub fn new_max(dim: usize) -> WasmTensorOp {
+        WasmTensorOp { inner: TensorOp::Reduce(Reduce::Max, dim) }
+    }
+
+    #[wasm_bindgen(js_name = newScaleBias)]
+    pub fn new_scale_bias(scale: f64, bias: f64) -> WasmTensorOp {
+        WasmTensorOp { inner: TensorOp::ScaleBias { scale: scale as f32, bias: bias as f32 } }
+    }
+
+    /// Shape tidak valid untuk op ini -> thrown string (bukan trap).
+    pub fn forward(&self, input: &WasmTensor) -> Result<WasmTensor, String> {
+        let out = self.inner.forward(input.inner.clone())?;
+        Ok(WasmTensor { inner: out })
+    }
+
+    #[wasm_bindgen(js_name = outputShape)]
+    pub fn output_shape(&self, shape: &[usize]) -> Result<Vec<usize>, String> {
+        self.inner.output_shape(crate::layers::pad_shape4(shape)).map(|s| s.to_vec())
+    }
+
+    // Parameter-free
+    pub fn num_params(&self) -> usize {
+        0
+    }
+}
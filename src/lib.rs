@@ -3,16 +3,34 @@ use burn::prelude::*;
 use burn::tensor::TensorData;
 use js_sys::Float32Array;
 
+pub mod api;
 pub mod layers;
 pub mod protocol;
 pub mod registry;
 pub mod es;
 pub mod graph;
+pub mod plan;
+pub mod model;
+pub mod memory;
+pub mod profile;
 #[cfg(test)]
 mod tests;
 
 pub type WasmBackend = burn_ndarray::NdArray<f32>;
 
+// -------------------------------------------------------------
+// THREADS — matmul/conv ndarray + evaluasi ES memakai pool rayon global.
+// Fitur `threads` (wasm): ekspor `initThreadPool(n)` dari wasm-bindgen-rayon.
+// -------------------------------------------------------------
+#[cfg(all(target_arch = "wasm32", feature = "threads"))]
+pub use wasm_bindgen_rayon::init_thread_pool;
+
+/// Jumlah thread pool rayon aktif (1 = single-threaded).
+#[wasm_bindgen(js_name = threadCount)]
+pub fn thread_count() -> usize {
+    rayon::current_num_threads()
+}
+
 // -------------------------------------------------------------
 // WASM TENSOR — 4D tensor bridge
 // -------------------------------------------------------------
@@ -51,7 +69,9 @@ impl WasmTensor {
 }
 
 // -------------------------------------------------------------
-// TENSOR VIEW — SharedArrayBuffer bridge (zero-copy JS side)
+// TENSOR VIEW — SharedArrayBuffer bridge (dibagi antar worker JS).
+// read/write MENYALIN lewat Vec perantara; untuk hot path tanpa salinan
+// pakai memory::MemoryView (storage di linear memory wasm).
 // -------------------------------------------------------------
 #[wasm_bindgen]
 pub struct TensorView {
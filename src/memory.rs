@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+use burn::prelude::*;
+use burn::tensor::TensorData;
+use js_sys::Float32Array;
+use crate::WasmTensor;
+
+// -------------------------------------------------------------
+// MEMORY VIEW — storage di linear memory wasm (zero-copy sungguhan)
+// -------------------------------------------------------------
+// JS menulis/membaca langsung lewat `array()` (Float32Array di atas wasm.memory.buffer).
+// Rust memindahkan Vec ke tensor (move, bukan copy) dan hasil forward dipindah balik.
+// PERINGATAN: Float32Array dari `array()` jadi invalid kalau memory wasm tumbuh
+// (alokasi apa pun) -> ambil ulang setelah setiap panggilan ke wasm.
+#[wasm_bindgen]
+pub struct MemoryView {
+    data: Vec<f32>,
+    shape: [usize; 4],
+}
+
+#[wasm_bindgen]
+impl MemoryView {
+    #[wasm_bindgen(constructor)]
+    pub fn new(shape: &[usize]) -> MemoryView {
+        let shape = crate::layers::pad_shape4(shape);
+        MemoryView { data: vec![0.0; shape.iter().product()], shape }
+    }
+
+    /// Float32Array di atas wasm.memory.buffer (tanpa salinan).
+    pub fn array(&self) -> Float32Array {
+        // SAFETY: view hidup selama `self` tidak diubah/di-drop dan memory tidak tumbuh;
+        // kontrak ini didokumentasikan untuk pemanggil JS (ambil ulang setelah tiap panggilan).
+        unsafe { Float32Array::view(&self.data) }
+    }
+
+    /// Offset byte di linear memory (untuk `new Float32Array(memory.buffer, ptr, len)`).
+    pub fn ptr(&self) -> usize {
+        self.data.as_ptr() as usize
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    #[wasm_bindgen(js_name = isEmpty)]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn shape(&self) -> Vec<usize> {
+        self.shape.to_vec()
+    }
+
+    /// Ubah header shape (numel harus sama; storage tidak disentuh).
+    #[wasm_bindgen(js_name = setShape)]
+    pub fn set_shape(&mut self, shape: &[usize]) -> Result<(), String> {
+        let shape = crate::layers::pad_shape4(shape);
+        if shape.iter().product::<usize>() != self.data.len() {
+            return Err(format!("setShape: {:?} does not match {} elements", shape, self.data.len()));
+        }
+        self.shape = shape;
+        Ok(())
+    }
+}
+
+impl MemoryView {
+    /// Pindahkan storage ke tensor (tanpa salinan). View diisi buffer dari pool
+    /// (isi lama tidak dijamin) supaya tetap bisa dipakai untuk input berikutnya.
+    pub(crate) fn take_tensor(&mut self, pool: &mut BufferPool) -> WasmTensor {
+        let fresh = pool.take_vec(self.shape);
+        let data = std::mem::replace(&mut self.data, fresh);
+        let device = Default::default();
+        WasmTensor { inner: Tensor::from_data(TensorData::new(data, self.shape), &device) }
+    }
+
+    /// Pindahkan hasil tensor ke view; storage lama dikembalikan ke pool.
+    pub(crate) fn put_tensor(&mut self, t: WasmTensor, pool: &mut BufferPool) -> Result<(), String> {
+        let dims = t.inner.dims();
+        let data = t
+            .inner
+            .into_data()
+            .into_vec::<f32>()
+            .map_err(|e| format!("MemoryView: output not f32: {:?}", e))?;
+        let old = std::mem::replace(&mut self.data, data);
+        pool.put_vec(self.shape, old);
+        self.shape = dims;
+        Ok(())
+    }
+}
+
+// -------------------------------------------------------------
+// BUFFER POOL — Vec<f32> bekas, dikunci per shape (hindari alokasi per call)
+// -------------------------------------------------------------
+#[wasm_bindgen]
+pub struct BufferPool {
+    free: HashMap<[usize; 4], Vec<Vec<f32>>>,
+    max_per_shape: usize,
+}
+
+#[wasm_bindgen]
+impl BufferPool {
+    #[wasm_bindgen(constructor)]
+    pub fn new(max_per_shape: Option<usize>) -> BufferPool {
+        BufferPool { free: HashMap::new(), max_per_shape: max_per_shape.unwrap_or(8) }
+    }
+
+    /// View dengan shape ini; pakai ulang buffer bekas kalau ada (isi tidak di-nol-kan).
+    pub fn acquire(&mut self, shape: &[usize]) -> MemoryView {
+        let shape = crate::layers::pad_shape4(shape);
+        MemoryView { data: self.take_vec(shape), shape }
+    }
+
+    /// Kembalikan view ke pool (handle JS jadi tidak valid).
+    pub fn release(&mut self, view: MemoryView) {
+        self.put_vec(view.shape, view.data);
+    }
+
+    /// Jumlah buffer bebas untuk shape ini.
+    #[wasm_bindgen(js_name = pooledCount)]
+    pub fn pooled_count(&self, shape: &[usize]) -> usize {
+        self.free.get(&crate::layers::pad_shape4(shape)).map_or(0, |v| v.len())
+    }
+
+    pub fn clear(&mut self) {
+        self.free.clear();
+    }
+}
+
+impl BufferPool {
+    pub(crate) fn take_vec(&mut self, shape: [usize; 4]) -> Vec<f32> {
+        self.free
+            .get_mut(&shape)
+            .and_then(|v| v.pop())
+            .unwrap_or_else(|| vec![0.0; shape.iter().product()])
+    }
+
+    pub(crate) fn put_vec(&mut self, shape: [usize; 4], data: Vec<f32>) {
+        if data.len() != shape.iter().product::<usize>() {
+            return;
+        }
+        let bucket = self.free.entry(shape).or_default();
+        if bucket.len() < self.max_per_shape {
+            bucket.push(data);
+        }
+    }
+}
@@ -0,0 +1,416 @@
+// ============================================================
+// .bmodel — satu file berisi registry (config + bobot), plan graph bernama,
+// signature shape input/output dan metadata bebas. Satu unduhan -> model siap jalan.
+//
+//   [MODEL_MAGIC "BMDL"][format u8][protocol u8][flags u8]
+//   lalu section berulang: [tag u8][len u32 LE][body]
+//     SEC_LAYERS: stream paket OP_INIT + OP_LOAD_STATE (LayerRegistry::snapshot_packets)
+//     SEC_GRAPH:  [name_len u16][name][plan_kind u8][in_rank u8][in u32..][out_rank u8][out u32..][plan]
+//     SEC_META:   [key_len u16][key][value utf8 sisa body]
+//     SEC_TIE:    [linear u32][src_type u8][src_id u32][transpose u8][segment_len u16][segment]
+//                 (tieWeights, diterapkan setelah SEC_LAYERS)
+//     SEC_FROZEN: [type u8][id u32][segment_len u16][segment] per segmen beku (setTrainable false)
+//   flags bit0 = registry id global (withGlobalIds). plan_kind 0 = compileGraph, 1 = compileGraphIds.
+//   Dim signature 0 = dinamis (mis. batch); rank < 4 dilengkapi trailing 1 seperti WasmTensor.
+//   build mengecek signature lewat outputShape, Model::run menolak input di luar signature.
+//   Tag tak dikenal dilewati (kompatibel ke depan).
+// ============================================================
+
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+use crate::graph::CompiledGraph;
+use crate::protocol::{PayloadCursor, PROTOCOL_VERSION};
+use crate::registry::LayerRegistry;
+use crate::WasmTensor;
+
+pub const MODEL_MAGIC: [u8; 4] = *b"BMDL";
+pub const MODEL_FORMAT_VERSION: u8 = 1;
+pub const MODEL_FLAG_GLOBAL_IDS: u8 = 0x01;
+
+const SEC_LAYERS: u8 = 1;
+const SEC_GRAPH: u8 = 2;
+const SEC_META: u8 = 3;
+const SEC_TIE: u8 = 4;
+const SEC_FROZEN: u8 = 5;
+
+const PLAN_TYPED: u8 = 0;
+const PLAN_IDS: u8 = 1;
+
+/// Shape input/output yang dijanjikan satu graph; dim 0 = dinamis.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GraphSignature {
+    pub input: Vec<usize>,
+    pub output: Vec<usize>,
+}
+
+impl GraphSignature {
+    /// `shape` memenuhi `want` (kosong = tanpa signature, dim 0 = bebas).
+    pub fn accepts(want: &[usize], shape: &[usize]) -> bool {
+        want.is_empty() || pad4(want).iter().zip(pad4(shape)).all(|(&w, s)| w == 0 || w == s)
+    }
+
+    /// Input signature ada -> outputShape untuk input contoh (dim dinamis = 1) harus cocok dengan output.
+    fn check(&self, registry: &LayerRegistry, graph: &CompiledGraph) -> Result<(), String> {
+        if self.input.is_empty() {
+            return Ok(());
+        }
+        let probe: Vec<usize> = pad4(&self.input).iter().map(|&d| d.max(1)).collect();
+        let out = graph.output_shape(registry, &probe)?;
+        if !Self::accepts(&self.output, &out) {
+            return Err(format!(
+                "signature output {:?} does not match plan output {:?} for input {:?}",
+                self.output, out, probe
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn pad4(shape: &[usize]) -> [usize; 4] {
+    let mut dims = [1usize; 4];
+    for (d, &s) in dims.iter_mut().zip(shape) {
+        *d = s;
+    }
+    dims
+}
+
+struct GraphEntry {
+    name: String,
+    ids: bool,
+    signature: GraphSignature,
+    plan: Vec<u8>,
+}
+
+/// Penulis .bmodel: kumpulkan graph + metadata, lalu `build(registry)`.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct ModelBuilder {
+    graphs: Vec<GraphEntry>,
+    meta: Vec<(String, String)>,
+}
+
+#[wasm_bindgen]
+impl ModelBuilder {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> ModelBuilder {
+        ModelBuilder::default()
+    }
+
+    /// Plan format compileGraph; nama harus unik.
+    #[wasm_bindgen(js_name = addGraph)]
+    pub fn add_graph(&mut self, name: &str, plan: &[u8], input_shape: &[usize], output_shape: &[usize]) -> Result<(), String> {
+        self.push_graph(name, false, plan, input_shape, output_shape)
+    }
+
+    /// Plan format compileGraphIds (registry withGlobalIds).
+    #[wasm_bindgen(js_name = addGraphIds)]
+    pub fn add_graph_ids(&mut self, name: &str, plan: &[u8], input_shape: &[usize], output_shape: &[usize]) -> Result<(), String> {
+        self.push_graph(name, true, plan, input_shape, output_shape)
+    }
+
+    /// Key sama -> nilai ditimpa.
+    #[wasm_bindgen(js_name = setMeta)]
+    pub fn set_meta(&mut self, key: &str, value: &str) {
+        match self.meta.iter_mut().find(|(k, _)| k == key) {
+            Some(entry) => entry.1 = value.to_string(),
+            None => self.meta.push((key.to_string(), value.to_string())),
+        }
+    }
+
+    /// Tiap plan dikompilasi dulu terhadap registry dan dicek terhadap signature-nya
+    /// (gagal di sini, bukan saat load).
+    pub fn build(&self, registry: &LayerRegistry) -> Result<Vec<u8>, String> {
+        let mut out = MODEL_MAGIC.to_vec();
+        out.push(MODEL_FORMAT_VERSION);
+        out.push(PROTOCOL_VERSION);
+        out.push(if registry.global_ids() { MODEL_FLAG_GLOBAL_IDS } else { 0 });
+        push_section(&mut out, SEC_LAYERS, &registry.snapshot_packets()?)?;
+        for (linear_id, tie) in registry.weight_ties() {
+            let mut body = linear_id.to_le_bytes().to_vec();
+            body.push(tie.src_type);
+            body.extend_from_slice(&tie.src_id.to_le_bytes());
+            body.push(tie.transpose as u8);
+            push_str(&mut body, tie.segment)?;
+            push_section(&mut out, SEC_TIE, &body)?;
+        }
+        for (t, id, segment) in registry.frozen_segments() {
+            let mut body = vec![t];
+            body.extend_from_slice(&id.to_le_bytes());
+            push_str(&mut body, segment)?;
+            push_section(&mut out, SEC_FROZEN, &body)?;
+        }
+        for g in &self.graphs {
+            let ctx = |e: String| format!("model: graph {:?}: {}", g.name, e);
+            let graph = compile(registry, g.ids, &g.plan).map_err(ctx)?;
+            g.signature.check(registry, &graph).map_err(ctx)?;
+            let mut body = Vec::new();
+            push_str(&mut body, &g.name)?;
+            body.push(if g.ids { PLAN_IDS } else { PLAN_TYPED });
+            push_shape(&mut body, &g.signature.input)?;
+            push_shape(&mut body, &g.signature.output)?;
+            body.extend_from_slice(&g.plan);
+            push_section(&mut out, SEC_GRAPH, &body)?;
+        }
+        for (k, v) in &self.meta {
+            let mut body = Vec::new();
+            push_str(&mut body, k)?;
+            body.extend_from_slice(v.as_bytes());
+            push_section(&mut out, SEC_META, &body)?;
+        }
+        Ok(out)
+    }
+}
+
+impl ModelBuilder {
+    fn push_graph(&mut self, name: &str, ids: bool, plan: &[u8], input: &[usize], output: &[usize]) -> Result<(), String> {
+        if self.graphs.iter().any(|g| g.name == name) {
+            return Err(format!("model: duplicate graph name {:?}", name));
+        }
+        self.graphs.push(GraphEntry {
+            name: name.to_string(),
+            ids,
+            signature: GraphSignature { input: input.to_vec(), output: output.to_vec() },
+            plan: plan.to_vec(),
+        });
+        Ok(())
+    }
+}
+
+/// .bmodel yang sudah dimuat: registry + graph terkompilasi per nama.
+#[wasm_bindgen]
+pub struct Model {
+    registry: LayerRegistry,
+    graphs: HashMap<String, CompiledGraph>,
+    signatures: HashMap<String, GraphSignature>,
+    /// Plan mentah per graph + apakah formatnya compileGraphIds (untuk pack ulang).
+    plans: HashMap<String, (bool, Vec<u8>)>,
+    meta: Vec<(String, String)>,
+}
+
+#[wasm_bindgen]
+impl Model {
+    /// Parse + applyPackets + compile semua graph; error menyebut section/graph yang gagal.
+    pub fn load(bytes: &[u8]) -> Result<Model, String> {
+        let mut c = PayloadCursor::new(bytes);
+        if c.read_bytes(4).ok() != Some(&MODEL_MAGIC[..]) {
+            return Err("model: missing BMDL magic".into());
+        }
+        let format = c.read_u8()?;
+        if format != MODEL_FORMAT_VERSION {
+            return Err(format!("model: unsupported format version {}", format));
+        }
+        let protocol = c.read_u8()?;
+        if protocol > PROTOCOL_VERSION {
+            return Err(format!("model: protocol version {} is newer than supported {}", protocol, PROTOCOL_VERSION));
+        }
+        let flags = c.read_u8()?;
+        let mut registry = if flags & MODEL_FLAG_GLOBAL_IDS != 0 {
+            LayerRegistry::with_global_ids()
+        } else {
+            LayerRegistry::new()
+        };
+        let mut model_graphs = Vec::new();
+        let mut meta = Vec::new();
+        while c.remaining() > 0 {
+            let tag = c.read_u8()?;
+            let len = c.read_u32()? as usize;
+            let body = c.read_bytes(len).map_err(|e| format!("model: section 0x{:02X}: {}", tag, e))?;
+            match tag {
+                SEC_LAYERS => {
+                    registry.apply_packets(body).map_err(|e| format!("model: layers: {}", e))?;
+                }
+                SEC_TIE => {
+                    let mut t = PayloadCursor::new(body);
+                    let (linear_id, src_type, src_id) = (t.read_u32()?, t.read_u8()?, t.read_u32()?);
+                    let transpose = t.read_bool()?;
+                    let segment = read_str(&mut t)?;
+                    registry
+                        .tie_weights(linear_id, src_type, src_id, &segment, transpose)
+                        .map_err(|e| format!("model: {}", e))?;
+                }
+                SEC_FROZEN => {
+                    let mut f = PayloadCursor::new(body);
+                    let (t, id) = (f.read_u8()?, f.read_u32()?);
+                    let segment = read_str(&mut f)?;
+                    registry.set_trainable(t, id, &segment, false).map_err(|e| format!("model: {}", e))?;
+                }
+                SEC_GRAPH => model_graphs.push(read_graph(body)?),
+                SEC_META => {
+                    let mut m = PayloadCursor::new(body);
+                    let key = read_str(&mut m)?;
+                    let value = utf8(m.read_bytes(m.remaining())?)?;
+                    meta.push((key, value));
+                }
+                _ => {} // section dari versi lebih baru
+            }
+        }
+        let mut graphs = HashMap::new();
+        let mut signatures = HashMap::new();
+        let mut plans = HashMap::new();
+        for g in model_graphs {
+            let graph = compile(&registry, g.ids, &g.plan).map_err(|e| format!("model: graph {:?}: {}", g.name, e))?;
+            if graphs.insert(g.name.clone(), graph).is_some() {
+                return Err(format!("model: duplicate graph name {:?}", g.name));
+            }
+            signatures.insert(g.name.clone(), g.signature);
+            plans.insert(g.name, (g.ids, g.plan));
+        }
+        Ok(Model { registry, graphs, signatures, plans, meta })
+    }
+
+    /// Input harus memenuhi signature graph (dim 0 = dinamis).
+    pub fn run(&self, name: &str, input: &WasmTensor) -> Result<WasmTensor, String> {
+        let graph = self.graph(name)?;
+        let sig = &self.signatures[name];
+        let shape = input.shape();
+        if !GraphSignature::accepts(&sig.input, &shape) {
+            return Err(format!("model: graph {:?}: input shape {:?} does not match signature {:?}", name, shape, sig.input));
+        }
+        graph.run(&self.registry, input)
+    }
+
+    /// JSON array nama graph, urut.
+    #[wasm_bindgen(js_name = graphNames)]
+    pub fn graph_names_json(&self) -> String {
+        let names: Vec<String> = self.graph_names().iter().map(|n| json_str(n)).collect();
+        format!("[{}]", names.join(","))
+    }
+
+    /// {"input":[..],"output":[..]} (0 = dinamis).
+    #[wasm_bindgen(js_name = signature)]
+    pub fn signature_json(&self, name: &str) -> Result<String, String> {
+        let sig = self.signature(name)?;
+        Ok(crate::layers::layout::JsonObj::new()
+            .list("input", &sig.input)
+            .list("output", &sig.output)
+            .finish())
+    }
+
+    #[wasm_bindgen(js_name = meta)]
+    pub fn meta_value(&self, key: &str) -> Option<String> {
+        self.meta.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone())
+    }
+
+    /// JSON objek semua metadata.
+    #[wasm_bindgen(js_name = metadata)]
+    pub fn metadata_json(&self) -> String {
+        let items: Vec<String> = self.meta.iter().map(|(k, v)| format!("{}:{}", json_str(k), json_str(v))).collect();
+        format!("{{{}}}", items.join(","))
+    }
+}
+
+impl Model {
+    pub fn registry(&self) -> &LayerRegistry {
+        &self.registry
+    }
+
+    pub fn graph(&self, name: &str) -> Result<&CompiledGraph, String> {
+        self.graphs.get(name).ok_or_else(|| format!("model: no graph named {:?}", name))
+    }
+
+    pub fn graph_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.graphs.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    pub fn signature(&self, name: &str) -> Result<&GraphSignature, String> {
+        self.signatures.get(name).ok_or_else(|| format!("model: no graph named {:?}", name))
+    }
+
+    /// (plan, format compileGraphIds?) seperti yang tersimpan di file.
+    pub fn plan(&self, name: &str) -> Result<(&[u8], bool), String> {
+        let (ids, plan) = self.plans.get(name).ok_or_else(|| format!("model: no graph named {:?}", name))?;
+        Ok((plan, *ids))
+    }
+
+    pub fn into_parts(self) -> (LayerRegistry, HashMap<String, CompiledGraph>) {
+        (self.registry, self.graphs)
+    }
+}
+
+impl LayerRegistry {
+    /// Muat .bmodel: registry baru + graph terkompilasi per nama (lihat Model::load).
+    pub fn load_model(bytes: &[u8]) -> Result<(LayerRegistry, HashMap<String, CompiledGraph>), String> {
+        Model::load(bytes).map(Model::into_parts)
+    }
+}
+
+fn compile(reg: &LayerRegistry, ids: bool, plan: &[u8]) -> Result<CompiledGraph, String> {
+    if ids { reg.compile_graph_ids(plan) } else { reg.compile_graph(plan) }
+}
+
+fn read_graph(body: &[u8]) -> Result<GraphEntry, String> {
+    let mut c = PayloadCursor::new(body);
+    let name = read_str(&mut c)?;
+    let ctx = |e: String| format!("model: graph {:?}: {}", name, e);
+    let ids = match c.read_u8().map_err(ctx)? {
+        PLAN_TYPED => false,
+        PLAN_IDS => true,
+        k => return Err(ctx(format!("unknown plan kind {}", k))),
+    };
+    let input = read_shape(&mut c).map_err(ctx)?;
+    let output = read_shape(&mut c).map_err(ctx)?;
+    let plan = c.read_bytes(c.remaining())?.to_vec();
+    Ok(GraphEntry { name, ids, signature: GraphSignature { input, output }, plan })
+}
+
+fn push_section(out: &mut Vec<u8>, tag: u8, body: &[u8]) -> Result<(), String> {
+    let len = u32::try_from(body.len()).map_err(|_| format!("model: section 0x{:02X} exceeds 4 GiB", tag))?;
+    out.push(tag);
+    out.extend_from_slice(&len.to_le_bytes());
+    out.extend_from_slice(body);
+    Ok(())
+}
+
+fn push_str(out: &mut Vec<u8>, s: &str) -> Result<(), String> {
+    let len = u16::try_from(s.len()).map_err(|_| format!("model: name too long ({} bytes)", s.len()))?;
+    out.extend_from_slice(&len.to_le_bytes());
+    out.extend_from_slice(s.as_bytes());
+    Ok(())
+}
+
+fn read_str(c: &mut PayloadCursor) -> Result<String, String> {
+    let len = c.read_u16()? as usize;
+    utf8(c.read_bytes(len)?)
+}
+
+fn utf8(b: &[u8]) -> Result<String, String> {
+    String::from_utf8(b.to_vec()).map_err(|_| "model: invalid utf-8 string".to_string())
+}
+
+fn push_shape(out: &mut Vec<u8>, shape: &[usize]) -> Result<(), String> {
+    if shape.len() > 4 {
+        return Err(format!("model: signature rank {} > 4", shape.len()));
+    }
+    out.push(shape.len() as u8);
+    for &d in shape {
+        let d = u32::try_from(d).map_err(|_| format!("model: signature dim {} exceeds u32", d))?;
+        out.extend_from_slice(&d.to_le_bytes());
+    }
+    Ok(())
+}
+
+fn read_shape(c: &mut PayloadCursor) -> Result<Vec<usize>, String> {
+    let rank = c.read_u8()? as usize;
+    if rank > 4 {
+        return Err(format!("signature rank {} > 4", rank));
+    }
+    (0..rank).map(|_| c.read_u32().map(|d| d as usize)).collect()
+}
+
+/// String JSON dengan escape (nama graph & metadata bebas, beda dengan JsonObj).
+fn json_str(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,375 @@
+// -------------------------------------------------------------
+// PLAN — satu parser + validator + alokasi slot untuk semua entry graph
+// (compileGraph, compileGraphIds, compileGraphFused, runGraph). Eksekusi: graph::CompiledGraph.
+// Error tanpa prefix; entry point menambahkan konteks ("compile_graph: ", "run_graph: ").
+// -------------------------------------------------------------
+//   v1: [num_steps u32][num_slots u32] step 9 byte (slot u8) .. [out u8]
+//   v2: [PLAN_MAGIC.., 2][num_steps u32][num_slots u32] step 12 byte (slot u16) .. [out u16]
+//   step layer: [arity][layer_type][id u32][in][in2][out]; plan id-only (compileGraphIds)
+//   tanpa byte layer_type, step control flow tetap penuh.
+
+use crate::protocol::*;
+use crate::registry::LayerRegistry;
+
+pub(crate) const ARITY_UNARY: u8 = 1;
+pub(crate) const ARITY_BINARY: u8 = 2;
+
+// Step control flow — operand di field step, bukan layer:
+//   v1: LOOP   [3][body_len][iters u32][0][0][0]
+//       IF     [4][cond][then_len | else_len << 16][0][0][0]
+//       SELECT [5][cond][0 u32][a][b][out]
+//   v2: LOOP   [3][0][iters u32][body_len u16][0][0]
+//       IF     [4][0][then_len | else_len << 16][cond u16][0][0]
+//       SELECT [5][0][cond u32][a][b][out]
+// body/then/else = step-step berikutnya (berurutan); SELECT: out = cond ? a : b (tanpa layer).
+// cond = slot berisi skalar (numel 1), true kalau > 0. Slot yang dibaca body loop sebelum
+// ditulis di body = loop-carried: nilai iterasi sebelumnya (iterasi pertama: nilai sebelum loop).
+pub(crate) const ARITY_LOOP: u8 = 3;
+pub(crate) const ARITY_IF: u8 = 4;
+pub(crate) const ARITY_SELECT: u8 = 5;
+
+const MAX_LOOP_ITERS: u32 = 1 << 16;
+
+struct PlanHeader {
+    version: u8,
+    num_steps: u32,
+    num_slots: u32,
+}
+
+impl PlanHeader {
+    /// Header + cursor yang sudah di posisi step pertama.
+    fn read(plan: &[u8]) -> Result<(PlanHeader, PayloadCursor<'_>), String> {
+        let (version, body) = match plan.get(..4) {
+            Some(p) if p[..3] == PLAN_MAGIC => (p[3], &plan[4..]),
+            _ => (1, plan),
+        };
+        if !PLAN_VERSIONS.contains(&version) {
+            return Err(format!("plan version {} not supported (expected {:?})", version, PLAN_VERSIONS));
+        }
+        let mut c = PayloadCursor::new(body);
+        let num_steps = c.read_u32()?;
+        let num_slots = c.read_u32()?;
+        if num_steps == 0 {
+            return Err("plan has no steps".into());
+        }
+        let max = Self::max_slots(version);
+        if !(1..=max).contains(&num_slots) {
+            return Err(format!("num_slots must be 1..={}, got {}", max, num_slots));
+        }
+        Ok((PlanHeader { version, num_steps, num_slots }, c))
+    }
+
+    /// v2: u16::MAX dicadangkan sebagai indeks tidak valid.
+    fn max_slots(version: u8) -> u32 {
+        if version == 1 { 1 << 8 } else { u16::MAX as u32 }
+    }
+
+    fn read_slot(&self, c: &mut PayloadCursor) -> Result<u16, String> {
+        if self.version == 1 { c.read_u8().map(u16::from) } else { c.read_u16() }
+    }
+}
+
+/// Bitset slot (ukuran bebas) untuk validasi "tidak dibaca sebelum ditulis".
+#[derive(Clone)]
+struct SlotSet(Vec<u64>);
+
+impl SlotSet {
+    fn new(num_slots: u32) -> SlotSet {
+        SlotSet(vec![0; (num_slots as usize).div_ceil(64)])
+    }
+
+    fn insert(&mut self, slot: u16) {
+        self.0[slot as usize / 64] |= 1 << (slot % 64);
+    }
+
+    fn contains(&self, slot: u16) -> bool {
+        self.0.get(slot as usize / 64).is_some_and(|w| (w >> (slot % 64)) & 1 == 1)
+    }
+
+    fn intersect(&mut self, other: &SlotSet) {
+        self.0.iter_mut().zip(&other.0).for_each(|(a, b)| *a &= b);
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct Step {
+    pub arity: u8,
+    pub layer_type: u8,
+    /// LOOP: jumlah iterasi.
+    pub layer_id: u32,
+    pub in_slot: u16,
+    pub in_slot2: u16,
+    pub out_slot: u16,
+    /// Slot output di plan (sebelum reuse slot), untuk laporan.
+    pub plan_out: u16,
+    /// Slot (in, in2, cond) di plan: outputShape menelusuri slot plan, bukan slot fisik.
+    pub plan_in: (u16, u16, u16),
+    /// Slot kondisi IF/SELECT.
+    pub cond: u16,
+    /// Panjang blok berikutnya: (body, 0) untuk LOOP, (then, else) untuk IF.
+    pub block: (u32, u32),
+}
+
+impl Step {
+    pub(crate) fn is_control(&self) -> bool {
+        matches!(self.arity, ARITY_LOOP | ARITY_IF | ARITY_SELECT)
+    }
+
+    pub(crate) fn block_lens(&self) -> (usize, usize) {
+        (self.block.0 as usize, self.block.1 as usize)
+    }
+
+    /// Normalisasi operand control flow v1/v2 (lihat ARITY_LOOP).
+    fn control(version: u8, arity: u8, op: u8, arg: u32, [a, b, out]: [u16; 3]) -> Step {
+        let v1 = version == 1;
+        let mut s = Step {
+            arity,
+            layer_type: 0,
+            layer_id: 0,
+            in_slot: 0,
+            in_slot2: 0,
+            out_slot: 0,
+            plan_out: 0,
+            plan_in: (0, 0, 0),
+            cond: 0,
+            block: (0, 0),
+        };
+        match arity {
+            ARITY_LOOP => {
+                s.layer_id = arg;
+                s.block = (if v1 { op as u32 } else { a as u32 }, 0);
+            }
+            ARITY_IF => {
+                s.cond = if v1 { op as u16 } else { a };
+                s.block = (arg & 0xFFFF, arg >> 16);
+            }
+            _ => {
+                // cond di luar u16 -> u16::MAX, selalu di luar rentang slot
+                s.cond = if v1 { op as u16 } else { u16::try_from(arg).unwrap_or(u16::MAX) };
+                (s.in_slot, s.in_slot2, s.out_slot) = (a, b, out);
+            }
+        }
+        s
+    }
+}
+
+/// Plan tervalidasi. `num_slots`/`out_slot` = slot fisik setelah `reuse_slots`,
+/// `plan_slots`/`plan_out` = seperti tertulis di plan.
+pub(crate) struct Plan {
+    pub steps: Vec<Step>,
+    pub num_slots: u32,
+    pub out_slot: u16,
+    pub plan_slots: u32,
+    pub plan_out: u16,
+}
+
+impl Plan {
+    /// Parse + validasi; `by_id` (compileGraphIds): tipe layer dicari dari registry id global.
+    pub(crate) fn parse(reg: &LayerRegistry, plan: &[u8], by_id: bool) -> Result<Plan, String> {
+        let (h, mut c) = PlanHeader::read(plan)?;
+        let num_slots = h.num_slots;
+        let mut steps: Vec<Step> = Vec::with_capacity(h.num_steps.min(1 << 16) as usize);
+        for _ in 0..h.num_steps {
+            let s = Self::read_step(&h, &mut c, by_id.then_some(reg))?;
+            let in_slot = s.in_slot as u32;
+            let in_slot2 = s.in_slot2 as u32;
+            let out_slot = s.out_slot as u32;
+            if in_slot >= num_slots || in_slot2 >= num_slots || out_slot >= num_slots {
+                return Err(format!("slot index out of range (num_slots={})", num_slots));
+            }
+            match s.arity {
+                ARITY_BINARY if s.layer_type != LAYER_BINARY => {
+                    return Err(format!("arity 2 requires LAYER_BINARY, got 0x{:02X}", s.layer_type));
+                }
+                ARITY_UNARY if s.layer_type == LAYER_BINARY => {
+                    return Err("arity 1 cannot use LAYER_BINARY (needs 2 inputs)".into());
+                }
+                ARITY_UNARY | ARITY_BINARY => {
+                    if !reg.layer_exists(s.layer_type, s.layer_id) {
+                        return Err(format!("layer type 0x{:02X} id {} not found", s.layer_type, s.layer_id));
+                    }
+                }
+                ARITY_IF | ARITY_SELECT if s.cond as u32 >= num_slots => {
+                    return Err(format!("condition slot {} out of range", s.cond));
+                }
+                ARITY_LOOP | ARITY_IF | ARITY_SELECT => {}
+                _ => return Err(format!("invalid arity {} (expected 1..=5)", s.arity)),
+            }
+            steps.push(s);
+        }
+        let mut filled = SlotSet::new(num_slots);
+        filled.insert(0);
+        let filled = check_flow(&steps, 0, steps.len(), filled)?;
+        let out_slot = h.read_slot(&mut c)?;
+        if out_slot as u32 >= num_slots {
+            return Err(format!("output slot {} out of range", out_slot));
+        }
+        if !filled.contains(out_slot) {
+            return Err(format!("output slot {} is never written", out_slot));
+        }
+        Ok(Plan { steps, num_slots, out_slot, plan_slots: num_slots, plan_out: out_slot })
+    }
+
+    fn read_step(h: &PlanHeader, c: &mut PayloadCursor, by_id: Option<&LayerRegistry>) -> Result<Step, String> {
+        let arity = c.read_u8()?;
+        if matches!(arity, ARITY_LOOP | ARITY_IF | ARITY_SELECT) {
+            let (op, arg) = (c.read_u8()?, c.read_u32()?);
+            let (a, b, out) = (h.read_slot(c)?, h.read_slot(c)?, h.read_slot(c)?);
+            return Ok(Step::control(h.version, arity, op, arg, [a, b, out]));
+        }
+        let (layer_type, layer_id) = match by_id {
+            Some(reg) => {
+                let id = c.read_u32()?;
+                (reg.resolve_id(id)?, id)
+            }
+            None => (c.read_u8()?, c.read_u32()?),
+        };
+        Ok(Step {
+            arity,
+            layer_type,
+            layer_id,
+            in_slot: h.read_slot(c)?,
+            in_slot2: h.read_slot(c)?,
+            out_slot: h.read_slot(c)?,
+            plan_out: 0,
+            plan_in: (0, 0, 0),
+            cond: 0,
+            block: (0, 0),
+        })
+    }
+
+    pub(crate) fn has_control_flow(&self) -> bool {
+        self.steps.iter().any(Step::is_control)
+    }
+}
+
+/// Validasi aliran slot `steps[lo..hi]`: tidak ada slot dibaca sebelum ditulis.
+/// `filled` = slot terisi saat masuk; hasil = slot terisi setelah range.
+fn check_flow(steps: &[Step], lo: usize, hi: usize, mut filled: SlotSet) -> Result<SlotSet, String> {
+    let need = |filled: &SlotSet, slot: u16| {
+        if !filled.contains(slot) {
+            return Err(format!("input slot {} is empty", slot));
+        }
+        Ok(())
+    };
+    let mut i = lo;
+    while i < hi {
+        let s = &steps[i];
+        let (a, b) = s.block_lens();
+        let end = i + 1 + a + b;
+        if end > hi {
+            return Err(format!("block at step {} runs past its enclosing block", i));
+        }
+        match s.arity {
+            ARITY_LOOP => {
+                if a == 0 || !(1..=MAX_LOOP_ITERS).contains(&s.layer_id) {
+                    return Err(format!(
+                        "loop at step {} needs a non-empty body and 1..={} iterations",
+                        i, MAX_LOOP_ITERS
+                    ));
+                }
+                // Cukup cek iterasi pertama: iterasi berikutnya masuk dengan slot terisi >= ini.
+                filled = check_flow(steps, i + 1, end, filled)?;
+            }
+            ARITY_IF => {
+                need(&filled, s.cond)?;
+                let mut then_filled = check_flow(steps, i + 1, i + 1 + a, filled.clone())?;
+                let else_filled = check_flow(steps, i + 1 + a, end, filled)?;
+                // Setelah IF hanya slot yang ditulis kedua cabang yang pasti terisi.
+                then_filled.intersect(&else_filled);
+                filled = then_filled;
+            }
+            _ => {
+                if s.arity == ARITY_SELECT {
+                    need(&filled, s.cond)?;
+                }
+                need(&filled, s.in_slot)?;
+                if s.arity != ARITY_UNARY {
+                    need(&filled, s.in_slot2)?;
+                }
+                filled.insert(s.out_slot);
+            }
+        }
+        i = end;
+    }
+    Ok(filled)
+}
+
+// ============================================================
+// SLOT REUSE — slot plan dipetakan ulang ke slot fisik berdasarkan liveness (otomatis di setiap compile).
+// Posisi baca step k = 2k, tulis = 2k+1, jadi step boleh menulis slot yang baru ia baca.
+// Slot yang disentuh di dalam LOOP hidup sepanjang loop terluarnya (nilai dibawa antar
+// iterasi); cabang IF diperlakukan berurutan (konservatif). Slot 0 = input, tetap 0.
+// Temporer kedua cabang IF bisa berbagi slot fisik -> outputShape memakai slot plan.
+// ============================================================
+impl Plan {
+    pub(crate) fn reuse_slots(&mut self) {
+        let n = self.steps.len();
+        let mut outer: Vec<Option<(usize, usize)>> = vec![None; n];
+        for (i, s) in self.steps.iter().enumerate() {
+            if s.arity == ARITY_LOOP {
+                let end = i + s.block.0 as usize;
+                for o in &mut outer[i + 1..=end] {
+                    o.get_or_insert((i, end));
+                }
+            }
+        }
+        let mut live: Vec<Option<(usize, usize)>> = vec![None; self.num_slots as usize];
+        let mut touch = |slot: u16, k: usize, write: bool| {
+            let (lo, hi) = match outer[k] {
+                Some((a, b)) => (2 * a, 2 * b + 1),
+                None => (2 * k + write as usize, 2 * k + write as usize),
+            };
+            let e = live[slot as usize].get_or_insert((lo, hi));
+            *e = (e.0.min(lo), e.1.max(hi));
+        };
+        for (k, s) in self.steps.iter().enumerate() {
+            match s.arity {
+                ARITY_LOOP => {}
+                ARITY_IF => touch(s.cond, k, false),
+                _ => {
+                    if s.arity == ARITY_SELECT {
+                        touch(s.cond, k, false);
+                    }
+                    touch(s.in_slot, k, false);
+                    if s.arity != ARITY_UNARY {
+                        touch(s.in_slot2, k, false);
+                    }
+                    touch(s.out_slot, k, true);
+                }
+            }
+        }
+        // Slot 0 terisi sebelum step 0; output hidup sampai akhir run.
+        live[0] = Some((0, live[0].map_or(0, |l| l.1)));
+        if let Some(l) = &mut live[self.out_slot as usize] {
+            l.1 = usize::MAX;
+        }
+        let mut order: Vec<(usize, (usize, usize))> =
+            live.iter().enumerate().skip(1).filter_map(|(s, l)| Some((s, (*l)?))).collect();
+        order.sort_by_key(|&(_, (lo, _))| lo);
+        let mut map = vec![0u16; live.len()];
+        let mut busy_until = vec![live[0].map_or(0, |l| l.1)];
+        for (slot, (lo, hi)) in order {
+            let p = match busy_until.iter().position(|&end| end < lo) {
+                Some(p) => p,
+                None => {
+                    busy_until.push(0);
+                    busy_until.len() - 1
+                }
+            };
+            busy_until[p] = hi;
+            map[slot] = p as u16;
+        }
+        let m = |slot: &mut u16| *slot = map[*slot as usize];
+        for s in &mut self.steps {
+            s.plan_out = s.out_slot;
+            s.plan_in = (s.in_slot, s.in_slot2, s.cond);
+            m(&mut s.in_slot);
+            m(&mut s.in_slot2);
+            m(&mut s.out_slot);
+            m(&mut s.cond);
+        }
+        m(&mut self.out_slot);
+        self.num_slots = busy_until.len() as u32;
+    }
+}
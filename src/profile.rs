@@ -0,0 +1,104 @@
+// -------------------------------------------------------------
+// PROFILER — rekaman per step CompiledGraph (opt-in, lihat CompiledGraph::setProfiling).
+// -------------------------------------------------------------
+// Per step: tipe/id layer, shape input/output, waktu (µs), estimasi FLOP, byte output.
+// Ekspor: JSON ringkas (lastProfile) dan Chrome trace (chromeTrace) untuk
+// chrome://tracing / Perfetto.
+
+use crate::layers::layout::JsonObj;
+use crate::protocol::layer_type_name;
+
+/// Waktu monoton dalam µs. wasm: performance.now() (fallback Date.now()), native: Instant.
+#[cfg(target_arch = "wasm32")]
+pub fn now_us() -> f64 {
+    let perf = js_sys::Reflect::get(&js_sys::global(), &"performance".into()).ok();
+    let now = perf.as_ref().and_then(|p| {
+        let f = js_sys::Reflect::get(p, &"now".into()).ok()?;
+        js_sys::Function::from(f).call0(p).ok()?.as_f64()
+    });
+    now.unwrap_or_else(js_sys::Date::now) * 1000.0
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now_us() -> f64 {
+    use std::sync::OnceLock;
+    use std::time::Instant;
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_secs_f64() * 1e6
+}
+
+#[derive(Clone, Debug)]
+pub struct StepProfile {
+    pub step: usize,
+    pub layer_type: u8,
+    pub layer_id: u32,
+    pub inputs: Vec<Vec<usize>>,
+    pub output: Vec<usize>,
+    /// Mulai relatif terhadap awal run (µs).
+    pub start_us: f64,
+    pub time_us: f64,
+    pub flops: u64,
+    pub out_bytes: usize,
+}
+
+fn shape_json(s: &[usize]) -> String {
+    format!("[{}]", s.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(","))
+}
+
+fn shapes_json(v: &[Vec<usize>]) -> String {
+    format!("[{}]", v.iter().map(|s| shape_json(s)).collect::<Vec<_>>().join(","))
+}
+
+/// {"total_us","total_flops","total_out_bytes","steps":[{"step","type","name","id","inputs","output","time_us","flops","out_bytes"}]}
+pub fn profile_json(steps: &[StepProfile], total_us: f64) -> String {
+    let entries: Vec<String> = steps
+        .iter()
+        .map(|p| {
+            JsonObj::new()
+                .num("step", p.step)
+                .num("type", p.layer_type)
+                .str("name", layer_type_name(p.layer_type))
+                .num("id", p.layer_id)
+                .raw("inputs", &shapes_json(&p.inputs))
+                .raw("output", &shape_json(&p.output))
+                .float("time_us", p.time_us)
+                .num("flops", p.flops)
+                .num("out_bytes", p.out_bytes)
+                .finish()
+        })
+        .collect();
+    JsonObj::new()
+        .float("total_us", total_us)
+        .num("total_flops", steps.iter().map(|p| p.flops).sum::<u64>())
+        .num("total_out_bytes", steps.iter().map(|p| p.out_bytes).sum::<usize>())
+        .raw("steps", &format!("[{}]", entries.join(",")))
+        .finish()
+}
+
+/// Chrome trace event format: satu event "X" (complete) per step, ts/dur dalam µs.
+pub fn chrome_trace(steps: &[StepProfile]) -> String {
+    let events: Vec<String> = steps
+        .iter()
+        .map(|p| {
+            let name = layer_type_name(p.layer_type);
+            let args = JsonObj::new()
+                .num("step", p.step)
+                .raw("inputs", &shapes_json(&p.inputs))
+                .raw("output", &shape_json(&p.output))
+                .num("flops", p.flops)
+                .num("out_bytes", p.out_bytes)
+                .finish();
+            JsonObj::new()
+                .str("name", &format!("{}#{}", name, p.layer_id))
+                .str("cat", name)
+                .str("ph", "X")
+                .float("ts", p.start_us)
+                .float("dur", p.time_us)
+                .num("pid", 1)
+                .num("tid", 1)
+                .raw("args", &args)
+                .finish()
+        })
+        .collect();
+    format!("{{\"traceEvents\":[{}],\"displayTimeUnit\":\"ms\"}}", events.join(","))
+}
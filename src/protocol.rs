@@ -20,12 +20,17 @@ pub const LAYER_CONV:        u8 = 0x03;
 pub const LAYER_ACTIVATION:  u8 = 0x04;
 pub const LAYER_EMBEDDING:   u8 = 0x05;
 pub const LAYER_POOL:        u8 = 0x06;
+pub const LAYER_RESAMPLE:    u8 = 0x07;
 
 // --- Custom layers (0x10+) ---
 pub const LAYER_SHIFT:       u8 = 0x10;
 pub const LAYER_GHOST:       u8 = 0x11;
 pub const LAYER_SEBLOCK:     u8 = 0x12;
 pub const LAYER_BINARY:      u8 = 0x13;
+pub const LAYER_TENSOR_OP:   u8 = 0x14;
+pub const LAYER_ECA:         u8 = 0x15;
+pub const LAYER_CBAM:        u8 = 0x16;
+pub const LAYER_SPATIAL_ATTN: u8 = 0x17;
 // ============================================================
 // VARIANTS — Pilihan dalam 1 engine
 // ============================================================
@@ -34,6 +39,13 @@ pub const LAYER_BINARY:      u8 = 0x13;
 pub const CONV_CONV1D:          u8 = 0x00;
 pub const CONV_CONV2D:          u8 = 0x01;
 pub const CONV_CONVTRANSPOSE2D: u8 = 0x02;
+pub const CONV_CONV3D:          u8 = 0x03;
+pub const CONV_CONVTRANSPOSE1D: u8 = 0x04;
+
+// Conv padding mode (byte di tail payload conv)
+pub const CONV_PAD_EXPLICIT: u8 = 0x00;
+pub const CONV_PAD_SAME:     u8 = 0x01;
+pub const CONV_PAD_VALID:    u8 = 0x02;
 
 // Norm variants
 pub const NORM_BATCH:     u8 = 0x00;
@@ -64,21 +76,152 @@ pub const POOL_MAXPOOL2D:          u8 = 0x01;
 pub const POOL_AVGPOOL1D:          u8 = 0x02;
 pub const POOL_AVGPOOL2D:          u8 = 0x03;
 pub const POOL_ADAPTIVEAVGPOOL2D:  u8 = 0x04;
+pub const POOL_ADAPTIVEMAXPOOL2D:  u8 = 0x05;
+pub const POOL_ADAPTIVEAVGPOOL1D:  u8 = 0x06;
+pub const POOL_GLOBALAVGPOOL:      u8 = 0x07;
+pub const POOL_GLOBALMAXPOOL:      u8 = 0x08;
 
 // Shift variants
 pub const SHIFT_UP:    u8 = 0x00;
 pub const SHIFT_DOWN:  u8 = 0x01;
 pub const SHIFT_LEFT:  u8 = 0x02;
 pub const SHIFT_RIGHT: u8 = 0x03;
+pub const SHIFT_UP_LEFT:    u8 = 0x04;
+pub const SHIFT_UP_RIGHT:   u8 = 0x05;
+pub const SHIFT_DOWN_LEFT:  u8 = 0x06;
+pub const SHIFT_DOWN_RIGHT: u8 = 0x07;
+pub const SHIFT_GROUPED:    u8 = 0x08; // channel group i -> arah ke-i
+
+// Shift fill mode (tail payload)
+pub const SHIFT_MODE_CONSTANT:  u8 = 0x00;
+pub const SHIFT_MODE_CIRCULAR:  u8 = 0x01;
+pub const SHIFT_MODE_REPLICATE: u8 = 0x02;
+// Resample variants (upsampling spasial)
+pub const RESAMPLE_NEAREST:       u8 = 0x00;
+pub const RESAMPLE_BILINEAR:      u8 = 0x01;
+pub const RESAMPLE_PIXEL_SHUFFLE: u8 = 0x02;
+
+// SE squeeze (tail payload SEBLOCK)
+pub const SE_SQUEEZE_AVG: u8 = 0x00;
+pub const SE_SQUEEZE_MAX: u8 = 0x01;
+
 // Binary variants (op 2-input)
 pub const BINARY_ADD:    u8 = 0x00;
 pub const BINARY_SUB:    u8 = 0x01;
 pub const BINARY_MUL:    u8 = 0x02;
 pub const BINARY_MATMUL: u8 = 0x03;
 pub const BINARY_CONCAT: u8 = 0x04;
+pub const BINARY_DIV:    u8 = 0x05;
+pub const BINARY_MAX:    u8 = 0x06;
+pub const BINARY_MIN:    u8 = 0x07;
+pub const BINARY_POW:    u8 = 0x08;
+pub const BINARY_WHERE:  u8 = 0x09; // a di mana b != 0, selain itu fill
+
+// Tensor-op variants (stateless reshape/reduce/elementwise)
+pub const TENSOR_RESHAPE:    u8 = 0x00;
+pub const TENSOR_PERMUTE:    u8 = 0x01;
+pub const TENSOR_TRANSPOSE:  u8 = 0x02;
+pub const TENSOR_FLATTEN:    u8 = 0x03;
+pub const TENSOR_SLICE:      u8 = 0x04;
+pub const TENSOR_PAD:        u8 = 0x05;
+pub const TENSOR_MEAN:       u8 = 0x06;
+pub const TENSOR_SUM:        u8 = 0x07;
+pub const TENSOR_MAX:        u8 = 0x08;
+pub const TENSOR_SCALE_BIAS: u8 = 0x09;
+
+// Pad mode (TENSOR_PAD)
+pub const PAD_CONSTANT: u8 = 0x00;
+pub const PAD_REFLECT:  u8 = 0x01;
+pub const PAD_EDGE:     u8 = 0x02;
+
+// ============================================================
+// PROTOCOL VERSION — handshake JS <-> wasm (rilis beda jadwal)
+// ============================================================
+// v1: header 8 byte polos (legacy, tetap diterima).
+// v2: prefix 4 byte [PACKET_MAGIC.., version] sebelum header 8 byte. Perubahan layout:
+//     BINARY `id` (+ CONCAT `dim`, + WHERE `fill?`) — tanpa `dim` palsu untuk op lain;
+//     NORM_GROUP `id, num_groups, num_channels, eps?` — tanpa `size` yang diabaikan.
+pub const PROTOCOL_VERSION:     u8 = 2;
+pub const PROTOCOL_MIN_VERSION: u8 = 1;
+// Byte pertama 0xB7 tidak pernah opcode valid -> prefix tidak bisa tertukar dengan header v1.
+pub const PACKET_MAGIC: [u8; 3] = [0xB7, b'R', b'P'];
+pub const PACKET_PREFIX_LEN: usize = 4;
+// Plan graph v2 (slot u16, lihat plan::PlanHeader): prefix [PLAN_MAGIC.., 2]. Plan v1 diawali
+// num_steps u32 LE -> tertukar hanya kalau num_steps >= 0x5047B7.
+pub const PLAN_MAGIC: [u8; 3] = [0xB7, b'G', b'P'];
+pub const PLAN_VERSIONS: [u8; 2] = [1, 2];
+
+#[wasm_bindgen(js_name = protocolVersion)]
+pub fn protocol_version() -> u8 {
+    PROTOCOL_VERSION
+}
+
+/// Opcode yang diproses stream paket (LayerRegistry::apply_packets).
+pub const PACKET_OPCODES: [u8; 3] = [OP_INIT, OP_LOAD_STATE, OP_DESTROY];
+
+/// (layer_type, nama, varian yang didukung). VARIANT_NONE = tipe tanpa varian.
+pub const LAYER_VARIANTS: &[(u8, &str, &[u8])] = &[
+    (LAYER_LINEAR, "linear", &[VARIANT_NONE]),
+    (LAYER_NORM, "norm", &[NORM_BATCH, NORM_GROUP, NORM_INSTANCE, NORM_LAYER, NORM_RMS]),
+    (LAYER_CONV, "conv", &[CONV_CONV1D, CONV_CONV2D, CONV_CONVTRANSPOSE2D, CONV_CONV3D, CONV_CONVTRANSPOSE1D]),
+    (LAYER_ACTIVATION, "activation", &[
+        ACT_GELU, ACT_RELU, ACT_SIGMOID, ACT_TANH, ACT_HARDSWISH, ACT_LEAKYRELU, ACT_PRELU,
+        ACT_SWIGLU, ACT_HARDSIGMOID, ACT_SOFTPLUS, ACT_MISH, ACT_SOFTMAX, ACT_LOGSOFTMAX, ACT_GLU,
+    ]),
+    (LAYER_EMBEDDING, "embedding", &[VARIANT_NONE]),
+    (LAYER_POOL, "pool", &[
+        POOL_MAXPOOL1D, POOL_MAXPOOL2D, POOL_AVGPOOL1D, POOL_AVGPOOL2D, POOL_ADAPTIVEAVGPOOL2D,
+        POOL_ADAPTIVEMAXPOOL2D, POOL_ADAPTIVEAVGPOOL1D, POOL_GLOBALAVGPOOL, POOL_GLOBALMAXPOOL,
+    ]),
+    (LAYER_RESAMPLE, "resample", &[RESAMPLE_NEAREST, RESAMPLE_BILINEAR, RESAMPLE_PIXEL_SHUFFLE]),
+    (LAYER_SHIFT, "shift", &[
+        SHIFT_UP, SHIFT_DOWN, SHIFT_LEFT, SHIFT_RIGHT, SHIFT_UP_LEFT, SHIFT_UP_RIGHT,
+        SHIFT_DOWN_LEFT, SHIFT_DOWN_RIGHT, SHIFT_GROUPED,
+    ]),
+    (LAYER_GHOST, "ghost", &[VARIANT_NONE]),
+    (LAYER_SEBLOCK, "seblock", &[VARIANT_NONE]),
+    (LAYER_BINARY, "binary", &[
+        BINARY_ADD, BINARY_SUB, BINARY_MUL, BINARY_MATMUL, BINARY_CONCAT, BINARY_DIV,
+        BINARY_MAX, BINARY_MIN, BINARY_POW, BINARY_WHERE,
+    ]),
+    (LAYER_TENSOR_OP, "tensor_op", &[
+        TENSOR_RESHAPE, TENSOR_PERMUTE, TENSOR_TRANSPOSE, TENSOR_FLATTEN, TENSOR_SLICE,
+        TENSOR_PAD, TENSOR_MEAN, TENSOR_SUM, TENSOR_MAX, TENSOR_SCALE_BIAS,
+    ]),
+    (LAYER_ECA, "eca", &[VARIANT_NONE]),
+    (LAYER_CBAM, "cbam", &[VARIANT_NONE]),
+    (LAYER_SPATIAL_ATTN, "spatial_attention", &[VARIANT_NONE]),
+];
+
+/// Nama tipe dari LAYER_VARIANTS ("unknown" kalau tidak terdaftar).
+pub fn layer_type_name(layer_type: u8) -> &'static str {
+    LAYER_VARIANTS.iter().find(|v| v.0 == layer_type).map_or("unknown", |v| v.1)
+}
+
+/// JSON: versi (+ minimum yang diterima), magic, opcode stream, dan layer + varian.
+/// Client JS cek ini sebelum mengirim tipe/varian baru ke build wasm yang lebih lama.
+#[wasm_bindgen]
+pub fn capabilities() -> String {
+    let list = |v: &[u8]| v.iter().map(|b| b.to_string()).collect::<Vec<_>>().join(",");
+    let layers = LAYER_VARIANTS
+        .iter()
+        .map(|(t, name, vars)| format!("{{\"type\":{},\"name\":\"{}\",\"variants\":[{}]}}", t, name, list(vars)))
+        .collect::<Vec<_>>()
+        .join(",");
+    format!(
+        "{{\"protocol_version\":{},\"min_protocol_version\":{},\"magic\":[{}],\"opcodes\":[{}],\"plan_versions\":[{}],\"init_schemes\":[{}],\"layers\":[{}]}}",
+        PROTOCOL_VERSION,
+        PROTOCOL_MIN_VERSION,
+        list(&PACKET_MAGIC),
+        list(&PACKET_OPCODES),
+        list(&PLAN_VERSIONS),
+        list(&INIT_SCHEMES),
+        layers
+    )
+}
 
 // ============================================================
-// PACKET HEADER — Fixed 8 bytes
+// PACKET HEADER — Fixed 8 bytes (+ prefix versi 4 byte untuk v2)
 // ============================================================
 // [0]     : OpCode
 // [1]     : LayerType
@@ -94,6 +237,8 @@ pub struct PacketHeader {
     pub variant: u8,
     pub flags: u8,
     pub payload_len: u32,
+    /// Versi layout payload (1 = header polos tanpa prefix).
+    pub version: u8,
 }
 
 #[wasm_bindgen]
@@ -109,12 +254,51 @@ impl PacketHeader {
             variant: bytes[2],
             flags: bytes[3],
             payload_len: u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+            version: 1,
         })
     }
 
+    /// Header dari awal paket: prefix magic -> versi dari prefix; tanpa prefix -> v1
+    /// (opcode harus dikenal, supaya byte acak/format asing tidak salah di-parse).
+    #[wasm_bindgen(js_name = fromPacket)]
+    pub fn from_packet(bytes: &[u8]) -> Result<PacketHeader, String> {
+        if bytes.first() == Some(&PACKET_MAGIC[0]) {
+            if bytes.len() < PACKET_PREFIX_LEN || bytes[..3] != PACKET_MAGIC {
+                return Err("bad packet magic".into());
+            }
+            let version = bytes[3];
+            if version < 2 || version > PROTOCOL_VERSION {
+                return Err(format!(
+                    "unsupported packet protocol v{} (this build: v{}..=v{}, prefix needs v2+)",
+                    version, PROTOCOL_MIN_VERSION, PROTOCOL_VERSION
+                ));
+            }
+            let mut h = Self::from_bytes(&bytes[PACKET_PREFIX_LEN..])?;
+            h.version = version;
+            return Ok(h);
+        }
+        let h = Self::from_bytes(bytes)?;
+        if !(OP_INIT..=OP_RUN_GRAPH).contains(&h.opcode) {
+            return Err(format!("unknown opcode 0x{:02X} in unversioned (v1) packet", h.opcode));
+        }
+        Ok(h)
+    }
+
+    /// Panjang header ter-encode: 8 (v1) atau 12 (prefix + 8).
+    #[wasm_bindgen(js_name = encodedLen)]
+    pub fn encoded_len(&self) -> usize {
+        if self.version >= 2 { PACKET_PREFIX_LEN + 8 } else { 8 }
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut buf = vec![0u8; 8];
-        self.write_to_slice(&mut buf);
+        let mut buf = Vec::with_capacity(self.encoded_len());
+        if self.version >= 2 {
+            buf.extend_from_slice(&PACKET_MAGIC);
+            buf.push(self.version);
+        }
+        let mut core = [0u8; 8];
+        self.write_to(&mut core);
+        buf.extend_from_slice(&core);
         buf
     }
 
@@ -169,6 +353,24 @@ impl PacketHeader {
 pub const VARIANT_NONE: u8 = 0xFF;
 pub const FLAG_BIAS: u8 = 1 << 0;
 pub const FLAG_TRAINING: u8 = 1 << 1;
+/// OP_INIT: payload diakhiri trailer init `[scheme u8][seed u32][param f32]` (INIT_TRAILER_LEN byte),
+/// dibuang sebelum payload per tipe dibaca -> berlaku untuk semua tipe dan semua versi paket.
+pub const FLAG_INIT_SCHEME: u8 = 1 << 2;
+pub const INIT_TRAILER_LEN: usize = 9;
+
+// Skema init bobot (lihat layers::init::InitScheme). param: CONSTANT nilai, NORMAL std,
+// UNIFORM batas (U(-a, a)), KAIMING/XAVIER/ORTHOGONAL gain (0 = 1).
+pub const INIT_DEFAULT:    u8 = 0x00;
+pub const INIT_ZEROS:      u8 = 0x01;
+pub const INIT_CONSTANT:   u8 = 0x02;
+pub const INIT_NORMAL:     u8 = 0x03;
+pub const INIT_UNIFORM:    u8 = 0x04;
+pub const INIT_KAIMING:    u8 = 0x05;
+pub const INIT_XAVIER:     u8 = 0x06;
+pub const INIT_ORTHOGONAL: u8 = 0x07;
+pub const INIT_SCHEMES: [u8; 8] = [
+    INIT_DEFAULT, INIT_ZEROS, INIT_CONSTANT, INIT_NORMAL, INIT_UNIFORM, INIT_KAIMING, INIT_XAVIER, INIT_ORTHOGONAL,
+];
 
 pub struct PayloadCursor<'a> {
     data: &'a [u8],
@@ -206,6 +408,14 @@ impl<'a> PayloadCursor<'a> {
         Ok(v)
     }
 
+    #[inline]
+    pub fn read_u16(&mut self) -> Result<u16, String> {
+        self.ensure(2)?;
+        let v = u16::from_le_bytes([self.data[self.pos], self.data[self.pos + 1]]);
+        self.pos += 2;
+        Ok(v)
+    }
+
     #[inline]
     pub fn read_u32(&mut self) -> Result<u32, String> {
         self.ensure(4)?;
@@ -222,6 +432,11 @@ impl<'a> PayloadCursor<'a> {
         Ok(u32::from_le_bytes(b))
     }
 
+    #[inline]
+    pub fn read_i32(&mut self) -> Result<i32, String> {
+        self.read_u32().map(|v| v as i32)
+    }
+
     #[inline]
     pub fn read_f32(&mut self) -> Result<f32, String> {
         self.ensure(4)?;
@@ -281,12 +496,76 @@ impl<'a> PayloadCursor<'a> {
         Ok(self.read_option_u32()?.map(|v| v as usize))
     }
 
+    /// n byte mentah berikutnya (string/blob dengan prefix panjang).
+    #[inline]
+    pub fn read_bytes(&mut self, n: usize) -> Result<&'a [u8], String> {
+        self.ensure(n)?;
+        let v = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(v)
+    }
+
     #[inline]
     pub fn remaining(&self) -> usize {
         self.data.len().saturating_sub(self.pos)
     }
 }
 
+/// Pasangan PayloadCursor: encoding sama (LE, Option = tag u8 + nilai fixed-size).
+#[derive(Default)]
+pub struct PayloadWriter {
+    buf: Vec<u8>,
+}
+
+impl PayloadWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn u8(mut self, v: u8) -> Self {
+        self.buf.push(v);
+        self
+    }
+
+    pub fn u32(mut self, v: u32) -> Self {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+        self
+    }
+
+    pub fn i32(self, v: i32) -> Self {
+        self.u32(v as u32)
+    }
+
+    pub fn f64(mut self, v: f64) -> Self {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+        self
+    }
+
+    pub fn bool(self, v: bool) -> Self {
+        self.u8(v as u8)
+    }
+
+    pub fn usize(self, v: usize) -> Self {
+        self.u32(v as u32)
+    }
+
+    pub fn option_u32(self, v: Option<u32>) -> Self {
+        self.bool(v.is_some()).u32(v.unwrap_or(0))
+    }
+
+    pub fn option_usize(self, v: Option<usize>) -> Self {
+        self.option_u32(v.map(|v| v as u32))
+    }
+
+    pub fn option_f64(self, v: Option<f64>) -> Self {
+        self.bool(v.is_some()).f64(v.unwrap_or(0.0))
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
 // ============================================================
 // HELPER: read/write multi-byte dari payload
 // ============================================================
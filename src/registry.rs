@@ -1,19 +1,38 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use wasm_bindgen::prelude::*;
 use crate::WasmTensor;
 use crate::protocol::*;
 use crate::layers::linear::WasmLinear;
 use crate::layers::norm::WasmNorm;
-use crate::layers::conv::WasmConv;
+use crate::layers::conv::{ConvPadding, ConvSpec, WasmConv};
 use crate::layers::activation::WasmActivation;
 use crate::layers::embedding::WasmEmbedding;
 use crate::layers::pool::WasmPool;
+use crate::layers::resample::WasmResample;
 use crate::layers::custom::shift::WasmShift;
 use crate::layers::custom::ghost::WasmGhostModule;
-use crate::layers::custom::seblock::WasmSeBlock;
+use crate::layers::custom::seblock::{SeBlockConfig, WasmSeBlock};
+use crate::layers::custom::eca::WasmEcaBlock;
+use crate::layers::custom::cbam::WasmCbamBlock;
+use crate::layers::custom::spatial_attention::WasmSpatialAttention;
+use crate::layers::activation::ActivationConfig;
 use crate::layers::binary::WasmBinary;
+use crate::layers::tensor_op::{Reduce, TensorOp, WasmTensorOp};
+use crate::layers::binary::{Binary, BinaryOp};
+use crate::layers::resample::{Resample, ResampleSize};
+use crate::layers::custom::shift::{Shift, ShiftDirection, ShiftFill};
+use crate::layers::custom::ghost::GhostModuleConfig;
+use crate::layers::custom::eca::EcaBlockConfig;
+use crate::layers::custom::cbam::CbamBlockConfig;
+use crate::layers::custom::spatial_attention::SpatialAttentionConfig;
+use crate::api::{ActivationSpec, ConvKind, EmbeddingSpec, LayerRef, LinearSpec, NormSpec, PoolSpec, Tensor4};
+use crate::layers::layout::{config_json, JsonObj};
+use crate::layers::init::{InitScheme, WeightShape};
+use burn::tensor::ops::PadMode;
 
 type LayerId = u32;
+/// (tipe, id, [(offset, len)]) — potongan flat satu layer.
+type LayerRanges = (u8, LayerId, Vec<(usize, usize)>);
 
 #[wasm_bindgen]
 pub struct LayerRegistry {
@@ -23,11 +42,28 @@ pub struct LayerRegistry {
     activations: HashMap<LayerId, WasmActivation>,
     embeddings:  HashMap<LayerId, WasmEmbedding>,
     pools:       HashMap<LayerId, WasmPool>,
+    resamples:   HashMap<LayerId, WasmResample>,
     shifts:      HashMap<LayerId, WasmShift>,
     ghosts:      HashMap<LayerId, WasmGhostModule>,
     seblocks:    HashMap<LayerId, WasmSeBlock>,
+    ecas:        HashMap<LayerId, WasmEcaBlock>,
+    cbams:       HashMap<LayerId, WasmCbamBlock>,
+    spatials:    HashMap<LayerId, WasmSpatialAttention>,
     binaries:    HashMap<LayerId, WasmBinary>,
+    tensor_ops:  HashMap<LayerId, WasmTensorOp>,
     cached_params: usize,
+    /// (tipe, id) -> (varian, config JSON) dicatat saat add_* (untuk listLayers/describeLayer).
+    meta:        HashMap<(u8, LayerId), (u8, String)>,
+    /// (tipe, id) -> paket OP_INIT asli (header + payload) dari initLayer, untuk ditulis ulang ke .bmodel.
+    /// Layer dari add_* (API Rust) tidak punya entri.
+    init_packets: HashMap<(u8, LayerId), Vec<u8>>,
+    /// Linear id -> segmen bobot layer lain yang dipakai bersama (tieWeights).
+    ties:        HashMap<LayerId, WeightTie>,
+    /// Segmen (tipe, id, nama) yang dibekukan; default semua trainable (setTrainable).
+    frozen:      HashSet<(u8, LayerId, &'static str)>,
+    /// Mode namespace id global (withGlobalIds): id -> tipe, satu id hanya boleh satu tipe.
+    /// None = mode lama (id per tipe, linear 1 dan conv 1 boleh berdampingan).
+    id_types:    Option<HashMap<LayerId, u8>>,
 }
 
 macro_rules! insert_layer {
@@ -83,50 +119,128 @@ impl LayerRegistry {
             activations: HashMap::new(),
             embeddings:  HashMap::new(),
             pools:       HashMap::new(),
+            resamples:   HashMap::new(),
             shifts:      HashMap::new(),
             ghosts:      HashMap::new(),
             seblocks:    HashMap::new(),
+            ecas:        HashMap::new(),
+            cbams:       HashMap::new(),
+            spatials:    HashMap::new(),
             binaries:    HashMap::new(),
+            tensor_ops:  HashMap::new(),
             cached_params: 0,
+            meta:        HashMap::new(),
+            init_packets: HashMap::new(),
+            ties:        HashMap::new(),
+            frozen:      HashSet::new(),
+            id_types:    None,
         }
     }
 
+    /// Registry dengan namespace id global: tipe cukup dicari dari id
+    /// (forwardById/destroyById/compileGraphIds). Id bentrok antar tipe -> Err saat init.
+    #[wasm_bindgen(js_name = withGlobalIds)]
+    pub fn with_global_ids() -> LayerRegistry {
+        let mut reg = LayerRegistry::new();
+        reg.id_types = Some(HashMap::new());
+        reg
+    }
+
+    #[wasm_bindgen(getter, js_name = globalIds)]
+    pub fn global_ids(&self) -> bool {
+        self.id_types.is_some()
+    }
+
     #[wasm_bindgen(js_name = initLayer)]
     pub fn init_layer(&mut self, header: &PacketHeader, payload: &[u8]) -> Result<(), String> {
-        let payload = header.validate_payload(payload)?;
-        match header.layer_type {
+        let full = header.validate_payload(payload)?;
+        // trailer skema init dilepas dulu: payload per tipe punya tail opsional sendiri
+        let (payload, init) = if header.flags & FLAG_INIT_SCHEME != 0 {
+            let split = full
+                .len()
+                .checked_sub(INIT_TRAILER_LEN)
+                .ok_or("initLayer: payload too short for init scheme trailer")?;
+            let init = InitScheme::from_trailer(&full[split..])?;
+            if init.0 != InitScheme::Default && !supports_init_scheme(header.layer_type) {
+                return Err(format!("initLayer: init scheme not supported for {}", layer_type_name(header.layer_type)));
+            }
+            (&full[..split], Some(init))
+        } else {
+            (full, None)
+        };
+        let res = match header.layer_type {
             LAYER_LINEAR      => self.init_linear(header, payload),
             LAYER_NORM        => self.init_norm(header, payload),
             LAYER_CONV        => self.init_conv(header, payload),
             LAYER_ACTIVATION  => self.init_activation(header, payload),
             LAYER_EMBEDDING   => self.init_embedding(header, payload),
             LAYER_POOL        => self.init_pool(header, payload),
+            LAYER_RESAMPLE    => self.init_resample(header, payload),
             LAYER_SHIFT       => self.init_shift(header, payload),
             LAYER_GHOST       => self.init_ghost(header, payload),
             LAYER_SEBLOCK     => self.init_seblock(header, payload),
+            LAYER_ECA         => self.init_eca(header, payload),
+            LAYER_CBAM        => self.init_cbam(header, payload),
+            LAYER_SPATIAL_ATTN => self.init_spatial_attn(header, payload),
             LAYER_BINARY      => self.init_binary(header, payload),
+            LAYER_TENSOR_OP   => self.init_tensor_op(header, payload),
             _ => Err(format!("Unknown layer type: 0x{:02X}", header.layer_type)),
+        };
+        res?;
+        let id = PayloadCursor::new(payload).read_u32()?;
+        if let Some((scheme, seed)) = init {
+            // gagal = layer dilepas lagi, jangan tertinggal dengan bobot default tanpa paket init
+            if let Err(e) = self.reinit(LayerRef { layer_type: header.layer_type, id }, scheme, seed) {
+                self.destroy_layer(id, header.layer_type);
+                return Err(e);
+            }
         }
+        let mut packet = header.to_bytes();
+        packet.extend_from_slice(full);
+        self.init_packets.insert((header.layer_type, id), packet);
+        Ok(())
     }
 
     #[wasm_bindgen(js_name = forwardLayer)]
     pub fn forward_layer(&self, layer_id: LayerId, layer_type: u8, input: &WasmTensor) -> Result<WasmTensor, String> {
+        self.check_type(layer_type, layer_id)?;
         match layer_type {
             LAYER_LINEAR      => self.linears.get(&layer_id).map(|l| l.forward(input)).ok_or("Linear not found".into()),
             LAYER_NORM        => self.norms.get(&layer_id).map(|l| l.forward(input)).ok_or("Norm not found".into()),
-            LAYER_CONV        => self.convs.get(&layer_id).map(|l| l.forward(input)).ok_or("Conv not found".into()),
+            LAYER_CONV        => self.convs.get(&layer_id).ok_or("Conv not found")?.forward(input),
             LAYER_ACTIVATION  => self.activations.get(&layer_id).map(|l| l.forward(input)).ok_or("Activation not found".into()),
             LAYER_EMBEDDING   => self.embeddings.get(&layer_id).map(|l| l.forward(input)).ok_or("Embedding not found".into()),
             LAYER_POOL        => self.pools.get(&layer_id).map(|l| l.forward(input)).ok_or("Pool not found".into()),
+            LAYER_RESAMPLE    => self.resamples.get(&layer_id).ok_or("Resample not found")?.forward(input),
             LAYER_SHIFT       => self.shifts.get(&layer_id).map(|l| l.forward(input)).ok_or("Shift not found".into()),
             LAYER_GHOST       => self.ghosts.get(&layer_id).map(|l| l.forward(input)).ok_or("Ghost not found".into()),
             LAYER_SEBLOCK     => self.seblocks.get(&layer_id).map(|l| l.forward(input)).ok_or("SEBlock not found".into()),
+            LAYER_ECA         => self.ecas.get(&layer_id).map(|l| l.forward(input)).ok_or("ECA not found".into()),
+            LAYER_CBAM        => self.cbams.get(&layer_id).map(|l| l.forward(input)).ok_or("CBAM not found".into()),
+            LAYER_SPATIAL_ATTN => self.spatials.get(&layer_id).map(|l| l.forward(input)).ok_or("SpatialAttention not found".into()),
+            LAYER_TENSOR_OP   => self.tensor_ops.get(&layer_id).ok_or("TensorOp not found")?.forward(input),
             _ => Err(format!("Unknown layer type for forward: 0x{:02X}", layer_type)),
         }
     }
 
+    /// forwardLayer lewat MemoryView (tanpa salinan, lihat CompiledGraph::runView).
+    #[wasm_bindgen(js_name = forwardLayerView)]
+    pub fn forward_layer_view(
+        &self,
+        layer_id: LayerId,
+        layer_type: u8,
+        pool: &mut crate::memory::BufferPool,
+        input: &mut crate::memory::MemoryView,
+        output: &mut crate::memory::MemoryView,
+    ) -> Result<(), String> {
+        let x = input.take_tensor(pool);
+        let out = self.forward_layer(layer_id, layer_type, &x)?;
+        output.put_tensor(out, pool)
+    }
+
     #[wasm_bindgen(js_name = getLayerState)]
     pub fn get_layer_state(&self, layer_id: LayerId, layer_type: u8) -> Result<Vec<u8>, String> {
+        self.check_type(layer_type, layer_id)?;
         match layer_type {
             LAYER_LINEAR      => self.linears.get(&layer_id).ok_or("Not found")?.get_state(),
             LAYER_NORM        => self.norms.get(&layer_id).ok_or("Not found")?.get_state(),
@@ -135,14 +249,18 @@ impl LayerRegistry {
             LAYER_EMBEDDING   => self.embeddings.get(&layer_id).ok_or("Not found")?.get_state(),
             LAYER_GHOST       => self.ghosts.get(&layer_id).ok_or("Not found")?.get_state(),
             LAYER_SEBLOCK     => self.seblocks.get(&layer_id).ok_or("Not found")?.get_state(),
-            LAYER_POOL | LAYER_SHIFT | LAYER_BINARY => Ok(vec![]), // stateless
+            LAYER_ECA         => self.ecas.get(&layer_id).ok_or("Not found")?.get_state(),
+            LAYER_CBAM        => self.cbams.get(&layer_id).ok_or("Not found")?.get_state(),
+            LAYER_SPATIAL_ATTN => self.spatials.get(&layer_id).ok_or("Not found")?.get_state(),
+            LAYER_POOL | LAYER_RESAMPLE | LAYER_SHIFT | LAYER_BINARY | LAYER_TENSOR_OP => Ok(vec![]), // stateless
             _ => Err(format!("Unknown layer type for get_state: 0x{:02X}", layer_type)),
         }
     }
 
     #[wasm_bindgen(js_name = loadLayerState)]
     pub fn load_layer_state(&mut self, layer_id: LayerId, layer_type: u8, data: &[u8]) -> Result<(), String> {
-        match layer_type {
+        self.check_type(layer_type, layer_id)?;
+        let res = match layer_type {
             LAYER_LINEAR      => load_layer_state!(self, linears, layer_id, data),
             LAYER_NORM        => load_layer_state!(self, norms, layer_id, data),
             LAYER_CONV        => load_layer_state!(self, convs, layer_id, data),
@@ -150,13 +268,30 @@ impl LayerRegistry {
             LAYER_EMBEDDING   => load_layer_state!(self, embeddings, layer_id, data),
             LAYER_GHOST       => load_layer_state!(self, ghosts, layer_id, data),
             LAYER_SEBLOCK     => load_layer_state!(self, seblocks, layer_id, data),
-            LAYER_POOL | LAYER_SHIFT | LAYER_BINARY => Ok(()), // stateless
+            LAYER_ECA         => load_layer_state!(self, ecas, layer_id, data),
+            LAYER_CBAM        => load_layer_state!(self, cbams, layer_id, data),
+            LAYER_SPATIAL_ATTN => load_layer_state!(self, spatials, layer_id, data),
+            LAYER_POOL | LAYER_RESAMPLE | LAYER_SHIFT | LAYER_BINARY | LAYER_TENSOR_OP => Ok(()), // stateless
             _ => Err(format!("Unknown layer type for load_state: 0x{:02X}", layer_type)),
-        }
+        };
+        res?;
+        self.sync_ties(layer_type, layer_id)
     }
 
     #[wasm_bindgen(js_name = destroyLayer)]
     pub fn destroy_layer(&mut self, layer_id: LayerId, layer_type: u8) -> bool {
+        if self.check_type(layer_type, layer_id).is_err() {
+            return false;
+        }
+        self.meta.remove(&(layer_type, layer_id));
+        self.init_packets.remove(&(layer_type, layer_id));
+        self.drop_ties(layer_type, layer_id);
+        self.frozen.retain(|&(t, id, _)| (t, id) != (layer_type, layer_id));
+        if let Some(ids) = &mut self.id_types {
+            if ids.get(&layer_id) == Some(&layer_type) {
+                ids.remove(&layer_id);
+            }
+        }
         match layer_type {
             LAYER_LINEAR      => remove_layer!(self, linears, layer_id),
             LAYER_NORM        => remove_layer!(self, norms, layer_id),
@@ -165,49 +300,111 @@ impl LayerRegistry {
             LAYER_EMBEDDING   => remove_layer!(self, embeddings, layer_id),
             LAYER_GHOST       => remove_layer!(self, ghosts, layer_id),
             LAYER_SEBLOCK     => remove_layer!(self, seblocks, layer_id),
+            LAYER_ECA         => remove_layer!(self, ecas, layer_id),
+            LAYER_CBAM        => remove_layer!(self, cbams, layer_id),
+            LAYER_SPATIAL_ATTN => remove_layer!(self, spatials, layer_id),
             LAYER_POOL        => self.pools.remove(&layer_id).is_some(),
+            LAYER_RESAMPLE    => self.resamples.remove(&layer_id).is_some(),
             LAYER_SHIFT       => self.shifts.remove(&layer_id).is_some(),
             LAYER_BINARY      => self.binaries.remove(&layer_id).is_some(),
+            LAYER_TENSOR_OP   => self.tensor_ops.remove(&layer_id).is_some(),
             _ => false,
         }
     }
 
     #[wasm_bindgen(js_name = totalParams)]
     pub fn total_params(&self) -> usize {
-        self.cached_params
+        // segmen bersama (tieWeights) dihitung sekali, di layer sumber
+        let shared: usize = self.ties.values().map(|t| t.len).sum();
+        self.cached_params.saturating_sub(shared)
+    }
+
+    /// Stream paket `[header][payload]` berurutan (mis. snapshot registry dari file);
+    /// tiap paket boleh v1 (header polos) atau v2+ (prefix magic, lihat PacketHeader::from_packet).
+    /// OP_INIT -> initLayer; OP_LOAD_STATE -> payload `[id u32][state]`;
+    /// OP_DESTROY -> payload `[id u32]`. Mengembalikan jumlah paket yang diproses.
+    #[wasm_bindgen(js_name = applyPackets)]
+    pub fn apply_packets(&mut self, stream: &[u8]) -> Result<u32, String> {
+        let mut pos = 0usize;
+        let mut count = 0u32;
+        while pos < stream.len() {
+            let header = PacketHeader::from_packet(&stream[pos..])
+                .map_err(|e| format!("applyPackets: packet {} at byte {}: {}", count, pos, e))?;
+            let payload = header
+                .validate_payload(&stream[pos + header.encoded_len()..])
+                .map_err(|e| format!("applyPackets: packet {} at byte {}: {}", count, pos, e))?;
+            let res = match header.opcode {
+                OP_INIT => self.init_layer(&header, payload),
+                OP_LOAD_STATE | OP_DESTROY => {
+                    let id = PayloadCursor::new(payload).read_u32()?;
+                    if header.opcode == OP_DESTROY {
+                        self.destroy_layer(id, header.layer_type);
+                        Ok(())
+                    } else {
+                        self.load_layer_state(id, header.layer_type, &payload[4..])
+                    }
+                }
+                op => Err(format!("unsupported opcode 0x{:02X}", op)),
+            };
+            res.map_err(|e| format!("applyPackets: packet {} at byte {}: {}", count, pos, e))?;
+            pos += header.encoded_len() + payload.len();
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Paket OP_LOAD_STATE untuk satu layer (pasangan applyPackets, untuk menulis snapshot).
+    #[wasm_bindgen(js_name = statePacket)]
+    pub fn state_packet(&self, layer_id: LayerId, layer_type: u8) -> Result<Vec<u8>, String> {
+        let state = self.get_layer_state(layer_id, layer_type)?;
+        let header = PacketHeader {
+            opcode: OP_LOAD_STATE,
+            layer_type,
+            variant: VARIANT_NONE,
+            flags: 0,
+            payload_len: (4 + state.len()) as u32,
+            version: PROTOCOL_VERSION,
+        };
+        let mut out = header.to_bytes();
+        out.extend_from_slice(&layer_id.to_le_bytes());
+        out.extend_from_slice(&state);
+        Ok(out)
     }
 
     // ---- init per tipe ----
     fn init_linear(&mut self, _header: &PacketHeader, payload: &[u8]) -> Result<(), String> {
         let mut c = PayloadCursor::new(payload);
         let id = c.read_u32()?;
-        let in_dim = c.read_usize()?;
-        let out_dim = c.read_usize()?;
-        let bias = c.read_bool()?;
-        let layer = WasmLinear::new(in_dim, out_dim, bias);
-        insert_layer!(self, linears, id, layer);
-        Ok(())
+        let spec = LinearSpec { d_in: c.read_usize()?, d_out: c.read_usize()?, bias: c.read_bool()? };
+        self.add_linear(id, spec).map(|_| ())
     }
     fn init_norm(&mut self, header: &PacketHeader, payload: &[u8]) -> Result<(), String> {
         let mut c = PayloadCursor::new(payload);
         let id = c.read_u32()?;
+        if header.version >= 2 && header.variant == NORM_GROUP {
+            // v2: id, num_groups, num_channels, eps?
+            let (num_groups, num_channels) = (c.read_usize()?, c.read_usize()?);
+            let epsilon = c.read_option_f64()?;
+            return self.add_norm(id, NormSpec::Group { num_groups, num_channels, epsilon }).map(|_| ());
+        }
+        // v1 GROUP: id, size (diabaikan), eps?, num_groups, num_channels
         let size = c.read_usize()?;
-        let eps = c.read_option_f64()?;
-        let layer = match header.variant {
-            NORM_BATCH     => WasmNorm::new_batch_norm(size, eps),
-            NORM_GROUP     => {
-                let num_groups = c.read_usize()?;
-                let num_channels = c.read_usize()?;
-                WasmNorm::new_group_norm(num_groups, num_channels, eps)
-            }
-            NORM_INSTANCE  => WasmNorm::new_instance_norm(size, eps),
-            NORM_LAYER     => WasmNorm::new_layer_norm(size, eps),
-            NORM_RMS       => WasmNorm::new_rms_norm(size, eps),
+        let epsilon = c.read_option_f64()?;
+        let spec = match header.variant {
+            NORM_BATCH     => NormSpec::Batch { num_features: size, epsilon },
+            NORM_GROUP     => NormSpec::Group { num_groups: c.read_usize()?, num_channels: c.read_usize()?, epsilon },
+            NORM_INSTANCE  => NormSpec::Instance { num_channels: size, epsilon },
+            NORM_LAYER     => NormSpec::Layer { size, epsilon },
+            NORM_RMS       => NormSpec::Rms { size, epsilon },
             _ => return Err(format!("Unknown norm variant: 0x{:02X}", header.variant)),
         };
-        insert_layer!(self, norms, id, layer);
-        Ok(())
+        self.add_norm(id, spec).map(|_| ())
     }
+    // Payload conv:
+    //   id, in_ch, out_ch, kh, kw, sh?, sw?, ph?, pw?            (layout lama, tetap valid)
+    //   [tail opsional] dh?, dw?, groups?, bias?, pad_mode(u8)   (CONV_PAD_*)
+    //   [CONV3D saja, wajib] kd, sd?, pd?, dd?
+    // Packet lama berhenti sebelum tail -> default Burn (dilation 1, groups 1, bias, explicit).
     fn init_conv(&mut self, header: &PacketHeader, payload: &[u8]) -> Result<(), String> {
         let mut c = PayloadCursor::new(payload);
         let id = c.read_u32()?;
@@ -215,137 +412,180 @@ impl LayerRegistry {
         let out_ch = c.read_usize()?;
         let kh = c.read_usize()?;
         let kw = c.read_usize()?;
-        let sh = c.read_option_usize()?;
-        let sw = c.read_option_usize()?;
-        let ph = c.read_option_usize()?;
-        let pw = c.read_option_usize()?;
-        let layer = match header.variant {
-            CONV_CONV1D          => WasmConv::new_conv1d(in_ch, out_ch, kh, sh, ph),
-            CONV_CONV2D          => WasmConv::new_conv2d(in_ch, out_ch, kh, kw, sh, sw, ph, pw),
-            CONV_CONVTRANSPOSE2D => WasmConv::new_conv_transpose2d(in_ch, out_ch, kh, kw, sh, sw, ph, pw),
+        let mut spec = ConvSpec::new(in_ch, out_ch, kh, kw);
+        spec.stride_h = c.read_option_usize()?;
+        spec.stride_w = c.read_option_usize()?;
+        spec.padding_h = c.read_option_usize()?;
+        spec.padding_w = c.read_option_usize()?;
+        if c.remaining() > 0 || header.variant == CONV_CONV3D {
+            spec.dilation_h = c.read_option_usize()?;
+            spec.dilation_w = c.read_option_usize()?;
+            spec.groups = c.read_option_usize()?;
+            spec.bias = c.read_option_u32()?.map(|v| v != 0);
+            spec.padding_mode = match c.read_u8()? {
+                CONV_PAD_EXPLICIT => ConvPadding::Explicit,
+                CONV_PAD_SAME     => ConvPadding::Same,
+                CONV_PAD_VALID    => ConvPadding::Valid,
+                m => return Err(format!("Unknown conv padding mode: 0x{:02X}", m)),
+            };
+        }
+        let kind = match header.variant {
+            CONV_CONV1D          => ConvKind::Conv1d,
+            CONV_CONV2D          => ConvKind::Conv2d,
+            CONV_CONVTRANSPOSE2D => ConvKind::ConvTranspose2d,
+            CONV_CONVTRANSPOSE1D => ConvKind::ConvTranspose1d,
+            CONV_CONV3D          => {
+                spec.kernel_d = c.read_usize()?;
+                spec.stride_d = c.read_option_usize()?;
+                spec.padding_d = c.read_option_usize()?;
+                spec.dilation_d = c.read_option_usize()?;
+                ConvKind::Conv3d
+            }
             _ => return Err(format!("Unknown conv variant: 0x{:02X}", header.variant)),
         };
-        insert_layer!(self, convs, id, layer);
-        Ok(())
+        self.add_conv(id, kind, &spec).map(|_| ())
     }
     fn init_activation(&mut self, header: &PacketHeader, payload: &[u8]) -> Result<(), String> {
         let mut c = PayloadCursor::new(payload);
         let id = c.read_u32()?;
-        let layer = match header.variant {
-            ACT_GELU        => WasmActivation::new_gelu(),
-            ACT_RELU        => WasmActivation::new_relu(),
-            ACT_SIGMOID     => WasmActivation::new_sigmoid(),
-            ACT_TANH        => WasmActivation::new_tanh(),
-            ACT_HARDSWISH   => WasmActivation::new_hard_swish(),
-            ACT_LEAKYRELU   => {
-                let slope = c.read_option_f64()?;
-                WasmActivation::new_leaky_relu(slope)
-            }
-            ACT_PRELU => {
-                let num_params = c.read_option_usize()?;
-                let alpha = c.read_option_f64()?;
-                WasmActivation::new_prelu(num_params, alpha)
-            }
-            ACT_SWIGLU => {
-                let d_in = c.read_usize()?;
-                let d_out = c.read_usize()?;
-                let bias = c.read_option_u32()?.map(|v| v != 0);
-                WasmActivation::new_swiglu(d_in, d_out, bias)
-            }
-            ACT_HARDSIGMOID => {
-                let alpha = c.read_option_f64()?;
-                let beta = c.read_option_f64()?;
-                WasmActivation::new_hard_sigmoid(alpha, beta)
-            }
-            ACT_SOFTPLUS => {
-                let beta = c.read_option_f64()?;
-                WasmActivation::new_softplus(beta)
-            }
-            ACT_MISH        => WasmActivation::new_mish(),
-            ACT_SOFTMAX     => {
-                let dim = c.read_usize()?;
-                WasmActivation::new_softmax(dim)
-            }
-            ACT_LOGSOFTMAX  => {
-                let dim = c.read_usize()?;
-                WasmActivation::new_log_softmax(dim)
-            }
-            ACT_GLU         => {
-                let dim = c.read_usize()?;
-                WasmActivation::new_glu(dim)
-            }
+        let spec = match header.variant {
+            ACT_GELU        => ActivationSpec::Gelu,
+            ACT_RELU        => ActivationSpec::Relu,
+            ACT_SIGMOID     => ActivationSpec::Sigmoid,
+            ACT_TANH        => ActivationSpec::Tanh,
+            ACT_HARDSWISH   => ActivationSpec::HardSwish,
+            ACT_LEAKYRELU   => ActivationSpec::LeakyRelu { negative_slope: c.read_option_f64()? },
+            ACT_PRELU => ActivationSpec::PRelu {
+                num_parameters: c.read_option_usize()?,
+                alpha: c.read_option_f64()?,
+            },
+            ACT_SWIGLU => ActivationSpec::SwiGlu {
+                d_input: c.read_usize()?,
+                d_output: c.read_usize()?,
+                bias: c.read_option_u32()?.map(|v| v != 0),
+            },
+            ACT_HARDSIGMOID => ActivationSpec::HardSigmoid { alpha: c.read_option_f64()?, beta: c.read_option_f64()? },
+            ACT_SOFTPLUS    => ActivationSpec::Softplus { beta: c.read_option_f64()? },
+            ACT_MISH        => ActivationSpec::Mish,
+            ACT_SOFTMAX     => ActivationSpec::Softmax { dim: c.read_usize()? },
+            ACT_LOGSOFTMAX  => ActivationSpec::LogSoftmax { dim: c.read_usize()? },
+            ACT_GLU         => ActivationSpec::Glu { dim: c.read_usize()? },
             _ => return Err(format!("Unknown activation variant: 0x{:02X}", header.variant)),
         };
-        insert_layer!(self, activations, id, layer);
-        Ok(())
+        self.add_activation(id, spec).map(|_| ())
     }
     fn init_embedding(&mut self, _header: &PacketHeader, payload: &[u8]) -> Result<(), String> {
         let mut c = PayloadCursor::new(payload);
         let id = c.read_u32()?;
-        let vocab = c.read_usize()?;
-        let d_model = c.read_usize()?;
-        let layer = WasmEmbedding::new(vocab, d_model);
-        insert_layer!(self, embeddings, id, layer);
-        Ok(())
+        let spec = EmbeddingSpec { vocab: c.read_usize()?, d_model: c.read_usize()? };
+        self.add_embedding(id, spec).map(|_| ())
     }
+    // Max pool punya tail opsional (packet lama berhenti sebelum tail):
+    //   1D: dilation?, ceil_mode(bool)   |   2D: dh?, dw?, ceil_mode(bool)
     fn init_pool(&mut self, header: &PacketHeader, payload: &[u8]) -> Result<(), String> {
         let mut c = PayloadCursor::new(payload);
         let id = c.read_u32()?;
-        let layer = match header.variant {
+        // 2D: sepasang Option -> Some hanya kalau keduanya ada (sama dengan konstruktor JS)
+        fn pair(a: Option<usize>, b: Option<usize>) -> Option<[usize; 2]> {
+            a.zip(b).map(|(a, b)| [a, b])
+        }
+        let spec = match header.variant {
             POOL_MAXPOOL1D => {
-                let k = c.read_usize()?;
-                let s = c.read_option_usize()?;
-                let p = c.read_option_usize()?;
-                WasmPool::new_max_pool1d(k, s, p)
-            }
-            POOL_AVGPOOL1D => {
-                let k = c.read_usize()?;
-                let s = c.read_option_usize()?;
-                let p = c.read_option_usize()?;
-                WasmPool::new_avg_pool1d(k, s, p)
+                let kernel = c.read_usize()?;
+                let stride = c.read_option_usize()?;
+                let padding = c.read_option_usize()?;
+                let (dilation, ceil_mode) = if c.remaining() > 0 {
+                    (c.read_option_usize()?, c.read_bool()?)
+                } else {
+                    (None, false)
+                };
+                PoolSpec::MaxPool1d { kernel, stride, padding, dilation, ceil_mode }
             }
+            POOL_AVGPOOL1D => PoolSpec::AvgPool1d {
+                kernel: c.read_usize()?,
+                stride: c.read_option_usize()?,
+                padding: c.read_option_usize()?,
+            },
             POOL_MAXPOOL2D => {
-                let k = c.read_usize()?;
-                let kw = c.read_usize()?;
-                let sh = c.read_option_usize()?;
-                let sw = c.read_option_usize()?;
-                let ph = c.read_option_usize()?;
-                let pw = c.read_option_usize()?;
-                WasmPool::new_max_pool2d(k, kw, sh, sw, ph, pw)
-            }
-            POOL_AVGPOOL2D => {
-                let k = c.read_usize()?;
-                let kw = c.read_usize()?;
-                let sh = c.read_option_usize()?;
-                let sw = c.read_option_usize()?;
-                let ph = c.read_option_usize()?;
-                let pw = c.read_option_usize()?;
-                WasmPool::new_avg_pool2d(k, kw, sh, sw, ph, pw)
-            }
-            POOL_ADAPTIVEAVGPOOL2D => {
-                let oh = c.read_usize()?;
-                let ow = c.read_usize()?;
-                WasmPool::new_adaptive_avg_pool2d(oh, ow)
+                let kernel = [c.read_usize()?, c.read_usize()?];
+                let stride = pair(c.read_option_usize()?, c.read_option_usize()?);
+                let padding = pair(c.read_option_usize()?, c.read_option_usize()?);
+                let (dilation, ceil_mode) = if c.remaining() > 0 {
+                    (pair(c.read_option_usize()?, c.read_option_usize()?), c.read_bool()?)
+                } else {
+                    (None, false)
+                };
+                PoolSpec::MaxPool2d { kernel, stride, padding, dilation, ceil_mode }
             }
+            POOL_AVGPOOL2D => PoolSpec::AvgPool2d {
+                kernel: [c.read_usize()?, c.read_usize()?],
+                stride: pair(c.read_option_usize()?, c.read_option_usize()?),
+                padding: pair(c.read_option_usize()?, c.read_option_usize()?),
+            },
+            POOL_ADAPTIVEAVGPOOL2D => PoolSpec::AdaptiveAvg2d { output: [c.read_usize()?, c.read_usize()?] },
+            POOL_ADAPTIVEMAXPOOL2D => PoolSpec::AdaptiveMax2d { output: [c.read_usize()?, c.read_usize()?] },
+            POOL_ADAPTIVEAVGPOOL1D => PoolSpec::AdaptiveAvg1d { output: c.read_usize()? },
+            POOL_GLOBALAVGPOOL => PoolSpec::GlobalAvg,
+            POOL_GLOBALMAXPOOL => PoolSpec::GlobalMax,
             _ => return Err(format!("Unknown pool variant: 0x{:02X}", header.variant)),
         };
-        self.pools.insert(id, layer);
-        Ok(())
+        self.add_pool(id, spec).map(|_| ())
     }
+    /// Payload NEAREST/BILINEAR: `id, by_size: bool` lalu `out_h, out_w: u32` (by_size)
+    /// atau `scale_h, scale_w: f64`. PIXEL_SHUFFLE: `id, factor: u32`.
+    fn init_resample(&mut self, header: &PacketHeader, payload: &[u8]) -> Result<(), String> {
+        let mut c = PayloadCursor::new(payload);
+        let id = c.read_u32()?;
+        let resample = match header.variant {
+            RESAMPLE_NEAREST | RESAMPLE_BILINEAR => {
+                let size = if c.read_bool()? {
+                    ResampleSize::Size(c.read_usize()?, c.read_usize()?)
+                } else {
+                    ResampleSize::Scale(c.read_f64()?, c.read_f64()?)
+                };
+                if header.variant == RESAMPLE_NEAREST { Resample::Nearest(size) } else { Resample::Bilinear(size) }
+            }
+            RESAMPLE_PIXEL_SHUFFLE => Resample::PixelShuffle(c.read_usize()?),
+            _ => return Err(format!("Unknown resample variant: 0x{:02X}", header.variant)),
+        };
+        self.add_resample(id, resample).map(|_| ())
+    }
+    /// Payload: `id, shift_size` (+ GROUPED: `n: u32, n byte arah`)
+    /// + tail opsional `mode: u8, fill: f64` (default constant 0).
     fn init_shift(&mut self, header: &PacketHeader, payload: &[u8]) -> Result<(), String> {
         let mut c = PayloadCursor::new(payload);
         let id = c.read_u32()?;
         let shift_size = c.read_usize()?;
-        let layer = match header.variant {
-            SHIFT_UP    => WasmShift::new_shift_up(shift_size),
-            SHIFT_DOWN  => WasmShift::new_shift_down(shift_size),
-            SHIFT_LEFT  => WasmShift::new_shift_left(shift_size),
-            SHIFT_RIGHT => WasmShift::new_shift_right(shift_size),
+        let directions = if header.variant == SHIFT_GROUPED {
+            let n = c.read_usize()?;
+            if n > c.remaining() {
+                return Err(format!("Grouped shift: {} directions but only {} bytes left", n, c.remaining()));
+            }
+            (0..n).map(|_| c.read_u8()).collect::<Result<Vec<u8>, String>>()?
+        } else {
+            vec![]
+        };
+        let (mode, fill) = if c.remaining() > 0 {
+            (Some(c.read_u8()?), Some(c.read_f64()?))
+        } else {
+            (None, None)
+        };
+        let fill = ShiftFill::from_code(mode.unwrap_or(SHIFT_MODE_CONSTANT), fill.unwrap_or(0.0) as f32)?;
+        let shift = match header.variant {
+            SHIFT_GROUPED => {
+                if directions.is_empty() {
+                    return Err("Grouped shift: need at least one direction".into());
+                }
+                let dirs = directions.iter().map(|&d| ShiftDirection::from_code(d)).collect::<Result<Vec<_>, _>>()?;
+                Shift::grouped(shift_size, dirs, fill)
+            }
+            SHIFT_UP..=SHIFT_DOWN_RIGHT => Shift::new(shift_size, ShiftDirection::from_code(header.variant)?).with_fill(fill),
             _ => return Err(format!("Unknown shift variant: 0x{:02X}", header.variant)),
         };
-        self.shifts.insert(id, layer);
-        Ok(())
+        self.add_shift(id, shift).map(|_| ())
     }
+    /// Payload: `id, in, out, kh, kw, ratio?, sh?, sw?, ph?, pw?`
+    /// + tail opsional `dw_kernel?, batch_norm: bool, relu: bool`.
     fn init_ghost(&mut self, _header: &PacketHeader, payload: &[u8]) -> Result<(), String> {
         let mut c = PayloadCursor::new(payload);
         let id = c.read_u32()?;
@@ -358,38 +598,377 @@ impl LayerRegistry {
         let sw = c.read_option_usize()?;
         let ph = c.read_option_usize()?;
         let pw = c.read_option_usize()?;
-        let layer = WasmGhostModule::new(in_ch, out_ch, kh, kw, ratio, sh, sw, ph, pw);
-        insert_layer!(self, ghosts, id, layer);
-        Ok(())
+        // tail opsional: dw_kernel?, batch_norm: bool, relu: bool
+        let (dw, bn, relu) = if c.remaining() > 0 {
+            (c.read_option_usize()?, Some(c.read_bool()?), Some(c.read_bool()?))
+        } else {
+            (None, None, None)
+        };
+        let mut config = GhostModuleConfig::new(in_ch, out_ch, [kh, kw]);
+        if let Some(r) = ratio {
+            config.ratio = r;
+        }
+        if let (Some(sh), Some(sw)) = (sh, sw) {
+            config.stride = [sh, sw];
+        }
+        if let (Some(ph), Some(pw)) = (ph, pw) {
+            config.padding = [ph, pw];
+        }
+        if let Some(k) = dw {
+            config.dw_kernel_size = k;
+        }
+        config.batch_norm = bn.unwrap_or(false);
+        config.relu = relu.unwrap_or(false);
+        self.add_ghost(id, &config).map(|_| ())
     }
     fn init_seblock(&mut self, _header: &PacketHeader, payload: &[u8]) -> Result<(), String> {
         let mut c = PayloadCursor::new(payload);
         let id = c.read_u32()?;
         let channels = c.read_usize()?;
         let reduction = c.read_option_usize()?;
-        let layer = WasmSeBlock::new(channels, reduction);
+        let mut config = SeBlockConfig::new(channels);
+        if let Some(r) = reduction {
+            config.reduction = r;
+        }
+        // tail opsional: activation u8 (ACT_*), gate u8 (ACT_*), squeeze u8 (SE_SQUEEZE_*)
+        if c.remaining() > 0 {
+            config.activation = param_free_activation(c.read_u8()?)?;
+            config.gate = param_free_activation(c.read_u8()?)?;
+            config.max_squeeze = match c.read_u8()? {
+                SE_SQUEEZE_AVG => false,
+                SE_SQUEEZE_MAX => true,
+                s => return Err(format!("Unknown SE squeeze: 0x{:02X}", s)),
+            };
+        }
+        self.add_seblock(id, &config).map(|_| ())
+    }
+    /// Payload: `id, channels, kernel?` (None -> kernel adaptif).
+    fn init_eca(&mut self, _header: &PacketHeader, payload: &[u8]) -> Result<(), String> {
+        let mut c = PayloadCursor::new(payload);
+        let id = c.read_u32()?;
+        let channels = c.read_usize()?;
+        let kernel = c.read_option_usize()?;
+        self.add_eca(id, &EcaBlockConfig::new(channels).with_kernel_size(kernel)).map(|_| ())
+    }
+    /// Payload: `id, channels, reduction?, kernel?`.
+    fn init_cbam(&mut self, _header: &PacketHeader, payload: &[u8]) -> Result<(), String> {
+        let mut c = PayloadCursor::new(payload);
+        let id = c.read_u32()?;
+        let channels = c.read_usize()?;
+        let reduction = c.read_option_usize()?;
+        let kernel = c.read_option_usize()?;
+        let mut config = CbamBlockConfig::new(channels);
+        if let Some(r) = reduction {
+            config.reduction = r;
+        }
+        if let Some(k) = kernel {
+            config.kernel_size = k;
+        }
+        self.add_cbam(id, &config).map(|_| ())
+    }
+    /// Payload: `id, kernel?`.
+    fn init_spatial_attn(&mut self, _header: &PacketHeader, payload: &[u8]) -> Result<(), String> {
+        let mut c = PayloadCursor::new(payload);
+        let id = c.read_u32()?;
+        let kernel = c.read_option_usize()?;
+        let mut config = SpatialAttentionConfig::new();
+        if let Some(k) = kernel {
+            config.kernel_size = k;
+        }
+        self.add_spatial_attention(id, &config).map(|_| ())
+    }
+}
+
+// ============================================================
+// IMPL #1b — RUST API (lihat crate::api): add_* bertipe, initLayer = decoder packet di atasnya.
+// ============================================================
+impl LayerRegistry {
+    pub fn add_linear(&mut self, id: LayerId, spec: LinearSpec) -> Result<LayerRef, String> {
+        self.check_id(LAYER_LINEAR, id)?;
+        let layer = WasmLinear::new(spec.d_in, spec.d_out, spec.bias);
+        insert_layer!(self, linears, id, layer);
+        Ok(self.describe_as(LAYER_LINEAR, id, VARIANT_NONE, spec.config_json(), Some(spec.init_fields())))
+    }
+
+    pub fn add_norm(&mut self, id: LayerId, spec: NormSpec) -> Result<LayerRef, String> {
+        self.check_id(LAYER_NORM, id)?;
+        let layer = match spec {
+            NormSpec::Batch { num_features, epsilon } => WasmNorm::new_batch_norm(num_features, epsilon),
+            NormSpec::Group { num_groups, num_channels, epsilon } => {
+                WasmNorm::new_group_norm(num_groups, num_channels, epsilon)
+            }
+            NormSpec::Instance { num_channels, epsilon } => WasmNorm::new_instance_norm(num_channels, epsilon),
+            NormSpec::Layer { size, epsilon } => WasmNorm::new_layer_norm(size, epsilon),
+            NormSpec::Rms { size, epsilon } => WasmNorm::new_rms_norm(size, epsilon),
+        };
+        insert_layer!(self, norms, id, layer);
+        let (variant, config) = spec.describe();
+        Ok(self.describe_as(LAYER_NORM, id, variant, config, Some(spec.init_fields())))
+    }
+
+    pub fn add_conv(&mut self, id: LayerId, kind: ConvKind, spec: &ConvSpec) -> Result<LayerRef, String> {
+        self.check_id(LAYER_CONV, id)?;
+        let config = match kind {
+            ConvKind::Conv1d          => spec.conv1d()?,
+            ConvKind::Conv2d          => spec.conv2d()?,
+            ConvKind::Conv3d          => spec.conv3d()?,
+            ConvKind::ConvTranspose1d => spec.conv_transpose1d()?,
+            ConvKind::ConvTranspose2d => spec.conv_transpose2d()?,
+        };
+        let config_json = config_json(&config);
+        let layer = WasmConv::from_config(config);
+        insert_layer!(self, convs, id, layer);
+        Ok(self.describe_as(LAYER_CONV, id, kind.variant(), config_json, Some(kind.init_fields(spec))))
+    }
+
+    pub fn add_activation(&mut self, id: LayerId, spec: ActivationSpec) -> Result<LayerRef, String> {
+        self.check_id(LAYER_ACTIVATION, id)?;
+        let layer = match spec {
+            ActivationSpec::Gelu      => WasmActivation::new_gelu(),
+            ActivationSpec::Relu      => WasmActivation::new_relu(),
+            ActivationSpec::Sigmoid   => WasmActivation::new_sigmoid(),
+            ActivationSpec::Tanh      => WasmActivation::new_tanh(),
+            ActivationSpec::HardSwish => WasmActivation::new_hard_swish(),
+            ActivationSpec::Mish      => WasmActivation::new_mish(),
+            ActivationSpec::LeakyRelu { negative_slope } => WasmActivation::new_leaky_relu(negative_slope),
+            ActivationSpec::PRelu { num_parameters, alpha } => WasmActivation::new_prelu(num_parameters, alpha),
+            ActivationSpec::SwiGlu { d_input, d_output, bias } => WasmActivation::new_swiglu(d_input, d_output, bias),
+            ActivationSpec::HardSigmoid { alpha, beta } => WasmActivation::new_hard_sigmoid(alpha, beta),
+            ActivationSpec::Softplus { beta } => WasmActivation::new_softplus(beta),
+            ActivationSpec::Softmax { dim } => WasmActivation::new_softmax(dim),
+            ActivationSpec::LogSoftmax { dim } => WasmActivation::new_log_softmax(dim),
+            ActivationSpec::Glu { dim } => WasmActivation::new_glu(dim),
+        };
+        insert_layer!(self, activations, id, layer);
+        let (variant, config) = spec.describe();
+        Ok(self.describe_as(LAYER_ACTIVATION, id, variant, config, Some(spec.init_fields())))
+    }
+
+    pub fn add_embedding(&mut self, id: LayerId, spec: EmbeddingSpec) -> Result<LayerRef, String> {
+        self.check_id(LAYER_EMBEDDING, id)?;
+        let layer = WasmEmbedding::new(spec.vocab, spec.d_model);
+        insert_layer!(self, embeddings, id, layer);
+        Ok(self.describe_as(LAYER_EMBEDDING, id, VARIANT_NONE, spec.config_json(), Some(spec.init_fields())))
+    }
+
+    pub fn add_pool(&mut self, id: LayerId, spec: PoolSpec) -> Result<LayerRef, String> {
+        self.check_id(LAYER_POOL, id)?;
+        let h = |a: Option<[usize; 2]>| a.map(|v| v[0]);
+        let w = |a: Option<[usize; 2]>| a.map(|v| v[1]);
+        let layer = match spec {
+            PoolSpec::MaxPool1d { kernel, stride, padding, dilation, ceil_mode } => {
+                WasmPool::new_max_pool1d(kernel, stride, padding, dilation, Some(ceil_mode))
+            }
+            PoolSpec::AvgPool1d { kernel, stride, padding } => WasmPool::new_avg_pool1d(kernel, stride, padding),
+            PoolSpec::MaxPool2d { kernel, stride, padding, dilation, ceil_mode } => WasmPool::new_max_pool2d(
+                kernel[0], kernel[1],
+                h(stride), w(stride),
+                h(padding), w(padding),
+                h(dilation), w(dilation),
+                Some(ceil_mode),
+            ),
+            PoolSpec::AvgPool2d { kernel, stride, padding } => {
+                WasmPool::new_avg_pool2d(kernel[0], kernel[1], h(stride), w(stride), h(padding), w(padding))
+            }
+            PoolSpec::AdaptiveAvg1d { output } => {
+                if output == 0 {
+                    return Err("adaptive avg pool: output size must be > 0".into());
+                }
+                WasmPool::new_adaptive_avg_pool1d(output)
+            }
+            PoolSpec::AdaptiveAvg2d { output } => {
+                if output[0] == 0 || output[1] == 0 {
+                    return Err("adaptive avg pool: output size must be > 0".into());
+                }
+                WasmPool::new_adaptive_avg_pool2d(output[0], output[1])
+            }
+            PoolSpec::AdaptiveMax2d { output } => {
+                if output[0] == 0 || output[1] == 0 {
+                    return Err("adaptive max pool: output size must be > 0".into());
+                }
+                WasmPool::new_adaptive_max_pool2d(output[0], output[1])
+            }
+            PoolSpec::GlobalAvg => WasmPool::new_global_avg_pool(),
+            PoolSpec::GlobalMax => WasmPool::new_global_max_pool(),
+        };
+        self.pools.insert(id, layer);
+        let (variant, config) = spec.describe();
+        Ok(self.describe_as(LAYER_POOL, id, variant, config, Some(spec.init_fields())))
+    }
+
+    pub fn add_resample(&mut self, id: LayerId, resample: Resample) -> Result<LayerRef, String> {
+        self.check_id(LAYER_RESAMPLE, id)?;
+        match resample {
+            Resample::Nearest(ResampleSize::Size(oh, ow)) | Resample::Bilinear(ResampleSize::Size(oh, ow))
+                if oh == 0 || ow == 0 =>
+            {
+                return Err("Resample: output size must be > 0".into());
+            }
+            Resample::Nearest(ResampleSize::Scale(sh, sw)) | Resample::Bilinear(ResampleSize::Scale(sh, sw))
+                if !(sh.is_finite() && sw.is_finite() && sh > 0.0 && sw > 0.0) =>
+            {
+                return Err(format!("Resample: scale must be finite and > 0, got ({}, {})", sh, sw));
+            }
+            Resample::PixelShuffle(0) => return Err("Resample: pixel-shuffle factor must be > 0".into()),
+            _ => {}
+        }
+        let (variant, config) = resample.describe();
+        let fields = resample.init_fields();
+        self.resamples.insert(id, WasmResample::from(resample));
+        Ok(self.describe_as(LAYER_RESAMPLE, id, variant, config, Some(fields)))
+    }
+
+    pub fn add_shift(&mut self, id: LayerId, shift: Shift) -> Result<LayerRef, String> {
+        self.check_id(LAYER_SHIFT, id)?;
+        let (variant, config) = shift.describe();
+        let fields = shift.init_fields();
+        self.shifts.insert(id, WasmShift::from(shift));
+        Ok(self.describe_as(LAYER_SHIFT, id, variant, config, Some(fields)))
+    }
+
+    pub fn add_ghost(&mut self, id: LayerId, config: &GhostModuleConfig) -> Result<LayerRef, String> {
+        self.check_id(LAYER_GHOST, id)?;
+        let layer = WasmGhostModule::from_config(config)?;
+        insert_layer!(self, ghosts, id, layer);
+        Ok(self.describe_as(LAYER_GHOST, id, VARIANT_NONE, config_json(config), Some(ghost_fields(config))))
+    }
+
+    pub fn add_seblock(&mut self, id: LayerId, config: &SeBlockConfig) -> Result<LayerRef, String> {
+        self.check_id(LAYER_SEBLOCK, id)?;
+        let layer = WasmSeBlock::from_config(config)?;
         insert_layer!(self, seblocks, id, layer);
-        Ok(())
+        Ok(self.describe_as(LAYER_SEBLOCK, id, VARIANT_NONE, config_json(config), seblock_fields(config)))
+    }
+
+    pub fn add_eca(&mut self, id: LayerId, config: &EcaBlockConfig) -> Result<LayerRef, String> {
+        self.check_id(LAYER_ECA, id)?;
+        let layer = WasmEcaBlock::from_config(config)?;
+        insert_layer!(self, ecas, id, layer);
+        Ok(self.describe_as(LAYER_ECA, id, VARIANT_NONE, config_json(config), Some(eca_fields(config))))
+    }
+
+    pub fn add_cbam(&mut self, id: LayerId, config: &CbamBlockConfig) -> Result<LayerRef, String> {
+        self.check_id(LAYER_CBAM, id)?;
+        let layer = WasmCbamBlock::from_config(config)?;
+        insert_layer!(self, cbams, id, layer);
+        Ok(self.describe_as(LAYER_CBAM, id, VARIANT_NONE, config_json(config), Some(cbam_fields(config))))
+    }
+
+    pub fn add_spatial_attention(&mut self, id: LayerId, config: &SpatialAttentionConfig) -> Result<LayerRef, String> {
+        self.check_id(LAYER_SPATIAL_ATTN, id)?;
+        let layer = WasmSpatialAttention::from_config(config)?;
+        insert_layer!(self, spatials, id, layer);
+        Ok(self.describe_as(LAYER_SPATIAL_ATTN, id, VARIANT_NONE, config_json(config), Some(spatial_attn_fields(config))))
+    }
+
+    pub fn add_binary(&mut self, id: LayerId, binary: Binary) -> Result<LayerRef, String> {
+        self.check_id(LAYER_BINARY, id)?;
+        let (variant, config) = binary.describe();
+        let fields = binary.init_fields();
+        self.binaries.insert(id, WasmBinary::from(binary)); // stateless: tanpa macro cache
+        Ok(self.describe_as(LAYER_BINARY, id, variant, config, Some(fields)))
+    }
+
+    pub fn add_tensor_op(&mut self, id: LayerId, op: TensorOp) -> Result<LayerRef, String> {
+        self.check_id(LAYER_TENSOR_OP, id)?;
+        let (variant, config) = op.describe();
+        let fields = op.init_fields();
+        self.tensor_ops.insert(id, WasmTensorOp::from(op)); // stateless: tanpa macro cache
+        Ok(self.describe_as(LAYER_TENSOR_OP, id, variant, config, Some(fields)))
+    }
+
+    /// Mode id global: id yang sudah dipakai tipe lain ditolak (tipe sama = ganti layer, seperti biasa).
+    fn check_id(&self, layer_type: u8, id: LayerId) -> Result<(), String> {
+        match self.id_types.as_ref().and_then(|ids| ids.get(&id)) {
+            Some(&t) if t != layer_type => Err(format!(
+                "layer id {} already used by type 0x{:02X} (global id namespace)",
+                id, t
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Mode id global: lookup bertipe ke id milik tipe lain ditolak (id_types = sumber kebenaran).
+    fn check_type(&self, layer_type: u8, id: LayerId) -> Result<(), String> {
+        match self.id_types.as_ref().and_then(|ids| ids.get(&id)) {
+            Some(&t) if t != layer_type => Err(format!(
+                "layer id {} is type 0x{:02X}, not 0x{:02X} (global id namespace)",
+                id, t, layer_type
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// `fields` = payload OP_INIT setelah id -> paket init sintetis (initLayer menimpanya dengan
+    /// paket asli); None = config tidak terwakili paket, layer tidak bisa di-snapshot.
+    fn describe_as(&mut self, layer_type: u8, id: LayerId, variant: u8, config: String, fields: Option<Vec<u8>>) -> LayerRef {
+        self.meta.insert((layer_type, id), (variant, config));
+        match fields {
+            Some(fields) => {
+                let header = PacketHeader {
+                    opcode: OP_INIT,
+                    layer_type,
+                    variant,
+                    flags: 0,
+                    payload_len: (4 + fields.len()) as u32,
+                    version: PROTOCOL_VERSION,
+                };
+                let mut packet = header.to_bytes();
+                packet.extend_from_slice(&id.to_le_bytes());
+                packet.extend_from_slice(&fields);
+                self.init_packets.insert((layer_type, id), packet);
+            }
+            None => {
+                self.init_packets.remove(&(layer_type, id));
+            }
+        }
+        self.drop_ties(layer_type, id); // layer diganti -> dims bisa berubah
+        self.frozen.retain(|&(t, i, _)| (t, i) != (layer_type, id));
+        if let Some(ids) = &mut self.id_types {
+            ids.insert(id, layer_type);
+        }
+        LayerRef { layer_type, id }
+    }
+
+    /// forwardLayer dengan burn::Tensor masuk/keluar.
+    pub fn forward(&self, layer: LayerRef, input: Tensor4) -> Result<Tensor4, String> {
+        Ok(self.forward_layer(layer.id, layer.layer_type, &WasmTensor { inner: input })?.inner)
+    }
+
+    /// forwardBinaryLayer dengan burn::Tensor (layer harus LAYER_BINARY).
+    pub fn forward_binary(&self, layer: LayerRef, a: Tensor4, b: Tensor4) -> Result<Tensor4, String> {
+        if layer.layer_type != LAYER_BINARY {
+            return Err(format!("forward_binary: layer type 0x{:02X} is not LAYER_BINARY", layer.layer_type));
+        }
+        let out = self.forward_binary_layer(layer.id, &WasmTensor { inner: a }, &WasmTensor { inner: b })?;
+        Ok(out.inner)
     }
 }
 
 // ============================================================
-// IMPL #2 — FLOAT-BRIDGE + WEIGHT LAYOUT (LINEAR/CONV/EMBEDDING/NORM)
+// IMPL #2 — FLOAT-BRIDGE + WEIGHT LAYOUT (LINEAR/CONV/EMBEDDING/NORM + blok custom)
 // Satu-satunya tempat ketiga method ini didefinisikan (TIDAK ada duplikat).
 // ============================================================
 #[wasm_bindgen]
 impl LayerRegistry {
     #[wasm_bindgen(js_name = getWeightsFlat)]
     pub fn get_weights_flat(&self, layer_id: LayerId, layer_type: u8) -> Result<Vec<f32>, String> {
+        self.check_type(layer_type, layer_id)?;
         match layer_type {
             LAYER_LINEAR    => self.linears.get(&layer_id).ok_or("Linear not found")?.get_weights_flat(),
             LAYER_CONV      => self.convs.get(&layer_id).ok_or("Conv not found")?.get_weights_flat(),
             LAYER_EMBEDDING => self.embeddings.get(&layer_id).ok_or("Embedding not found")?.get_weights_flat(),
             LAYER_NORM      => self.norms.get(&layer_id).ok_or("Norm not found")?.get_weights_flat(),
+            LAYER_GHOST     => self.ghosts.get(&layer_id).ok_or("Ghost not found")?.get_weights_flat(),
+            LAYER_SEBLOCK   => self.seblocks.get(&layer_id).ok_or("SEBlock not found")?.get_weights_flat(),
+            LAYER_ECA       => self.ecas.get(&layer_id).ok_or("ECA not found")?.get_weights_flat(),
+            LAYER_CBAM      => self.cbams.get(&layer_id).ok_or("CBAM not found")?.get_weights_flat(),
+            LAYER_SPATIAL_ATTN => self.spatials.get(&layer_id).ok_or("SpatialAttention not found")?.get_weights_flat(),
             _ => Err(format!("getWeightsFlat: not yet supported for type 0x{:02X}", layer_type)),
         }
     }
 
+    /// Layer yang terikat tieWeights ikut diperbarui (kedua arah).
     #[wasm_bindgen(js_name = setWeightsFlat)]
     pub fn set_weights_flat(
         &mut self,
@@ -397,25 +976,513 @@ impl LayerRegistry {
         layer_type: u8,
         data: &[f32],
     ) -> Result<(), String> {
+        self.write_weights_flat(layer_id, layer_type, data)?;
+        self.sync_ties(layer_type, layer_id)
+    }
+
+    /// Segmen yang dipakai bersama (tieWeights) diberi `"tied":{type,id,segment,transpose}`;
+    /// `len` tetap jumlah float di getWeightsFlat layer ini.
+    #[wasm_bindgen(js_name = weightLayout)]
+    pub fn weight_layout(&self, layer_id: LayerId, layer_type: u8) -> Result<String, String> {
+        let segs = self.weight_segs(layer_id, layer_type)?;
+        let tie = if layer_type == LAYER_LINEAR { self.ties.get(&layer_id) } else { None };
+        let Some(tie) = tie else {
+            return Ok(crate::layers::layout::segs_json(&segs));
+        };
+        let entries: Vec<String> = segs
+            .iter()
+            .map(|&(name, len)| {
+                let seg = JsonObj::new().str("name", name).num("len", len);
+                if name != "weight" {
+                    return seg.finish();
+                }
+                seg.raw("tied", &tie.to_json()).finish()
+            })
+            .collect();
+        Ok(format!("[{}]", entries.join(",")))
+    }
+
+    /// Shape inference per layer (tanpa forward). Tipe yang belum punya rumus -> Err.
+    #[wasm_bindgen(js_name = outputShape)]
+    pub fn output_shape(&self, layer_id: LayerId, layer_type: u8, shape: &[usize]) -> Result<Vec<usize>, String> {
+        self.check_type(layer_type, layer_id)?;
+        match layer_type {
+            LAYER_LINEAR     => self.linears.get(&layer_id).ok_or("Linear not found")?.output_shape(shape),
+            LAYER_CONV       => self.convs.get(&layer_id).ok_or("Conv not found")?.output_shape(shape),
+            LAYER_ACTIVATION => self.activations.get(&layer_id).ok_or("Activation not found")?.output_shape(shape),
+            LAYER_EMBEDDING  => self.embeddings.get(&layer_id).ok_or("Embedding not found")?.output_shape(shape),
+            LAYER_GHOST      => self.ghosts.get(&layer_id).ok_or("Ghost not found")?.output_shape(shape),
+            LAYER_POOL       => self.pools.get(&layer_id).ok_or("Pool not found")?.output_shape(shape),
+            LAYER_RESAMPLE   => self.resamples.get(&layer_id).ok_or("Resample not found")?.output_shape(shape),
+            // shape-preserving
+            LAYER_NORM | LAYER_SHIFT | LAYER_SEBLOCK | LAYER_ECA | LAYER_CBAM | LAYER_SPATIAL_ATTN => {
+                if !self.layer_exists(layer_type, layer_id) {
+                    return Err(format!("Layer type 0x{:02X} id {} not found", layer_type, layer_id));
+                }
+                Ok(crate::layers::pad_shape4(shape).to_vec())
+            }
+            LAYER_TENSOR_OP  => self.tensor_ops.get(&layer_id).ok_or("TensorOp not found")?.output_shape(shape),
+            _ => Err(format!("outputShape: not yet supported for type 0x{:02X}", layer_type)),
+        }
+    }
+}
+
+impl LayerRegistry {
+    /// setWeightsFlat tanpa sinkronisasi tie.
+    fn write_weights_flat(&mut self, layer_id: LayerId, layer_type: u8, data: &[f32]) -> Result<(), String> {
+        self.check_type(layer_type, layer_id)?;
         match layer_type {
             LAYER_LINEAR    => self.linears.get_mut(&layer_id).ok_or("Linear not found")?.set_weights_flat(data),
             LAYER_CONV      => self.convs.get_mut(&layer_id).ok_or("Conv not found")?.set_weights_flat(data),
             LAYER_EMBEDDING => self.embeddings.get_mut(&layer_id).ok_or("Embedding not found")?.set_weights_flat(data),
             LAYER_NORM      => self.norms.get_mut(&layer_id).ok_or("Norm not found")?.set_weights_flat(data),
+            LAYER_GHOST     => self.ghosts.get_mut(&layer_id).ok_or("Ghost not found")?.set_weights_flat(data),
+            LAYER_SEBLOCK   => self.seblocks.get_mut(&layer_id).ok_or("SEBlock not found")?.set_weights_flat(data),
+            LAYER_ECA       => self.ecas.get_mut(&layer_id).ok_or("ECA not found")?.set_weights_flat(data),
+            LAYER_CBAM      => self.cbams.get_mut(&layer_id).ok_or("CBAM not found")?.set_weights_flat(data),
+            LAYER_SPATIAL_ATTN => self.spatials.get_mut(&layer_id).ok_or("SpatialAttention not found")?.set_weights_flat(data),
             _ => Err(format!("setWeightsFlat: not yet supported for type 0x{:02X}", layer_type)),
         }
     }
 
-    #[wasm_bindgen(js_name = weightLayout)]
-    pub fn weight_layout(&self, layer_id: LayerId, layer_type: u8) -> Result<String, String> {
+    /// Segmen (nama, len) per layer, urutan sama dengan getWeightsFlat.
+    pub fn weight_segs(&self, layer_id: LayerId, layer_type: u8) -> Result<Vec<(&'static str, usize)>, String> {
+        self.check_type(layer_type, layer_id)?;
         match layer_type {
-            LAYER_LINEAR    => Ok(self.linears.get(&layer_id).ok_or("Linear not found")?.weight_layout()),
-            LAYER_CONV      => Ok(self.convs.get(&layer_id).ok_or("Conv not found")?.weight_layout()),
-            LAYER_EMBEDDING => Ok(self.embeddings.get(&layer_id).ok_or("Embedding not found")?.weight_layout()),
-            LAYER_NORM      => Ok(self.norms.get(&layer_id).ok_or("Norm not found")?.weight_layout()),
+            LAYER_LINEAR    => Ok(self.linears.get(&layer_id).ok_or("Linear not found")?.weight_segs()),
+            LAYER_CONV      => Ok(self.convs.get(&layer_id).ok_or("Conv not found")?.weight_segs()),
+            LAYER_EMBEDDING => Ok(self.embeddings.get(&layer_id).ok_or("Embedding not found")?.weight_segs()),
+            LAYER_NORM      => Ok(self.norms.get(&layer_id).ok_or("Norm not found")?.weight_segs()),
+            LAYER_GHOST     => Ok(self.ghosts.get(&layer_id).ok_or("Ghost not found")?.weight_segs()),
+            LAYER_SEBLOCK   => Ok(self.seblocks.get(&layer_id).ok_or("SEBlock not found")?.weight_segs()),
+            LAYER_ECA       => Ok(self.ecas.get(&layer_id).ok_or("ECA not found")?.weight_segs()),
+            LAYER_CBAM      => Ok(self.cbams.get(&layer_id).ok_or("CBAM not found")?.weight_segs()),
+            LAYER_SPATIAL_ATTN => Ok(self.spatials.get(&layer_id).ok_or("SpatialAttention not found")?.weight_segs()),
             _ => Err(format!("weightLayout: not yet supported for type 0x{:02X}", layer_type)),
         }
     }
+
+    /// weight_segs + dims burn tiap segmen (linear [d_in, d_out], conv [out, in/groups, k..]).
+    pub fn weight_seg_dims(&self, layer_id: LayerId, layer_type: u8) -> Result<Vec<(&'static str, Vec<usize>)>, String> {
+        let weight = match layer_type {
+            LAYER_LINEAR       => self.linears.get(&layer_id).ok_or("Linear not found")?.weight_dims(),
+            LAYER_CONV         => self.convs.get(&layer_id).ok_or("Conv not found")?.weight_dims(),
+            LAYER_EMBEDDING    => self.embeddings.get(&layer_id).ok_or("Embedding not found")?.weight_dims(),
+            LAYER_GHOST        => return Ok(self.ghosts.get(&layer_id).ok_or("Ghost not found")?.weight_seg_dims()),
+            LAYER_SEBLOCK      => return Ok(self.seblocks.get(&layer_id).ok_or("SEBlock not found")?.weight_seg_dims()),
+            LAYER_ECA          => return Ok(self.ecas.get(&layer_id).ok_or("ECA not found")?.weight_seg_dims()),
+            LAYER_CBAM         => return Ok(self.cbams.get(&layer_id).ok_or("CBAM not found")?.weight_seg_dims()),
+            LAYER_SPATIAL_ATTN => return Ok(self.spatials.get(&layer_id).ok_or("SpatialAttention not found")?.weight_seg_dims()),
+            _ => Vec::new(),
+        };
+        Ok(self
+            .weight_segs(layer_id, layer_type)?
+            .into_iter()
+            .map(|(name, len)| (name, if name == "weight" { weight.clone() } else { vec![len] }))
+            .collect())
+    }
+}
+
+// ============================================================
+// INIT SCHEME — bobot deterministik dari (skema, seed), lihat layers::init.
+// Segmen "*weight" diisi skema, "*bias" dinolkan; linear/conv/embedding + conv/linear di dalam blok custom.
+// ============================================================
+#[wasm_bindgen]
+impl LayerRegistry {
+    /// scheme = INIT_*, param sesuai skema (lihat protocol.rs). Sama dengan trailer FLAG_INIT_SCHEME.
+    #[wasm_bindgen(js_name = initWeights)]
+    pub fn init_weights(&mut self, layer_id: LayerId, layer_type: u8, scheme: u8, param: f32, seed: u32) -> Result<(), String> {
+        self.reinit(LayerRef { layer_type, id: layer_id }, InitScheme::from_code(scheme, param)?, seed)
+    }
+}
+
+/// Tipe yang punya segmen bobot conv/linear/embedding untuk diisi skema init.
+fn supports_init_scheme(layer_type: u8) -> bool {
+    matches!(
+        layer_type,
+        LAYER_LINEAR | LAYER_CONV | LAYER_EMBEDDING | LAYER_GHOST | LAYER_SEBLOCK | LAYER_ECA | LAYER_CBAM | LAYER_SPATIAL_ATTN
+    )
+}
+
+impl LayerRegistry {
+    /// Isi ulang bobot layer dengan `scheme`, Rng(seed) berurutan per segmen weight.
+    pub fn reinit(&mut self, layer: LayerRef, scheme: InitScheme, seed: u32) -> Result<(), String> {
+        if scheme == InitScheme::Default {
+            return Ok(());
+        }
+        let LayerRef { layer_type, id } = layer;
+        if !supports_init_scheme(layer_type) {
+            return Err(format!("initWeights: not supported for {}", layer_type_name(layer_type)));
+        }
+        scheme.validate()?;
+        let mut rng = crate::es::rng::Rng::new(seed);
+        let mut flat = self.get_weights_flat(id, layer_type)?;
+        let mut offset = 0;
+        for (name, dims) in self.weight_seg_dims(id, layer_type)? {
+            let len = dims.iter().product::<usize>();
+            let seg = &mut flat[offset..offset + len];
+            if name.ends_with("weight") {
+                // 2-D selain embedding = linear burn [d_in, d_out]; sisanya konvensi PyTorch [out, in, k..]
+                let shape = if dims.len() == 2 && layer_type != LAYER_EMBEDDING {
+                    WeightShape::linear(dims[0], dims[1])
+                } else {
+                    WeightShape::from_dims(&dims)
+                };
+                if let Some(v) = scheme.fill(&mut rng, &shape) {
+                    seg.copy_from_slice(&v);
+                }
+            } else if name.ends_with("bias") {
+                seg.fill(0.0);
+            }
+            offset += len;
+        }
+        self.set_weights_flat(id, layer_type, &flat)
+    }
+}
+
+// ============================================================
+// WEIGHT TYING — weight linear = segmen bobot layer lain (tied embedding, cabang Siamese).
+// Tiap layer tetap punya modul burn sendiri; registry menyalin segmen bersama ke dua arah
+// setiap kali salah satu sisi ditulis (setWeightsFlat/loadLayerState). Tanpa rantai:
+// sumber tidak boleh linear yang juga terikat.
+// ============================================================
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeightTie {
+    pub src_type: u8,
+    pub src_id: LayerId,
+    pub segment: &'static str,
+    /// Posisi segmen di getWeightsFlat sumber.
+    pub offset: usize,
+    pub len: usize,
+    /// true: segmen dibaca [d_out, d_in] lalu ditranspos (embedding [vocab, d_model] -> linear d_model -> vocab).
+    pub transpose: bool,
+}
+
+impl WeightTie {
+    fn to_json(&self) -> String {
+        JsonObj::new()
+            .num("type", self.src_type)
+            .num("id", self.src_id)
+            .str("segment", self.segment)
+            .num("transpose", self.transpose)
+            .finish()
+    }
+}
+
+#[wasm_bindgen]
+impl LayerRegistry {
+    /// Weight linear `linear_id` dipakai bersama dengan segmen `segment` (nama dari weightLayout)
+    /// milik layer (src_type, src_id). transpose=false: segmen = weight [d_in, d_out];
+    /// transpose=true: segmen = [d_out, d_in]; dims dicek, bukan cuma jumlah float. Bias linear tetap milik sendiri.
+    /// Weight linear langsung diisi dari sumber.
+    #[wasm_bindgen(js_name = tieWeights)]
+    pub fn tie_weights(
+        &mut self,
+        linear_id: LayerId,
+        src_type: u8,
+        src_id: LayerId,
+        segment: &str,
+        transpose: bool,
+    ) -> Result<(), String> {
+        if !self.linears.contains_key(&linear_id) {
+            return Err("tieWeights: Linear not found".into());
+        }
+        if src_type == LAYER_LINEAR && (src_id == linear_id || self.ties.contains_key(&src_id)) {
+            return Err(format!("tieWeights: source linear {} is the target or itself tied", src_id));
+        }
+        if self.ties.values().any(|t| t.src_type == LAYER_LINEAR && t.src_id == linear_id) {
+            return Err(format!("tieWeights: linear {} is already a tie source", linear_id));
+        }
+        let tie = self
+            .tie_segment(linear_id, src_type, src_id, segment, transpose)
+            .map_err(|e| format!("tieWeights: {}", e))?;
+        self.ties.insert(linear_id, tie);
+        self.pull_tie(linear_id)
+    }
+
+    /// Lepas tie; weight linear tetap salinan terakhir. false kalau tidak terikat.
+    #[wasm_bindgen(js_name = untieWeights)]
+    pub fn untie_weights(&mut self, linear_id: LayerId) -> bool {
+        self.ties.remove(&linear_id).is_some()
+    }
+
+    /// {"type","id","segment","transpose"} sumber tie linear, undefined kalau tidak terikat.
+    #[wasm_bindgen(js_name = weightTie)]
+    pub fn weight_tie_json(&self, linear_id: LayerId) -> Option<String> {
+        self.ties.get(&linear_id).map(WeightTie::to_json)
+    }
+}
+
+impl LayerRegistry {
+    pub fn weight_tie(&self, linear_id: LayerId) -> Option<&WeightTie> {
+        self.ties.get(&linear_id)
+    }
+
+    /// Semua tie, urut id linear.
+    pub fn weight_ties(&self) -> Vec<(LayerId, &WeightTie)> {
+        let mut ties: Vec<(LayerId, &WeightTie)> = self.ties.iter().map(|(&id, t)| (id, t)).collect();
+        ties.sort_unstable_by_key(|&(id, _)| id);
+        ties
+    }
+
+    /// Lokasi segmen sumber; dims 2-D harus sama dengan weight linear (ditranspos kalau `transpose`).
+    fn tie_segment(
+        &self,
+        linear_id: LayerId,
+        src_type: u8,
+        src_id: LayerId,
+        segment: &str,
+        transpose: bool,
+    ) -> Result<WeightTie, String> {
+        let dims = self.linears.get(&linear_id).ok_or("Linear not found")?.weight_dims();
+        let segs = self.weight_seg_dims(src_id, src_type)?;
+        let idx = segs
+            .iter()
+            .position(|(name, _)| *name == segment)
+            .ok_or_else(|| format!("{} id {} has no segment {:?}", layer_type_name(src_type), src_id, segment))?;
+        let (name, src_dims) = segs[idx].clone();
+        let want = if transpose { vec![dims[1], dims[0]] } else { dims.clone() };
+        if src_dims != want {
+            return Err(format!(
+                "segment {:?} has dims {:?}, linear {} weight {:?} needs {:?} (transpose={})",
+                segment, src_dims, linear_id, dims, want, transpose
+            ));
+        }
+        let len = dims[0] * dims[1];
+        let offset = segs[..idx].iter().map(|(_, d)| d.iter().product::<usize>()).sum();
+        Ok(WeightTie { src_type, src_id, segment: name, offset, len, transpose })
+    }
+
+    /// Tie dicek ulang sebelum tiap salinan: sumber atau linear bisa berganti shape
+    /// (loadLayerState). Tidak cocok lagi -> tie dilepas, Err.
+    fn checked_tie(&mut self, linear_id: LayerId) -> Result<WeightTie, String> {
+        let t = self.ties[&linear_id].clone();
+        match self.tie_segment(linear_id, t.src_type, t.src_id, t.segment, t.transpose) {
+            Ok(tie) => {
+                self.ties.insert(linear_id, tie.clone());
+                Ok(tie)
+            }
+            Err(e) => {
+                self.ties.remove(&linear_id);
+                Err(format!("weight tie of linear {} dropped: {}", linear_id, e))
+            }
+        }
+    }
+
+    /// Segmen sumber -> weight linear (bias dipertahankan).
+    fn pull_tie(&mut self, linear_id: LayerId) -> Result<(), String> {
+        let tie = self.checked_tie(linear_id)?;
+        let src = self.get_weights_flat(tie.src_id, tie.src_type)?;
+        let seg = &src[tie.offset..tie.offset + tie.len];
+        let linear = self.linears.get_mut(&linear_id).ok_or("Linear not found")?;
+        let (d_in, d_out) = (linear.weight_dims()[0], linear.weight_dims()[1]);
+        let mut flat = linear.get_weights_flat()?;
+        for i in 0..d_in {
+            for j in 0..d_out {
+                flat[i * d_out + j] = if tie.transpose { seg[j * d_in + i] } else { seg[i * d_out + j] };
+            }
+        }
+        linear.set_weights_flat(&flat)
+    }
+
+    /// Weight linear -> segmen sumber (kebalikan pull_tie).
+    fn push_tie(&mut self, linear_id: LayerId) -> Result<(), String> {
+        let tie = self.checked_tie(linear_id)?;
+        let linear = self.linears.get(&linear_id).ok_or("Linear not found")?;
+        let (d_in, d_out) = (linear.weight_dims()[0], linear.weight_dims()[1]);
+        let w = linear.get_weights_flat()?;
+        let mut src = self.get_weights_flat(tie.src_id, tie.src_type)?;
+        let seg = &mut src[tie.offset..tie.offset + tie.len];
+        for i in 0..d_in {
+            for j in 0..d_out {
+                let k = if tie.transpose { j * d_in + i } else { i * d_out + j };
+                seg[k] = w[i * d_out + j];
+            }
+        }
+        self.write_weights_flat(tie.src_id, tie.src_type, &src)
+    }
+
+    /// Setelah bobot (tipe, id) berubah: linear terikat -> tulis balik ke sumber,
+    /// lalu semua linear yang memakai sumber itu disalin ulang.
+    fn sync_ties(&mut self, layer_type: u8, layer_id: LayerId) -> Result<(), String> {
+        let tie = if layer_type == LAYER_LINEAR { self.ties.get(&layer_id).cloned() } else { None };
+        let (src_type, src_id) = match tie {
+            Some(t) => {
+                self.push_tie(layer_id)?;
+                (t.src_type, t.src_id)
+            }
+            None => (layer_type, layer_id),
+        };
+        let dsts: Vec<LayerId> = self
+            .ties
+            .iter()
+            .filter(|(&d, t)| t.src_type == src_type && t.src_id == src_id && !(layer_type == LAYER_LINEAR && d == layer_id))
+            .map(|(&d, _)| d)
+            .collect();
+        // tie yang tidak cocok lagi dilepas tanpa menghentikan sinkronisasi tie lain
+        let mut res = Ok(());
+        for d in dsts {
+            res = res.and(self.pull_tie(d));
+        }
+        res
+    }
+
+    /// Layer dihapus/diganti: lepas tie yang melibatkannya.
+    fn drop_ties(&mut self, layer_type: u8, layer_id: LayerId) {
+        if layer_type == LAYER_LINEAR {
+            self.ties.remove(&layer_id);
+        }
+        self.ties.retain(|_, t| !(t.src_type == layer_type && t.src_id == layer_id));
+    }
+}
+
+// ============================================================
+// TRAINABLE MASK — freeze/unfreeze per (layer, segmen). trainableLayout/setTrainableFlat
+// menggabungkan semua segmen yang tidak dibekukan (urut (tipe, id), lalu urutan segmen)
+// jadi satu vektor ES. Weight linear yang terikat (tieWeights) tidak ikut: sudah diwakili
+// segmen sumbernya.
+// ============================================================
+#[wasm_bindgen]
+impl LayerRegistry {
+    /// segment "" = semua segmen layer. Nama segmen dari weightLayout.
+    #[wasm_bindgen(js_name = setTrainable)]
+    pub fn set_trainable(&mut self, layer_type: u8, layer_id: LayerId, segment: &str, trainable: bool) -> Result<(), String> {
+        let segs = self.weight_segs(layer_id, layer_type).map_err(|e| format!("setTrainable: {}", e))?;
+        let mut found = false;
+        for (name, _) in segs.into_iter().filter(|&(name, _)| segment.is_empty() || name == segment) {
+            found = true;
+            if trainable {
+                self.frozen.remove(&(layer_type, layer_id, name));
+            } else {
+                self.frozen.insert((layer_type, layer_id, name));
+            }
+        }
+        if !found {
+            return Err(format!("setTrainable: {} id {} has no segment {:?}", layer_type_name(layer_type), layer_id, segment));
+        }
+        Ok(())
+    }
+
+    /// Bekukan / lepas semua segmen semua layer yang ada saat ini.
+    #[wasm_bindgen(js_name = setAllTrainable)]
+    pub fn set_all_trainable(&mut self, trainable: bool) {
+        self.frozen.clear();
+        if !trainable {
+            for (t, id) in self.layer_keys() {
+                for (name, _) in self.weight_segs(id, t).unwrap_or_default() {
+                    self.frozen.insert((t, id, name));
+                }
+            }
+        }
+    }
+
+    #[wasm_bindgen(js_name = isTrainable)]
+    pub fn is_trainable(&self, layer_type: u8, layer_id: LayerId, segment: &str) -> bool {
+        !self.frozen.contains(&(layer_type, layer_id, segment))
+    }
+
+    /// [{"type","id","name","len","offset"}] — offset = posisi di vektor trainable.
+    #[wasm_bindgen(js_name = trainableLayout)]
+    pub fn trainable_layout(&self) -> String {
+        let mut pos = 0;
+        let entries: Vec<String> = self
+            .trainable_segs()
+            .iter()
+            .map(|&(t, id, name, _, len)| {
+                let e = JsonObj::new()
+                    .num("type", t)
+                    .num("id", id)
+                    .str("name", name)
+                    .num("len", len)
+                    .num("offset", pos)
+                    .finish();
+                pos += len;
+                e
+            })
+            .collect();
+        format!("[{}]", entries.join(","))
+    }
+
+    /// Panjang vektor trainable (dimensi ES).
+    #[wasm_bindgen(js_name = trainableLen)]
+    pub fn trainable_len(&self) -> usize {
+        self.trainable_segs().iter().map(|s| s.4).sum()
+    }
+
+    /// Nilai semua segmen trainable saat ini (titik awal ES), urut trainableLayout.
+    #[wasm_bindgen(js_name = getTrainableFlat)]
+    pub fn get_trainable_flat(&self) -> Result<Vec<f32>, String> {
+        let mut out = Vec::new();
+        for (t, id, segs) in self.trainable_by_layer() {
+            let flat = self.get_weights_flat(id, t)?;
+            for (offset, len) in segs {
+                out.extend_from_slice(&flat[offset..offset + len]);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Sebar satu vektor (urut trainableLayout) ke semua segmen trainable; segmen beku tidak disentuh.
+    #[wasm_bindgen(js_name = setTrainableFlat)]
+    pub fn set_trainable_flat(&mut self, data: &[f32]) -> Result<(), String> {
+        let need = self.trainable_len();
+        if data.len() != need {
+            return Err(format!("setTrainableFlat: expected {} floats, got {}", need, data.len()));
+        }
+        let mut pos = 0;
+        for (t, id, segs) in self.trainable_by_layer() {
+            let mut flat = self.get_weights_flat(id, t)?;
+            for (offset, len) in segs {
+                flat[offset..offset + len].copy_from_slice(&data[pos..pos + len]);
+                pos += len;
+            }
+            self.set_weights_flat(id, t, &flat)?;
+        }
+        Ok(())
+    }
+}
+
+impl LayerRegistry {
+    /// Latih hanya layer ini (mis. head di atas backbone beku): semua lain dibekukan.
+    pub fn train_only(&mut self, layers: &[LayerRef]) -> Result<(), String> {
+        self.set_all_trainable(false);
+        layers.iter().try_for_each(|l| self.set_trainable(l.layer_type, l.id, "", true))
+    }
+
+    /// Segmen beku (tipe, id, nama), urut — mis. untuk disimpan ke .bmodel.
+    pub fn frozen_segments(&self) -> Vec<(u8, LayerId, &'static str)> {
+        let mut out: Vec<_> = self.frozen.iter().copied().collect();
+        out.sort_unstable();
+        out
+    }
+
+    /// (tipe, id, nama, offset di flat layer, len) untuk segmen trainable, urut vektor ES.
+    fn trainable_segs(&self) -> Vec<(u8, LayerId, &'static str, usize, usize)> {
+        let mut out = Vec::new();
+        for (t, id) in self.layer_keys() {
+            let mut offset = 0;
+            for (name, len) in self.weight_segs(id, t).unwrap_or_default() {
+                let tied = t == LAYER_LINEAR && name == "weight" && self.ties.contains_key(&id);
+                if !tied && !self.frozen.contains(&(t, id, name)) {
+                    out.push((t, id, name, offset, len));
+                }
+                offset += len;
+            }
+        }
+        out
+    }
+
+    /// trainable_segs dikelompokkan per layer: (tipe, id, [(offset, len)]).
+    fn trainable_by_layer(&self) -> Vec<LayerRanges> {
+        let mut out: Vec<LayerRanges> = Vec::new();
+        for (t, id, _, offset, len) in self.trainable_segs() {
+            match out.last_mut() {
+                Some(last) if (last.0, last.1) == (t, id) => last.2.push((offset, len)),
+                _ => out.push((t, id, vec![(offset, len)])),
+            }
+        }
+        out
+    }
 }
 
 // ============================================================
@@ -430,156 +1497,179 @@ impl LayerRegistry {
         a: &WasmTensor,
         b: &WasmTensor,
     ) -> Result<WasmTensor, String> {
+        self.check_type(LAYER_BINARY, layer_id)?;
         self.binaries
             .get(&layer_id)
             .ok_or_else(|| format!("Binary layer {} not found", layer_id))?
             .forward_binary(a, b)
     }
 
+    #[wasm_bindgen(js_name = binaryOutputShape)]
+    pub fn binary_output_shape(&self, layer_id: LayerId, a: &[usize], b: &[usize]) -> Result<Vec<usize>, String> {
+        self.binaries
+            .get(&layer_id)
+            .ok_or_else(|| format!("Binary layer {} not found", layer_id))?
+            .output_shape(a, b)
+    }
+
+    /// Payload v1: `id, dim` | v2: `id` (+ `dim` untuk CONCAT). WHERE: + `fill: f64` opsional (default 0).
     fn init_binary(&mut self, header: &PacketHeader, payload: &[u8]) -> Result<(), String> {
         let mut c = PayloadCursor::new(payload);
         let id = c.read_u32()?;
-        let dim = c.read_usize()?; // hanya bermakna untuk CONCAT
-        let layer = match header.variant {
-            BINARY_ADD    => WasmBinary::new_add(),
-            BINARY_SUB    => WasmBinary::new_sub(),
-            BINARY_MUL    => WasmBinary::new_mul(),
-            BINARY_MATMUL => WasmBinary::new_matmul(),
-            BINARY_CONCAT => WasmBinary::new_concat(dim),
+        // v1: `dim` selalu ada (hanya bermakna untuk CONCAT); v2: hanya CONCAT yang membawa `dim`.
+        let dim = if header.version < 2 || header.variant == BINARY_CONCAT { c.read_usize()? } else { 0 };
+        let op = match header.variant {
+            BINARY_ADD    => BinaryOp::Add,
+            BINARY_SUB    => BinaryOp::Sub,
+            BINARY_MUL    => BinaryOp::Mul,
+            BINARY_MATMUL => BinaryOp::Matmul,
+            BINARY_CONCAT => BinaryOp::Concat,
+            BINARY_DIV    => BinaryOp::Div,
+            BINARY_MAX    => BinaryOp::Max,
+            BINARY_MIN    => BinaryOp::Min,
+            BINARY_POW    => BinaryOp::Pow,
+            BINARY_WHERE  => {
+                let fill = if c.remaining() > 0 { c.read_f64()? } else { 0.0 };
+                return self.add_binary(id, Binary::new_where(fill as f32)).map(|_| ());
+            }
             _ => return Err(format!("Unknown binary variant: 0x{:02X}", header.variant)),
         };
-        self.binaries.insert(id, layer); // stateless: tanpa macro cache
-        Ok(())
+        self.add_binary(id, Binary::new(op, dim)).map(|_| ())
     }
 }
 
 // ============================================================
-// GRAPH EXECUTOR — plan 9 byte/step (unary + binary)
+// IMPL #4 — TENSOR OP (stateless 1-input)
 // ============================================================
-const MAX_SLOTS: u32 = 64;
-
-#[derive(Clone, Copy)]
-struct RunStep {
-    arity: u8,        // 1 = unary, 2 = binary
-    layer_type: u8,
-    layer_id: u32,
-    in_slot: u8,
-    in_slot2: u8,     // hanya untuk arity 2
-    out_slot: u8,
-}
-
-fn read_run_step(c: &mut PayloadCursor) -> Result<RunStep, String> {
-    Ok(RunStep {
-        arity: c.read_u8()?,
-        layer_type: c.read_u8()?,
-        layer_id: c.read_u32()?,
-        in_slot: c.read_u8()?,
-        in_slot2: c.read_u8()?,
-        out_slot: c.read_u8()?,
-    })
+impl LayerRegistry {
+    /// Payload: `id` + field per varian:
+    /// RESHAPE `d0..d3: i32` | PERMUTE `a0..a3: u32` | TRANSPOSE `d0, d1` | FLATTEN `start, end`
+    /// SLICE `dim, start, end` | PAD `l, r, t, b, mode: u8, value: f64`
+    /// MEAN/SUM/MAX `dim` | SCALE_BIAS `scale: f64, bias: f64`
+    fn init_tensor_op(&mut self, header: &PacketHeader, payload: &[u8]) -> Result<(), String> {
+        let mut c = PayloadCursor::new(payload);
+        let id = c.read_u32()?;
+        let op = match header.variant {
+            TENSOR_RESHAPE => TensorOp::Reshape([c.read_i32()?, c.read_i32()?, c.read_i32()?, c.read_i32()?]),
+            TENSOR_PERMUTE => {
+                TensorOp::Permute([c.read_usize()?, c.read_usize()?, c.read_usize()?, c.read_usize()?])
+            }
+            TENSOR_TRANSPOSE => TensorOp::Transpose(c.read_usize()?, c.read_usize()?),
+            TENSOR_FLATTEN   => TensorOp::Flatten(c.read_usize()?, c.read_usize()?),
+            TENSOR_SLICE     => TensorOp::Slice { dim: c.read_usize()?, start: c.read_usize()?, end: c.read_usize()? },
+            TENSOR_PAD => {
+                let pads = [c.read_usize()?, c.read_usize()?, c.read_usize()?, c.read_usize()?];
+                let mode = c.read_u8()?;
+                let value = c.read_f64()?;
+                let mode = match mode {
+                    PAD_CONSTANT => PadMode::Constant(value as f32),
+                    PAD_REFLECT  => PadMode::Reflect,
+                    PAD_EDGE     => PadMode::Edge,
+                    m => return Err(format!("Unknown pad mode: 0x{:02X}", m)),
+                };
+                TensorOp::Pad { pads, mode }
+            }
+            TENSOR_MEAN       => TensorOp::Reduce(Reduce::Mean, c.read_usize()?),
+            TENSOR_SUM        => TensorOp::Reduce(Reduce::Sum, c.read_usize()?),
+            TENSOR_MAX        => TensorOp::Reduce(Reduce::Max, c.read_usize()?),
+            TENSOR_SCALE_BIAS => TensorOp::ScaleBias { scale: c.read_f64()? as f32, bias: c.read_f64()? as f32 },
+            _ => return Err(format!("Unknown tensor op variant: 0x{:02X}", header.variant)),
+        };
+        self.add_tensor_op(id, op).map(|_| ())
+    }
 }
 
-fn contains_layer(reg: &LayerRegistry, layer_type: u8, layer_id: u32) -> bool {
-    match layer_type {
-        LAYER_LINEAR     => reg.linears.contains_key(&layer_id),
-        LAYER_NORM       => reg.norms.contains_key(&layer_id),
-        LAYER_CONV       => reg.convs.contains_key(&layer_id),
-        LAYER_ACTIVATION => reg.activations.contains_key(&layer_id),
-        LAYER_EMBEDDING  => reg.embeddings.contains_key(&layer_id),
-        LAYER_POOL       => reg.pools.contains_key(&layer_id),
-        LAYER_SHIFT      => reg.shifts.contains_key(&layer_id),
-        LAYER_GHOST      => reg.ghosts.contains_key(&layer_id),
-        LAYER_SEBLOCK    => reg.seblocks.contains_key(&layer_id),
-        LAYER_BINARY     => reg.binaries.contains_key(&layer_id),
-        _ => false,
-    }
+// ============================================================
+// GRAPH EXECUTOR — runGraph = compile + run (parser/validator: plan.rs, executor: graph.rs)
+// ============================================================
+/// Aktivasi tanpa parameter untuk blok attention (SE tail).
+fn param_free_activation(code: u8) -> Result<ActivationConfig, String> {
+    Ok(match code {
+        ACT_GELU        => ActivationConfig::Gelu,
+        ACT_RELU        => ActivationConfig::Relu,
+        ACT_SIGMOID     => ActivationConfig::Sigmoid,
+        ACT_TANH        => ActivationConfig::Tanh,
+        ACT_HARDSWISH   => ActivationConfig::HardSwish,
+        ACT_HARDSIGMOID => ActivationConfig::HardSigmoid(burn::nn::HardSigmoidConfig::new()),
+        ACT_MISH        => ActivationConfig::Mish,
+        _ => return Err(format!("Activation 0x{:02X} not allowed here (parameter-free only)", code)),
+    })
 }
 
-fn validate_plan(reg: &LayerRegistry, plan: &[u8]) -> Result<(u32, u32, u8), String> {
-    let mut c = PayloadCursor::new(plan);
-    let num_steps = c.read_u32()?;
-    let num_slots = c.read_u32()?;
-    if num_steps == 0 {
-        return Err("run_graph: plan has no steps".into());
-    }
-    if !(1..=MAX_SLOTS).contains(&num_slots) {
-        return Err(format!("run_graph: num_slots must be 1..={}, got {}", MAX_SLOTS, num_slots));
-    }
-    let mut filled: u64 = 1; // bit 0 = slot 0 (input eksternal)
-    for _ in 0..num_steps {
-        let s = read_run_step(&mut c)?;
-        let in_slot = s.in_slot as u32;
-        let in_slot2 = s.in_slot2 as u32;
-        let out_slot = s.out_slot as u32;
-        if in_slot >= num_slots || in_slot2 >= num_slots || out_slot >= num_slots {
-            return Err(format!("run_graph: slot index out of range (num_slots={})", num_slots));
-        }
-        if s.arity == crate::graph::ARITY_BINARY {
-            if s.layer_type != LAYER_BINARY {
-                return Err(format!("run_graph: arity 2 requires LAYER_BINARY, got 0x{:02X}", s.layer_type));
+/// Kebalikan param_free_activation; None kalau tidak terwakili kode ACT_* (mis. HardSigmoid non-default).
+fn param_free_activation_code(act: &ActivationConfig) -> Option<u8> {
+    Some(match act {
+        ActivationConfig::Gelu      => ACT_GELU,
+        ActivationConfig::Relu      => ACT_RELU,
+        ActivationConfig::Sigmoid   => ACT_SIGMOID,
+        ActivationConfig::Tanh      => ACT_TANH,
+        ActivationConfig::HardSwish => ACT_HARDSWISH,
+        ActivationConfig::Mish      => ACT_MISH,
+        ActivationConfig::HardSigmoid(c) => {
+            let d = burn::nn::HardSigmoidConfig::new();
+            if c.alpha != d.alpha || c.beta != d.beta {
+                return None;
             }
-            if (filled >> in_slot) & 1 == 0 {
-                return Err(format!("run_graph: input slot {} is empty", in_slot));
-            }
-            if (filled >> in_slot2) & 1 == 0 {
-                return Err(format!("run_graph: input slot {} is empty", in_slot2));
-            }
-        } else if s.arity == crate::graph::ARITY_UNARY {
-            if s.layer_type == LAYER_BINARY {
-                return Err("run_graph: arity 1 cannot use LAYER_BINARY (needs 2 inputs)".into());
-            }
-            if (filled >> in_slot) & 1 == 0 {
-                return Err(format!("run_graph: input slot {} is empty", in_slot));
-            }
-        } else {
-            return Err(format!("run_graph: invalid arity {} (expected 1 or 2)", s.arity));
-        }
-        if !contains_layer(reg, s.layer_type, s.layer_id) {
-            return Err(format!("run_graph: layer type 0x{:02X} id {} not found", s.layer_type, s.layer_id));
+            ACT_HARDSIGMOID
         }
-        filled |= 1u64 << out_slot;
-    }
-    let out_slot = c.read_u8()? as u32;
-    if out_slot >= num_slots {
-        return Err(format!("run_graph: output slot {} out of range", out_slot));
-    }
-    if (filled >> out_slot) & 1 == 0 {
-        return Err(format!("run_graph: output slot {} is never written", out_slot));
-    }
-    Ok((num_steps, num_slots, out_slot as u8))
+        _ => return None,
+    })
+}
+
+// ---- payload OP_INIT blok custom setelah `id` (kebalikan init_ghost/seblock/eca/cbam/spatial_attn) ----
+fn ghost_fields(c: &GhostModuleConfig) -> Vec<u8> {
+    PayloadWriter::new()
+        .usize(c.in_channels)
+        .usize(c.out_channels)
+        .usize(c.kernel_size[0])
+        .usize(c.kernel_size[1])
+        .option_usize(Some(c.ratio))
+        .option_usize(Some(c.stride[0]))
+        .option_usize(Some(c.stride[1]))
+        .option_usize(Some(c.padding[0]))
+        .option_usize(Some(c.padding[1]))
+        .option_usize(Some(c.dw_kernel_size))
+        .bool(c.batch_norm)
+        .bool(c.relu)
+        .finish()
+}
+
+fn seblock_fields(c: &SeBlockConfig) -> Option<Vec<u8>> {
+    let squeeze = if c.max_squeeze { SE_SQUEEZE_MAX } else { SE_SQUEEZE_AVG };
+    Some(
+        PayloadWriter::new()
+            .usize(c.channels)
+            .option_usize(Some(c.reduction))
+            .u8(param_free_activation_code(&c.activation)?)
+            .u8(param_free_activation_code(&c.gate)?)
+            .u8(squeeze)
+            .finish(),
+    )
+}
+
+/// Kernel adaptif ditulis sebagai kernel tetap (gamma/beta tidak ada di paket).
+fn eca_fields(c: &EcaBlockConfig) -> Vec<u8> {
+    PayloadWriter::new().usize(c.channels).option_usize(Some(c.resolved_kernel())).finish()
+}
+
+fn cbam_fields(c: &CbamBlockConfig) -> Vec<u8> {
+    PayloadWriter::new()
+        .usize(c.channels)
+        .option_usize(Some(c.reduction))
+        .option_usize(Some(c.kernel_size))
+        .finish()
+}
+
+fn spatial_attn_fields(c: &SpatialAttentionConfig) -> Vec<u8> {
+    PayloadWriter::new().option_usize(Some(c.kernel_size)).finish()
 }
 
 #[wasm_bindgen]
 impl LayerRegistry {
+    /// Plan sekali pakai; untuk plan yang dijalankan berulang pakai compileGraph.
     #[wasm_bindgen(js_name = runGraph)]
     pub fn run_graph(&self, plan: &[u8], input: &WasmTensor) -> Result<WasmTensor, String> {
-        let (num_steps, num_slots, out_slot) = validate_plan(self, plan)?;
-        let mut c = PayloadCursor::new(plan);
-        let _ = c.read_u32()?;
-        let _ = c.read_u32()?;
-        let mut slots: Vec<Option<WasmTensor>> = (0..num_slots as usize).map(|_| None).collect();
-        slots[0] = Some(input.clone());
-        for _ in 0..num_steps {
-            let s = read_run_step(&mut c)?;
-            let out = if s.arity == crate::graph::ARITY_BINARY {
-                let a = slots[s.in_slot as usize]
-                    .as_ref()
-                    .ok_or_else(|| format!("run_graph: runtime empty slot {}", s.in_slot))?;
-                let b = slots[s.in_slot2 as usize]
-                    .as_ref()
-                    .ok_or_else(|| format!("run_graph: runtime empty slot {}", s.in_slot2))?;
-                self.forward_binary_layer(s.layer_id, a, b)?
-            } else {
-                let inp = slots[s.in_slot as usize]
-                    .as_ref()
-                    .ok_or_else(|| format!("run_graph: runtime empty slot {}", s.in_slot))?;
-                self.forward_layer(s.layer_id, s.layer_type, inp)?
-            };
-            slots[s.out_slot as usize] = Some(out);
-        }
-        slots[out_slot as usize]
-            .take()
-            .ok_or_else(|| format!("run_graph: runtime empty output slot {}", out_slot))
+        crate::graph::CompiledGraph::compile(self, plan, false, "run_graph")?.run(self, input)
     }
 }
 
@@ -590,6 +1680,9 @@ impl LayerRegistry {
 impl LayerRegistry {
     #[wasm_bindgen(js_name = layerExists)]
     pub fn layer_exists(&self, layer_type: u8, layer_id: LayerId) -> bool {
+        if self.check_type(layer_type, layer_id).is_err() {
+            return false;
+        }
         match layer_type {
             LAYER_LINEAR     => self.linears.contains_key(&layer_id),
             LAYER_NORM       => self.norms.contains_key(&layer_id),
@@ -597,16 +1690,240 @@ impl LayerRegistry {
             LAYER_ACTIVATION => self.activations.contains_key(&layer_id),
             LAYER_EMBEDDING  => self.embeddings.contains_key(&layer_id),
             LAYER_POOL       => self.pools.contains_key(&layer_id),
+            LAYER_RESAMPLE   => self.resamples.contains_key(&layer_id),
             LAYER_SHIFT      => self.shifts.contains_key(&layer_id),
             LAYER_GHOST      => self.ghosts.contains_key(&layer_id),
             LAYER_SEBLOCK    => self.seblocks.contains_key(&layer_id),
+            LAYER_ECA        => self.ecas.contains_key(&layer_id),
+            LAYER_CBAM       => self.cbams.contains_key(&layer_id),
+            LAYER_SPATIAL_ATTN => self.spatials.contains_key(&layer_id),
             LAYER_BINARY     => self.binaries.contains_key(&layer_id),
+            LAYER_TENSOR_OP  => self.tensor_ops.contains_key(&layer_id),
             _ => false,
         }
     }
 
+    /// Step plan boleh berupa control flow LOOP/IF/SELECT (lihat plan::ARITY_LOOP).
     #[wasm_bindgen(js_name = compileGraph)]
     pub fn compile_graph(&self, plan: &[u8]) -> Result<crate::graph::CompiledGraph, String> {
         crate::graph::CompiledGraph::build(self, plan)
     }
+
+    /// compileGraph + pass fusion (BN eval di-fold ke conv/linear, aktivasi ditempel ke producer).
+    /// Bobot hasil fold di-snapshot: compile ulang setelah bobot layer terkait berubah.
+    /// Plan dengan control flow di-compile apa adanya (tanpa fusion).
+    #[wasm_bindgen(js_name = compileGraphFused)]
+    pub fn compile_graph_fused(&self, plan: &[u8]) -> Result<crate::graph::CompiledGraph, String> {
+        crate::graph::CompiledGraph::build_fused(self, plan)
+    }
+            }
+
+// ============================================================
+// INTROSPEKSI — listLayers/describeLayer (model browser tanpa salinan bayangan paket init)
+// ============================================================
+#[wasm_bindgen]
+impl LayerRegistry {
+    /// JSON array semua layer, urut (type, id). Lihat describeLayer untuk bentuk entri.
+    #[wasm_bindgen(js_name = listLayers)]
+    pub fn list_layers(&self) -> String {
+        let entries: Vec<String> = self.layer_keys().iter().filter_map(|&(t, id)| self.describe_layer(t, id).ok()).collect();
+        format!("[{}]", entries.join(","))
+    }
+
+    /// {"type","name","variant","id","config":{..},"num_params","weight_layout":[..]|null}.
+    /// weight_layout null = tipe tanpa float bridge; config {} kalau tidak tercatat.
+    #[wasm_bindgen(js_name = describeLayer)]
+    pub fn describe_layer(&self, layer_type: u8, layer_id: LayerId) -> Result<String, String> {
+        self.check_type(layer_type, layer_id)?;
+        let num_params = self
+            .layer_num_params(layer_type, layer_id)
+            .ok_or_else(|| format!("describeLayer: type 0x{:02X} id {} not found", layer_type, layer_id))?;
+        let name = layer_type_name(layer_type);
+        let (variant, config) = self
+            .meta
+            .get(&(layer_type, layer_id))
+            .map_or((VARIANT_NONE, "{}"), |(v, c)| (*v, c.as_str()));
+        let layout = self.weight_layout(layer_id, layer_type).unwrap_or_else(|_| "null".into());
+        Ok(JsonObj::new()
+            .num("type", layer_type)
+            .str("name", name)
+            .num("variant", variant)
+            .num("id", layer_id)
+            .raw("config", config)
+            .num("num_params", num_params)
+            .raw("weight_layout", &layout)
+            .finish())
+    }
+}
+
+impl LayerRegistry {
+    /// Semua (tipe, id) terdaftar, urut.
+    fn layer_keys(&self) -> Vec<(u8, LayerId)> {
+        let mut keys: Vec<(u8, LayerId)> = LAYER_VARIANTS
+            .iter()
+            .flat_map(|&(t, ..)| self.layer_ids(t).into_iter().map(move |id| (t, id)))
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    /// Snapshot lengkap sebagai stream paket (pasangan applyPackets): per layer urut (tipe, id)
+    /// paket OP_INIT (asli, atau sintetis dari spec add_*) + OP_LOAD_STATE (dilewati untuk layer
+    /// stateless). Config yang tidak terwakili paket (SE dengan gate HardSigmoid non-default) -> Err.
+    pub fn snapshot_packets(&self) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        for (t, id) in self.layer_keys() {
+            let init = self.init_packets.get(&(t, id)).ok_or_else(|| {
+                format!("snapshot: {} id {} has no init packet (config not representable as packet)", layer_type_name(t), id)
+            })?;
+            out.extend_from_slice(init);
+            let state = self.state_packet(id, t)?;
+            if state.len() > PACKET_PREFIX_LEN + 8 + 4 {
+                out.extend_from_slice(&state);
             }
+        }
+        Ok(out)
+    }
+
+    /// Id terdaftar untuk satu tipe (urutan HashMap, belum diurutkan).
+    pub fn layer_ids(&self, layer_type: u8) -> Vec<LayerId> {
+        match layer_type {
+            LAYER_LINEAR     => self.linears.keys().copied().collect(),
+            LAYER_NORM       => self.norms.keys().copied().collect(),
+            LAYER_CONV       => self.convs.keys().copied().collect(),
+            LAYER_ACTIVATION => self.activations.keys().copied().collect(),
+            LAYER_EMBEDDING  => self.embeddings.keys().copied().collect(),
+            LAYER_POOL       => self.pools.keys().copied().collect(),
+            LAYER_RESAMPLE   => self.resamples.keys().copied().collect(),
+            LAYER_SHIFT      => self.shifts.keys().copied().collect(),
+            LAYER_GHOST      => self.ghosts.keys().copied().collect(),
+            LAYER_SEBLOCK    => self.seblocks.keys().copied().collect(),
+            LAYER_ECA        => self.ecas.keys().copied().collect(),
+            LAYER_CBAM       => self.cbams.keys().copied().collect(),
+            LAYER_SPATIAL_ATTN => self.spatials.keys().copied().collect(),
+            LAYER_BINARY     => self.binaries.keys().copied().collect(),
+            LAYER_TENSOR_OP  => self.tensor_ops.keys().copied().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Estimasi FLOP satu forward (profiler): linear/conv = 2 x MAC; norm ~5/elemen;
+    /// blok custom ~2 x param per posisi output; lainnya ~1/elemen. `inputs` = shape input.
+    pub(crate) fn estimate_flops(&self, layer_type: u8, layer_id: LayerId, inputs: &[&[usize]], out: &[usize]) -> u64 {
+        let numel = |s: &[usize]| s.iter().product::<usize>() as u64;
+        let out_n = numel(out);
+        let in_n = inputs.first().map_or(0, |s| numel(s));
+        match layer_type {
+            LAYER_LINEAR => self.linears.get(&layer_id).map_or(0, |l| 2 * l.macs(out_n as usize)),
+            LAYER_CONV => self.convs.get(&layer_id).map_or(0, |l| 2 * l.macs(in_n as usize, out_n as usize)),
+            LAYER_EMBEDDING => 0,
+            LAYER_NORM => 5 * out_n,
+            LAYER_GHOST | LAYER_SEBLOCK | LAYER_ECA | LAYER_CBAM | LAYER_SPATIAL_ATTN => {
+                let positions = out_n / out.get(1).copied().unwrap_or(1).max(1) as u64;
+                let params = self.layer_num_params(layer_type, layer_id).unwrap_or(0) as u64;
+                2 * params * positions + out_n
+            }
+            LAYER_BINARY => match self.binaries.get(&layer_id).map(|b| b.op()) {
+                // [.., m, k] x [.., k, n]: tiap elemen output = dot sepanjang k
+                Some(BinaryOp::Matmul) => 2 * out_n * inputs.first().map_or(1, |s| s[s.len() - 1]) as u64,
+                _ => out_n,
+            },
+            _ => in_n.max(out_n),
+        }
+    }
+
+    fn layer_num_params(&self, layer_type: u8, layer_id: LayerId) -> Option<usize> {
+        match layer_type {
+            LAYER_LINEAR     => self.linears.get(&layer_id).map(|l| l.num_params()),
+            LAYER_NORM       => self.norms.get(&layer_id).map(|l| l.num_params()),
+            LAYER_CONV       => self.convs.get(&layer_id).map(|l| l.num_params()),
+            LAYER_ACTIVATION => self.activations.get(&layer_id).map(|l| l.num_params()),
+            LAYER_EMBEDDING  => self.embeddings.get(&layer_id).map(|l| l.num_params()),
+            LAYER_POOL       => self.pools.get(&layer_id).map(|l| l.num_params()),
+            LAYER_RESAMPLE   => self.resamples.get(&layer_id).map(|l| l.num_params()),
+            LAYER_SHIFT      => self.shifts.get(&layer_id).map(|l| l.num_params()),
+            LAYER_GHOST      => self.ghosts.get(&layer_id).map(|l| l.num_params()),
+            LAYER_SEBLOCK    => self.seblocks.get(&layer_id).map(|l| l.num_params()),
+            LAYER_ECA        => self.ecas.get(&layer_id).map(|l| l.num_params()),
+            LAYER_CBAM       => self.cbams.get(&layer_id).map(|l| l.num_params()),
+            LAYER_SPATIAL_ATTN => self.spatials.get(&layer_id).map(|l| l.num_params()),
+            LAYER_BINARY     => self.binaries.get(&layer_id).map(|l| l.num_params()),
+            LAYER_TENSOR_OP  => self.tensor_ops.get(&layer_id).map(|l| l.num_params()),
+            _ => None,
+        }
+    }
+}
+
+// ============================================================
+// GLOBAL ID — akses hanya dengan id (registry withGlobalIds)
+// ============================================================
+#[wasm_bindgen]
+impl LayerRegistry {
+    /// Tipe layer untuk id (mode id global); undefined kalau tidak ada / mode per tipe.
+    #[wasm_bindgen(js_name = layerTypeOf)]
+    pub fn layer_type_of(&self, layer_id: LayerId) -> Option<u8> {
+        self.id_types.as_ref()?.get(&layer_id).copied()
+    }
+
+    #[wasm_bindgen(js_name = forwardById)]
+    pub fn forward_by_id(&self, layer_id: LayerId, input: &WasmTensor) -> Result<WasmTensor, String> {
+        self.forward_layer(layer_id, self.resolve_id(layer_id)?, input)
+    }
+
+    #[wasm_bindgen(js_name = getStateById)]
+    pub fn get_state_by_id(&self, layer_id: LayerId) -> Result<Vec<u8>, String> {
+        self.get_layer_state(layer_id, self.resolve_id(layer_id)?)
+    }
+
+    #[wasm_bindgen(js_name = loadStateById)]
+    pub fn load_state_by_id(&mut self, layer_id: LayerId, data: &[u8]) -> Result<(), String> {
+        self.load_layer_state(layer_id, self.resolve_id(layer_id)?, data)
+    }
+
+    #[wasm_bindgen(js_name = destroyById)]
+    pub fn destroy_by_id(&mut self, layer_id: LayerId) -> bool {
+        match self.layer_type_of(layer_id) {
+            Some(t) => self.destroy_layer(layer_id, t),
+            None => false,
+        }
+    }
+
+    /// compileGraph dengan step tanpa byte tipe: `[arity u8][id u32][in u8][in2 u8][out u8]`
+    /// (header & output slot sama). Tipe dicari dari id -> butuh withGlobalIds.
+    #[wasm_bindgen(js_name = compileGraphIds)]
+    pub fn compile_graph_ids(&self, plan: &[u8]) -> Result<crate::graph::CompiledGraph, String> {
+        crate::graph::CompiledGraph::build_ids(self, plan)
+    }
+}
+
+impl LayerRegistry {
+    pub(crate) fn resolve_id(&self, layer_id: LayerId) -> Result<u8, String> {
+        if self.id_types.is_none() {
+            return Err("layer lookup by id needs a registry created with withGlobalIds".into());
+        }
+        self.layer_type_of(layer_id)
+            .ok_or_else(|| format!("layer id {} not found", layer_id))
+    }
+}
+
+// ============================================================
+// FUSION ACCESS — dipakai pass fusion CompiledGraph (build_fused)
+// ============================================================
+impl LayerRegistry {
+    /// Varian yang tercatat saat add_* (lihat describeLayer).
+    pub(crate) fn layer_variant(&self, layer_type: u8, layer_id: LayerId) -> Option<u8> {
+        self.meta.get(&(layer_type, layer_id)).map(|m| m.0)
+    }
+
+    pub(crate) fn linear_ref(&self, layer_id: LayerId) -> Option<&WasmLinear> {
+        self.linears.get(&layer_id)
+    }
+
+    pub(crate) fn conv_ref(&self, layer_id: LayerId) -> Option<&WasmConv> {
+        self.convs.get(&layer_id)
+    }
+
+    pub(crate) fn norm_ref(&self, layer_id: LayerId) -> Option<&WasmNorm> {
+        self.norms.get(&layer_id)
+    }
+}
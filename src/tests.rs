@@ -3,7 +3,7 @@ mod tests {
     use crate::protocol::{
         PacketHeader, PayloadCursor, OP_INIT, VARIANT_NONE, LAYER_LINEAR, LAYER_ACTIVATION,
         ACT_RELU, LAYER_BINARY, BINARY_ADD, LAYER_EMBEDDING, LAYER_CONV, CONV_CONV2D,
-        LAYER_NORM, NORM_LAYER,
+        LAYER_NORM, NORM_LAYER, LAYER_TENSOR_OP,
     };
     use crate::registry::LayerRegistry;
     use crate::WasmTensor;
@@ -363,4 +363,1604 @@ mod tests {
         assert!(layout.contains("\"name\":\"gamma\""));
         assert!(layout.contains("\"name\":\"beta\""));
     }
-                   }
+
+    // ---- conv payload tail: dilation/groups/bias/pad mode + Conv3d/ConvTranspose1d ----
+    fn push_opt(p: &mut Vec<u8>, v: Option<u32>) {
+        p.push(v.is_some() as u8);
+        p.extend_from_slice(&v.unwrap_or(0).to_le_bytes());
+    }
+    // dims = [id, in_ch, out_ch, kh, kw]; tail = (dilation, groups, bias, pad_mode)
+    fn conv_payload(dims: [u32; 5], tail: (Option<u32>, Option<u32>, Option<u32>, u8)) -> Vec<u8> {
+        let (dil, groups, bias, pad_mode) = tail;
+        let mut p = Vec::new();
+        for v in dims { p.extend_from_slice(&v.to_le_bytes()); }
+        for _ in 0..4 { push_opt(&mut p, None); }
+        push_opt(&mut p, dil); push_opt(&mut p, dil);
+        push_opt(&mut p, groups);
+        push_opt(&mut p, bias);
+        p.push(pad_mode);
+        p
+    }
+    #[test]
+    fn conv_depthwise_dilated_same_keeps_spatial_and_layout() {
+        use crate::protocol::CONV_PAD_SAME;
+        let mut reg = LayerRegistry::new();
+        let p = conv_payload([1, 4, 4, 3, 3], (Some(2), Some(4), Some(0), CONV_PAD_SAME));
+        reg.init_layer(&mk_header(LAYER_CONV, CONV_CONV2D, p.len()), &p).unwrap();
+        // depthwise: weight [4, 1, 3, 3], tanpa bias
+        assert_eq!(reg.total_params(), 4 * 9);
+        assert_eq!(reg.weight_layout(1, LAYER_CONV).unwrap(), "[{\"name\":\"weight\",\"len\":36}]");
+        let input = WasmTensor::new(&vec![1.0; 4 * 5 * 5], &[1, 4, 5, 5]);
+        let out = reg.forward_layer(1, LAYER_CONV, &input).unwrap();
+        assert_eq!(out.shape(), vec![1, 4, 5, 5]);
+        let w = reg.get_weights_flat(1, LAYER_CONV).unwrap();
+        reg.set_weights_flat(1, LAYER_CONV, &w).unwrap();
+        assert_eq!(reg.get_weights_flat(1, LAYER_CONV).unwrap(), w);
+    }
+    #[test]
+    fn conv_invalid_tail_and_1d_kernel_are_err() {
+        use crate::protocol::{CONV_CONV1D, CONV_PAD_EXPLICIT, CONV_PAD_SAME};
+        let mut reg = LayerRegistry::new();
+        let groups_bad = conv_payload([1, 4, 6, 3, 3], (None, Some(4), None, CONV_PAD_EXPLICIT));
+        assert!(reg.init_layer(&mk_header(LAYER_CONV, CONV_CONV2D, groups_bad.len()), &groups_bad).is_err());
+        let even_same = conv_payload([1, 2, 2, 2, 2], (None, None, None, CONV_PAD_SAME));
+        assert!(reg.init_layer(&mk_header(LAYER_CONV, CONV_CONV2D, even_same.len()), &even_same).is_err());
+        let bad_mode = conv_payload([1, 2, 2, 3, 3], (None, None, None, 0x7F));
+        assert!(reg.init_layer(&mk_header(LAYER_CONV, CONV_CONV2D, bad_mode.len()), &bad_mode).is_err());
+        let kw_1d = conv_payload([1, 2, 2, 3, 3], (None, None, None, CONV_PAD_EXPLICIT));
+        assert!(reg.init_layer(&mk_header(LAYER_CONV, CONV_CONV1D, kw_1d.len()), &kw_1d).is_err());
+        assert_eq!(reg.total_params(), 0);
+    }
+    #[test]
+    fn conv3d_and_transpose1d_shapes_and_float_bridge() {
+        use crate::protocol::{CONV_CONV3D, CONV_CONVTRANSPOSE1D, CONV_PAD_VALID};
+        let mut reg = LayerRegistry::new();
+        // conv3d: in 2, out 3, kernel 3x3x3 (kd di blok khusus 3D), valid
+        let mut p = conv_payload([1, 2, 3, 3, 3], (None, None, None, CONV_PAD_VALID));
+        p.extend_from_slice(&3u32.to_le_bytes());
+        for _ in 0..3 { push_opt(&mut p, None); }
+        reg.init_layer(&mk_header(LAYER_CONV, CONV_CONV3D, p.len()), &p).unwrap();
+        // [B, C*D, H, W] = [1, 2*4, 5, 5] -> [1, 3*2, 3, 3]
+        let vol = WasmTensor::new(&vec![0.5; 2 * 4 * 5 * 5], &[1, 8, 5, 5]);
+        assert_eq!(reg.forward_layer(1, LAYER_CONV, &vol).unwrap().shape(), vec![1, 6, 3, 3]);
+        assert_eq!(reg.output_shape(1, LAYER_CONV, &[1, 8, 5, 5]).unwrap(), vec![1, 6, 3, 3]);
+        // dim 1 bukan kelipatan C_in -> Err, bukan panic di reshape
+        let odd = WasmTensor::new(&vec![0.5; 7 * 5 * 5], &[1, 7, 5, 5]);
+        assert!(reg.forward_layer(1, LAYER_CONV, &odd).err().unwrap().contains("C_in"));
+        assert!(reg.output_shape(1, LAYER_CONV, &[1, 7, 5, 5]).is_err());
+        let w = reg.get_weights_flat(1, LAYER_CONV).unwrap();
+        assert_eq!(w.len(), 3 * 2 * 27 + 3);
+        reg.set_weights_flat(1, LAYER_CONV, &w).unwrap();
+        assert_eq!(reg.get_weights_flat(1, LAYER_CONV).unwrap(), w);
+        // conv transpose 1d: stride 2 lewat payload lama, tanpa tail
+        let mut t = Vec::new();
+        for v in [2u32, 2, 4, 3, 1] { t.extend_from_slice(&v.to_le_bytes()); }
+        push_opt(&mut t, Some(2)); push_opt(&mut t, None); push_opt(&mut t, None); push_opt(&mut t, None);
+        reg.init_layer(&mk_header(LAYER_CONV, CONV_CONVTRANSPOSE1D, t.len()), &t).unwrap();
+        let seq = WasmTensor::new(&[1.0; 2 * 5], &[1, 2, 5, 1]);
+        assert_eq!(reg.forward_layer(2, LAYER_CONV, &seq).unwrap().shape(), vec![1, 4, 11, 1]);
+        let segs = reg.weight_layout(2, LAYER_CONV).unwrap();
+        assert_eq!(segs, "[{\"name\":\"weight\",\"len\":24},{\"name\":\"bias\",\"len\":4}]");
+    }
+
+    // ---- pool: adaptive max / adaptive avg 1d / global + tail dilation/ceil ----
+    fn init_pool(reg: &mut LayerRegistry, variant: u8, p: &[u8]) {
+        use crate::protocol::LAYER_POOL;
+        reg.init_layer(&mk_header(LAYER_POOL, variant, p.len()), p).unwrap();
+    }
+    #[test]
+    fn pool_global_and_adaptive_variants_forward_and_shape() {
+        use crate::protocol::{
+            LAYER_POOL, POOL_ADAPTIVEAVGPOOL1D, POOL_ADAPTIVEMAXPOOL2D, POOL_GLOBALAVGPOOL,
+            POOL_GLOBALMAXPOOL,
+        };
+        let mut reg = LayerRegistry::new();
+        init_pool(&mut reg, POOL_GLOBALAVGPOOL, &1u32.to_le_bytes());
+        init_pool(&mut reg, POOL_GLOBALMAXPOOL, &2u32.to_le_bytes());
+        let mut p = 3u32.to_le_bytes().to_vec();
+        p.extend_from_slice(&2u32.to_le_bytes());
+        p.extend_from_slice(&1u32.to_le_bytes());
+        init_pool(&mut reg, POOL_ADAPTIVEMAXPOOL2D, &p);
+        let mut p1 = 4u32.to_le_bytes().to_vec();
+        p1.extend_from_slice(&2u32.to_le_bytes());
+        init_pool(&mut reg, POOL_ADAPTIVEAVGPOOL1D, &p1);
+        assert_eq!(reg.total_params(), 0);
+
+        let x = WasmTensor::new(&[1.0, 5.0, 3.0, 2.0, 0.0, 4.0, 7.0, 6.0], &[1, 2, 2, 2]);
+        let avg = reg.forward_layer(1, LAYER_POOL, &x).unwrap();
+        assert_eq!(avg.shape(), vec![1, 2, 1, 1]);
+        assert_eq!(avg.to_array(), vec![2.75, 4.25]);
+        let max = reg.forward_layer(2, LAYER_POOL, &x).unwrap();
+        assert_eq!(max.to_array(), vec![5.0, 7.0]);
+        // adaptive max [2,1]: max per baris
+        let am = reg.forward_layer(3, LAYER_POOL, &x).unwrap();
+        assert_eq!(am.shape(), vec![1, 2, 2, 1]);
+        assert_eq!(am.to_array(), vec![5.0, 3.0, 4.0, 7.0]);
+        for id in 1..=3 {
+            let out = reg.forward_layer(id, LAYER_POOL, &x).unwrap();
+            assert_eq!(reg.output_shape(id, LAYER_POOL, &[1, 2, 2, 2]).unwrap(), out.shape());
+        }
+        // adaptive avg 1d: [B, C, L, 1] -> [B, C, 2, 1]
+        let seq = WasmTensor::new(&[1.0, 2.0, 3.0, 4.0], &[1, 1, 4, 1]);
+        let a1 = reg.forward_layer(4, LAYER_POOL, &seq).unwrap();
+        assert_eq!(a1.to_array(), vec![1.5, 3.5]);
+        assert_eq!(reg.output_shape(4, LAYER_POOL, &[1, 1, 4]).unwrap(), a1.shape());
+        // output 0 ditolak seperti adaptive max
+        let p0 = [5u32.to_le_bytes(), 0u32.to_le_bytes()].concat();
+        let err = reg.init_layer(&mk_header(LAYER_POOL, POOL_ADAPTIVEAVGPOOL1D, p0.len()), &p0).err().unwrap();
+        assert!(err.contains("output size must be > 0"), "{}", err);
+        assert!(!reg.layer_exists(LAYER_POOL, 5));
+    }
+    #[test]
+    fn pool_max2d_dilation_ceil_tail_matches_shape_inference() {
+        use crate::protocol::{LAYER_POOL, POOL_MAXPOOL2D};
+        let mut reg = LayerRegistry::new();
+        let mut p = 1u32.to_le_bytes().to_vec();
+        p.extend_from_slice(&2u32.to_le_bytes());
+        p.extend_from_slice(&2u32.to_le_bytes());
+        for s in [Some(2u32), Some(2), None, None, Some(2), Some(2)] {
+            p.push(s.is_some() as u8);
+            p.extend_from_slice(&s.unwrap_or(0).to_le_bytes());
+        }
+        p.push(1); // ceil_mode
+        init_pool(&mut reg, POOL_MAXPOOL2D, &p);
+        let x = WasmTensor::new(&[1.0; 6 * 6], &[1, 1, 6, 6]);
+        let out = reg.forward_layer(1, LAYER_POOL, &x).unwrap();
+        // span = 2*(2-1)+1 = 3 ; ceil((6-3)/2)+1 = 3
+        assert_eq!(out.shape(), vec![1, 1, 3, 3]);
+        assert_eq!(reg.output_shape(1, LAYER_POOL, &[1, 1, 6, 6]).unwrap(), out.shape());
+        assert!(reg.output_shape(1, LAYER_POOL, &[1, 1, 2, 2]).is_err());
+    }
+
+    // ---- binary broadcasting + tensor op engine ----
+    fn init_binary_op(reg: &mut LayerRegistry, id: u32, variant: u8, fill: Option<f64>) {
+        let mut p = id.to_le_bytes().to_vec();
+        p.extend_from_slice(&0u32.to_le_bytes());
+        if let Some(f) = fill {
+            p.extend_from_slice(&f.to_le_bytes());
+        }
+        reg.init_layer(&mk_header(LAYER_BINARY, variant, p.len()), &p).unwrap();
+    }
+    #[test]
+    fn binary_broadcast_div_max_where() {
+        use crate::protocol::{BINARY_DIV, BINARY_MAX, BINARY_WHERE};
+        let mut reg = LayerRegistry::new();
+        init_binary_op(&mut reg, 1, BINARY_DIV, None);
+        init_binary_op(&mut reg, 2, BINARY_MAX, None);
+        init_binary_op(&mut reg, 3, BINARY_WHERE, Some(-1.0));
+        let a = WasmTensor::new(&[2.0, 4.0, 6.0, 8.0], &[1, 2, 2, 1]);
+        // skala per channel [1,2,1,1] di-broadcast ke [1,2,2,1]
+        let b = WasmTensor::new(&[2.0, 4.0], &[1, 2, 1, 1]);
+        let d = reg.forward_binary_layer(1, &a, &b).unwrap();
+        assert_eq!(d.shape(), vec![1, 2, 2, 1]);
+        assert_eq!(d.to_array(), vec![1.0, 2.0, 1.5, 2.0]);
+        let m = reg.forward_binary_layer(2, &a, &WasmTensor::new(&[5.0], &[1, 1, 1, 1])).unwrap();
+        assert_eq!(m.to_array(), vec![5.0, 5.0, 6.0, 8.0]);
+        let mask = WasmTensor::new(&[1.0, 0.0, 0.0, 1.0], &[1, 2, 2, 1]);
+        let w = reg.forward_binary_layer(3, &a, &mask).unwrap();
+        assert_eq!(w.to_array(), vec![2.0, -1.0, -1.0, 8.0]);
+        // shape tidak kompatibel -> Err, bukan panic
+        let bad = WasmTensor::new(&[1.0, 2.0, 3.0], &[1, 3, 1, 1]);
+        assert!(reg.forward_binary_layer(1, &a, &bad).is_err());
+    }
+    fn init_tensor_op(reg: &mut LayerRegistry, id: u32, variant: u8, fields: &[u8]) {
+        let mut p = id.to_le_bytes().to_vec();
+        p.extend_from_slice(fields);
+        reg.init_layer(&mk_header(LAYER_TENSOR_OP, variant, p.len()), &p).unwrap();
+    }
+    fn le_u32s(v: &[u32]) -> Vec<u8> {
+        v.iter().flat_map(|x| x.to_le_bytes()).collect()
+    }
+    #[test]
+    fn tensor_op_reshape_permute_reduce_pad_match_shape_inference() {
+        use crate::protocol::{
+            PAD_REFLECT, TENSOR_MEAN, TENSOR_PAD, TENSOR_PERMUTE, TENSOR_RESHAPE,
+            TENSOR_SCALE_BIAS, TENSOR_SLICE,
+        };
+        let mut reg = LayerRegistry::new();
+        let reshape: Vec<u8> = [0i32, -1, 2, 1].iter().flat_map(|x| x.to_le_bytes()).collect();
+        init_tensor_op(&mut reg, 1, TENSOR_RESHAPE, &reshape);
+        init_tensor_op(&mut reg, 2, TENSOR_PERMUTE, &le_u32s(&[0, 2, 1, 3]));
+        init_tensor_op(&mut reg, 3, TENSOR_MEAN, &le_u32s(&[1]));
+        let mut pad = le_u32s(&[1, 1, 0, 0]);
+        pad.push(PAD_REFLECT);
+        pad.extend_from_slice(&0f64.to_le_bytes());
+        init_tensor_op(&mut reg, 4, TENSOR_PAD, &pad);
+        init_tensor_op(&mut reg, 5, TENSOR_SLICE, &le_u32s(&[1, 1, 2]));
+        let mut sb = 2f64.to_le_bytes().to_vec();
+        sb.extend_from_slice(&1f64.to_le_bytes());
+        init_tensor_op(&mut reg, 6, TENSOR_SCALE_BIAS, &sb);
+        assert_eq!(reg.total_params(), 0);
+
+        let x = WasmTensor::new(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &[1, 6, 1, 1]);
+        let r = reg.forward_layer(1, LAYER_TENSOR_OP, &x).unwrap();
+        assert_eq!(r.shape(), vec![1, 3, 2, 1]);
+        let p = reg.forward_layer(2, LAYER_TENSOR_OP, &r).unwrap();
+        assert_eq!(p.shape(), vec![1, 2, 3, 1]);
+        assert_eq!(p.to_array(), vec![1.0, 3.0, 5.0, 2.0, 4.0, 6.0]);
+        let m = reg.forward_layer(3, LAYER_TENSOR_OP, &p).unwrap();
+        assert_eq!(m.to_array(), vec![1.5, 3.5, 5.5]);
+        let row = WasmTensor::new(&[1.0, 2.0, 3.0], &[1, 1, 1, 3]);
+        let padded = reg.forward_layer(4, LAYER_TENSOR_OP, &row).unwrap();
+        assert_eq!(padded.to_array(), vec![2.0, 1.0, 2.0, 3.0, 2.0]);
+        let sl = reg.forward_layer(5, LAYER_TENSOR_OP, &p).unwrap();
+        assert_eq!(sl.shape(), vec![1, 1, 3, 1]);
+        assert_eq!(sl.to_array(), vec![2.0, 4.0, 6.0]);
+        let y = reg.forward_layer(6, LAYER_TENSOR_OP, &sl).unwrap();
+        assert_eq!(y.to_array(), vec![5.0, 9.0, 13.0]);
+        for (id, inp) in [(1, &x), (2, &r), (3, &p), (4, &row), (5, &p), (6, &sl)] {
+            let out = reg.forward_layer(id, LAYER_TENSOR_OP, inp).unwrap();
+            assert_eq!(reg.output_shape(id, LAYER_TENSOR_OP, &inp.shape()).unwrap(), out.shape());
+        }
+    }
+    #[test]
+    fn tensor_op_invalid_shapes_and_variants_are_err() {
+        use crate::protocol::{TENSOR_PERMUTE, TENSOR_RESHAPE, TENSOR_SLICE};
+        let mut reg = LayerRegistry::new();
+        let reshape: Vec<u8> = [5i32, 0, 1, 1].iter().flat_map(|x| x.to_le_bytes()).collect();
+        init_tensor_op(&mut reg, 1, TENSOR_RESHAPE, &reshape);
+        init_tensor_op(&mut reg, 2, TENSOR_PERMUTE, &le_u32s(&[0, 1, 1, 3]));
+        init_tensor_op(&mut reg, 3, TENSOR_SLICE, &le_u32s(&[1, 2, 9]));
+        let x = WasmTensor::new(&[1.0; 6], &[1, 6, 1, 1]);
+        for id in 1..=3 {
+            assert!(reg.forward_layer(id, LAYER_TENSOR_OP, &x).is_err());
+        }
+        let p = 4u32.to_le_bytes();
+        assert!(reg.init_layer(&mk_header(LAYER_TENSOR_OP, 0x7F, p.len()), &p).is_err());
+    }
+
+    // ---- resample: nearest / bilinear / pixel-shuffle + shape di compiled graph ----
+    fn init_resample(reg: &mut LayerRegistry, id: u32, variant: u8, fields: &[u8]) -> Result<(), String> {
+        use crate::protocol::LAYER_RESAMPLE;
+        let mut p = id.to_le_bytes().to_vec();
+        p.extend_from_slice(fields);
+        reg.init_layer(&mk_header(LAYER_RESAMPLE, variant, p.len()), &p)
+    }
+    fn scale_fields(sh: f64, sw: f64) -> Vec<u8> {
+        let mut f = vec![0u8];
+        f.extend_from_slice(&sh.to_le_bytes());
+        f.extend_from_slice(&sw.to_le_bytes());
+        f
+    }
+    #[test]
+    fn resample_nearest_bilinear_pixel_shuffle_forward() {
+        use crate::protocol::{LAYER_RESAMPLE, RESAMPLE_BILINEAR, RESAMPLE_NEAREST, RESAMPLE_PIXEL_SHUFFLE};
+        let mut reg = LayerRegistry::new();
+        init_resample(&mut reg, 1, RESAMPLE_NEAREST, &scale_fields(2.0, 2.0)).unwrap();
+        let mut size = vec![1u8];
+        size.extend_from_slice(&le_u32s(&[3, 5]));
+        init_resample(&mut reg, 2, RESAMPLE_BILINEAR, &size).unwrap();
+        init_resample(&mut reg, 3, RESAMPLE_PIXEL_SHUFFLE, &le_u32s(&[2])).unwrap();
+        assert_eq!(reg.total_params(), 0);
+
+        let x = WasmTensor::new(&[1.0, 2.0, 3.0, 4.0], &[1, 1, 2, 2]);
+        let up = reg.forward_layer(1, LAYER_RESAMPLE, &x).unwrap();
+        assert_eq!(up.shape(), vec![1, 1, 4, 4]);
+        assert_eq!(
+            up.to_array(),
+            vec![1.0, 1.0, 2.0, 2.0, 1.0, 1.0, 2.0, 2.0, 3.0, 3.0, 4.0, 4.0, 3.0, 3.0, 4.0, 4.0]
+        );
+        // bilinear pada input konstan tetap konstan
+        let flat = WasmTensor::new(&[7.0; 4], &[1, 1, 2, 2]);
+        let bl = reg.forward_layer(2, LAYER_RESAMPLE, &flat).unwrap();
+        assert_eq!(bl.shape(), vec![1, 1, 3, 5]);
+        assert!(bl.to_array().iter().all(|v| (v - 7.0).abs() < 1e-5));
+        // pixel-shuffle: 4 channel 1x1 -> 1 channel 2x2 (urutan channel = raster r x r)
+        let ps_in = WasmTensor::new(&[1.0, 2.0, 3.0, 4.0], &[1, 4, 1, 1]);
+        let ps = reg.forward_layer(3, LAYER_RESAMPLE, &ps_in).unwrap();
+        assert_eq!(ps.shape(), vec![1, 1, 2, 2]);
+        assert_eq!(ps.to_array(), vec![1.0, 2.0, 3.0, 4.0]);
+        assert!(reg.forward_layer(3, LAYER_RESAMPLE, &x).is_err());
+        // payload invalid
+        assert!(init_resample(&mut reg, 4, RESAMPLE_NEAREST, &scale_fields(0.0, 2.0)).is_err());
+        assert!(init_resample(&mut reg, 5, RESAMPLE_PIXEL_SHUFFLE, &le_u32s(&[0])).is_err());
+        assert!(init_resample(&mut reg, 6, 0x7F, &le_u32s(&[2])).is_err());
+    }
+    #[test]
+    fn compiled_graph_output_shape_matches_run() {
+        use crate::protocol::{LAYER_RESAMPLE, RESAMPLE_NEAREST, RESAMPLE_PIXEL_SHUFFLE};
+        let mut reg = LayerRegistry::new();
+        init_resample(&mut reg, 1, RESAMPLE_PIXEL_SHUFFLE, &le_u32s(&[2])).unwrap();
+        init_resample(&mut reg, 2, RESAMPLE_NEAREST, &scale_fields(2.0, 2.0)).unwrap();
+        init_binary_op(&mut reg, 3, BINARY_ADD, None);
+        let mut plan = Vec::new();
+        plan.extend_from_slice(&3u32.to_le_bytes());
+        plan.extend_from_slice(&4u32.to_le_bytes());
+        push_unary(&mut plan, LAYER_RESAMPLE, 1, 0, 1); // [1,8,2,2] -> [1,2,4,4]
+        push_unary(&mut plan, LAYER_RESAMPLE, 2, 0, 2); // [1,8,2,2] -> [1,8,4,4]
+        push_binary(&mut plan, LAYER_BINARY, 3, 1, 2, 3); // broadcast gagal: 2 vs 8
+        plan.push(3);
+        let g = reg.compile_graph(&plan).unwrap();
+        let shape = [1, 8, 2, 2];
+        let err = g.output_shape(&reg, &shape).unwrap_err();
+        assert!(err.contains("step 2"), "{}", err);
+
+        // plan valid: pixel-shuffle -> upsample
+        let mut plan = Vec::new();
+        plan.extend_from_slice(&2u32.to_le_bytes());
+        plan.extend_from_slice(&2u32.to_le_bytes());
+        push_unary(&mut plan, LAYER_RESAMPLE, 1, 0, 1);
+        push_unary(&mut plan, LAYER_RESAMPLE, 2, 1, 1);
+        plan.push(1);
+        let g = reg.compile_graph(&plan).unwrap();
+        let x = WasmTensor::new(&[0.5; 32], &shape);
+        let out = g.run(&reg, &x).unwrap();
+        assert_eq!(out.shape(), vec![1, 2, 8, 8]);
+        assert_eq!(g.output_shape(&reg, &shape).unwrap(), out.shape());
+    }
+
+    // ---- shift: circular/replicate, diagonal, grouped ----
+    fn init_shift(reg: &mut LayerRegistry, id: u32, variant: u8, fields: &[u8]) -> Result<(), String> {
+        use crate::protocol::LAYER_SHIFT;
+        let mut p = id.to_le_bytes().to_vec();
+        p.extend_from_slice(fields);
+        reg.init_layer(&mk_header(LAYER_SHIFT, variant, p.len()), &p)
+    }
+    fn shift_tail(mode: u8, fill: f64) -> Vec<u8> {
+        let mut t = vec![mode];
+        t.extend_from_slice(&fill.to_le_bytes());
+        t
+    }
+    #[test]
+    fn shift_modes_and_diagonal() {
+        use crate::protocol::{
+            LAYER_SHIFT, SHIFT_DOWN, SHIFT_DOWN_RIGHT, SHIFT_LEFT, SHIFT_MODE_CIRCULAR,
+            SHIFT_MODE_CONSTANT, SHIFT_MODE_REPLICATE, SHIFT_RIGHT,
+        };
+        let mut reg = LayerRegistry::new();
+        // payload lama (tanpa tail) tetap zero-fill
+        init_shift(&mut reg, 1, SHIFT_RIGHT, &1u32.to_le_bytes()).unwrap();
+        let mut f = 1u32.to_le_bytes().to_vec();
+        f.extend(shift_tail(SHIFT_MODE_CIRCULAR, 0.0));
+        init_shift(&mut reg, 2, SHIFT_LEFT, &f).unwrap();
+        let mut f = 1u32.to_le_bytes().to_vec();
+        f.extend(shift_tail(SHIFT_MODE_REPLICATE, 0.0));
+        init_shift(&mut reg, 3, SHIFT_DOWN, &f).unwrap();
+        let mut f = 1u32.to_le_bytes().to_vec();
+        f.extend(shift_tail(SHIFT_MODE_CONSTANT, -1.0));
+        init_shift(&mut reg, 4, SHIFT_DOWN_RIGHT, &f).unwrap();
+
+        let row = WasmTensor::new(&[1.0, 2.0, 3.0], &[1, 1, 1, 3]);
+        assert_eq!(reg.forward_layer(1, LAYER_SHIFT, &row).unwrap().to_array(), vec![0.0, 1.0, 2.0]);
+        assert_eq!(reg.forward_layer(2, LAYER_SHIFT, &row).unwrap().to_array(), vec![2.0, 3.0, 1.0]);
+        let col = WasmTensor::new(&[1.0, 2.0, 3.0], &[1, 1, 3, 1]);
+        assert_eq!(reg.forward_layer(3, LAYER_SHIFT, &col).unwrap().to_array(), vec![1.0, 1.0, 2.0]);
+        let x = WasmTensor::new(&[1.0, 2.0, 3.0, 4.0], &[1, 1, 2, 2]);
+        assert_eq!(reg.forward_layer(4, LAYER_SHIFT, &x).unwrap().to_array(), vec![-1.0, -1.0, -1.0, 1.0]);
+
+        let mut bad = 1u32.to_le_bytes().to_vec();
+        bad.extend(shift_tail(0x09, 0.0));
+        assert!(init_shift(&mut reg, 5, SHIFT_RIGHT, &bad).is_err());
+    }
+    #[test]
+    fn shift_grouped_shifts_each_channel_group() {
+        use crate::protocol::{LAYER_SHIFT, SHIFT_DOWN, SHIFT_GROUPED, SHIFT_LEFT, SHIFT_MODE_CIRCULAR, SHIFT_RIGHT, SHIFT_UP};
+        let mut reg = LayerRegistry::new();
+        let mut f = le_u32s(&[1, 4]);
+        f.extend_from_slice(&[SHIFT_UP, SHIFT_DOWN, SHIFT_LEFT, SHIFT_RIGHT]);
+        f.extend(shift_tail(SHIFT_MODE_CIRCULAR, 0.0));
+        init_shift(&mut reg, 1, SHIFT_GROUPED, &f).unwrap();
+        // 5 channel x 2x2: 4 group @1 channel + 1 channel sisa (tidak digeser)
+        let base = [1.0, 2.0, 3.0, 4.0];
+        let data: Vec<f32> = (0..5).flat_map(|_| base).collect();
+        let x = WasmTensor::new(&data, &[1, 5, 2, 2]);
+        let y = reg.forward_layer(1, LAYER_SHIFT, &x).unwrap();
+        assert_eq!(y.shape(), vec![1, 5, 2, 2]);
+        let out = y.to_array();
+        assert_eq!(&out[0..4], &[3.0, 4.0, 1.0, 2.0]); // up (wrap)
+        assert_eq!(&out[4..8], &[3.0, 4.0, 1.0, 2.0]); // down (wrap, H=2)
+        assert_eq!(&out[8..12], &[2.0, 1.0, 4.0, 3.0]); // left
+        assert_eq!(&out[12..16], &[2.0, 1.0, 4.0, 3.0]); // right
+        assert_eq!(&out[16..20], &base);
+        // jumlah arah melebihi payload / arah tidak dikenal -> Err
+        assert!(init_shift(&mut reg, 2, SHIFT_GROUPED, &le_u32s(&[1, 9])).is_err());
+        let mut f = le_u32s(&[1, 1]);
+        f.push(0x20);
+        assert!(init_shift(&mut reg, 3, SHIFT_GROUPED, &f).is_err());
+    }
+
+    // ---- ghost: ratio > 2, dw kernel, BN+ReLU, trim ke out_channels ----
+    fn ghost_payload(id: u32, out: u32, ratio: u32, tail: Option<(Option<u32>, bool, bool)>) -> Vec<u8> {
+        let mut p = le_u32s(&[id, 2, out, 1, 1]);
+        push_opt(&mut p, Some(ratio));
+        for _ in 0..4 {
+            push_opt(&mut p, None);
+        }
+        if let Some((dw, bn, relu)) = tail {
+            push_opt(&mut p, dw);
+            p.push(bn as u8);
+            p.push(relu as u8);
+        }
+        p
+    }
+    #[test]
+    fn ghost_ratio3_trims_channels_and_counts_params() {
+        use crate::protocol::LAYER_GHOST;
+        let mut reg = LayerRegistry::new();
+        // out 8, ratio 3: intrinsic ceil(8/3)=3, ghost 3*2=6 -> 9 dipotong ke 8
+        let p = ghost_payload(1, 8, 3, Some((Some(3), true, true)));
+        reg.init_layer(&mk_header(LAYER_GHOST, VARIANT_NONE, p.len()), &p).unwrap();
+        // primary 1x1 tanpa bias (BN) = 2*3 ; cheap dw 3x3 = 6*9 ;
+        // BN gamma/beta/running mean/var (num_params burn ikut running state) = 4*3 + 4*6
+        assert_eq!(reg.total_params(), 6 + 54 + 12 + 24);
+        let x = WasmTensor::new(&[0.5; 2 * 5 * 5], &[1, 2, 5, 5]);
+        let y = reg.forward_layer(1, LAYER_GHOST, &x).unwrap();
+        assert_eq!(y.shape(), vec![1, 8, 5, 5]);
+        assert!(y.to_array().iter().all(|v| *v >= 0.0));
+        // payload lama (tanpa tail): ratio 2 tetap intrinsic + ghost 1x1
+        let p = ghost_payload(2, 4, 2, None);
+        reg.init_layer(&mk_header(LAYER_GHOST, VARIANT_NONE, p.len()), &p).unwrap();
+        assert_eq!(reg.forward_layer(2, LAYER_GHOST, &x).unwrap().shape(), vec![1, 4, 5, 5]);
+    }
+    #[test]
+    fn ghost_invalid_config_is_err() {
+        use crate::protocol::LAYER_GHOST;
+        let mut reg = LayerRegistry::new();
+        for p in [ghost_payload(1, 8, 0, None), ghost_payload(2, 8, 2, Some((Some(2), false, false)))] {
+            assert!(reg.init_layer(&mk_header(LAYER_GHOST, VARIANT_NONE, p.len()), &p).is_err());
+        }
+        assert_eq!(reg.total_params(), 0);
+    }
+
+    // ---- attention family: SE options, ECA, CBAM, spatial attention ----
+    fn init_attention(reg: &mut LayerRegistry, lt: u8, p: &[u8]) -> Result<(), String> {
+        reg.init_layer(&mk_header(lt, VARIANT_NONE, p.len()), p)
+    }
+    fn build_attention() -> LayerRegistry {
+        use crate::protocol::{ACT_GELU, ACT_HARDSIGMOID, LAYER_CBAM, LAYER_ECA, LAYER_SEBLOCK, LAYER_SPATIAL_ATTN, SE_SQUEEZE_MAX};
+        let mut reg = LayerRegistry::new();
+        let mut se = le_u32s(&[1, 8]);
+        push_opt(&mut se, Some(4));
+        se.extend_from_slice(&[ACT_GELU, ACT_HARDSIGMOID, SE_SQUEEZE_MAX]);
+        init_attention(&mut reg, LAYER_SEBLOCK, &se).unwrap();
+        let mut eca = le_u32s(&[2, 8]);
+        push_opt(&mut eca, None); // adaptif: C=8 -> k=3
+        init_attention(&mut reg, LAYER_ECA, &eca).unwrap();
+        let mut cbam = le_u32s(&[3, 8]);
+        push_opt(&mut cbam, Some(4));
+        push_opt(&mut cbam, Some(3));
+        init_attention(&mut reg, LAYER_CBAM, &cbam).unwrap();
+        let mut sp = 4u32.to_le_bytes().to_vec();
+        push_opt(&mut sp, None); // default 7
+        init_attention(&mut reg, LAYER_SPATIAL_ATTN, &sp).unwrap();
+        reg
+    }
+    #[test]
+    fn attention_blocks_float_bridge_layout_and_state() {
+        use crate::protocol::{LAYER_CBAM, LAYER_ECA, LAYER_SEBLOCK, LAYER_SPATIAL_ATTN};
+        let mut reg = build_attention();
+        let data: Vec<f32> = (0..8 * 3 * 3).map(|i| (i % 5) as f32 - 2.0).collect();
+        let x = WasmTensor::new(&data, &[1, 8, 3, 3]);
+        // (id, type, jumlah param, skala output saat semua bobot 0)
+        let cases = [
+            (1, LAYER_SEBLOCK, 8 * 2 + 2 + 2 * 8 + 8, 0.5), // hardsigmoid(0) = 0.5
+            (2, LAYER_ECA, 3, 0.5),
+            (3, LAYER_CBAM, 8 * 2 + 2 + 2 * 8 + 8 + 2 * 3 * 3, 0.25), // channel 0.5 * spatial 0.5
+            (4, LAYER_SPATIAL_ATTN, 2 * 7 * 7, 0.5),
+        ];
+        for (id, lt, n, scale) in cases {
+            let flat = reg.get_weights_flat(id, lt).unwrap();
+            assert_eq!(flat.len(), n, "type 0x{:02X}", lt);
+            let layout = reg.weight_layout(id, lt).unwrap();
+            let layout_sum: usize = layout
+                .split("\"len\":")
+                .skip(1)
+                .map(|t| t.split(|ch: char| !ch.is_ascii_digit()).next().unwrap().parse::<usize>().unwrap())
+                .sum();
+            assert_eq!(layout_sum, n);
+            let y0 = reg.forward_layer(id, lt, &x).unwrap();
+            assert_eq!(y0.shape(), x.shape());
+            assert_eq!(reg.output_shape(id, lt, &[1, 8, 3, 3]).unwrap(), y0.shape());
+
+            // state roundtrip: zero -> load state lama -> output kembali sama
+            let state = reg.get_layer_state(id, lt).unwrap();
+            reg.set_weights_flat(id, lt, &vec![0.0; n]).unwrap();
+            let y = reg.forward_layer(id, lt, &x).unwrap().to_array();
+            for (a, b) in y.iter().zip(&data) {
+                assert!((a - b * scale).abs() < 1e-5, "type 0x{:02X}: {} vs {}", lt, a, b * scale);
+            }
+            reg.load_layer_state(id, lt, &state).unwrap();
+            assert_eq!(reg.forward_layer(id, lt, &x).unwrap().to_array(), y0.to_array());
+            assert!(reg.set_weights_flat(id, lt, &[0.0; 1]).is_err());
+        }
+        assert_eq!(reg.total_params(), cases.iter().map(|c| c.2).sum::<usize>());
+    }
+    #[test]
+    fn attention_invalid_config_is_err() {
+        use crate::protocol::{ACT_PRELU, ACT_SIGMOID, LAYER_ECA, LAYER_SEBLOCK, LAYER_SPATIAL_ATTN, SE_SQUEEZE_AVG};
+        let mut reg = LayerRegistry::new();
+        let mut se = le_u32s(&[1, 8]);
+        push_opt(&mut se, Some(4));
+        se.extend_from_slice(&[ACT_PRELU, ACT_SIGMOID, SE_SQUEEZE_AVG]);
+        assert!(init_attention(&mut reg, LAYER_SEBLOCK, &se).is_err());
+        let mut se = le_u32s(&[1, 2]);
+        push_opt(&mut se, Some(4)); // channels < reduction
+        assert!(init_attention(&mut reg, LAYER_SEBLOCK, &se).is_err());
+        let mut eca = le_u32s(&[2, 8]);
+        push_opt(&mut eca, Some(4));
+        assert!(init_attention(&mut reg, LAYER_ECA, &eca).is_err());
+        let mut sp = 3u32.to_le_bytes().to_vec();
+        push_opt(&mut sp, Some(2));
+        assert!(init_attention(&mut reg, LAYER_SPATIAL_ATTN, &sp).is_err());
+        assert_eq!(reg.total_params(), 0);
+    }
+
+    // ---- run_batch: N input ditumpuk = N forward terpisah ----
+    #[test]
+    fn compiled_graph_run_batch_matches_individual_runs() {
+        let (reg, _) = build_binary();
+        let g = reg.compile_graph(&binary_plan()).unwrap();
+        let data: Vec<f32> = (0..9).map(|i| i as f32 * 0.5 - 1.0).collect();
+        let out = g.run_batch_flat(&reg, &data, &[1, 3], 3).unwrap();
+        assert_eq!(out.shape(), vec![3, 4, 1, 1]);
+        let all = out.to_array();
+        for i in 0..3 {
+            let single = WasmTensor::new(&data[i * 3..(i + 1) * 3], &[1, 3, 1, 1]);
+            let expect = g.run(&reg, &single).unwrap().to_array();
+            for (a, b) in all[i * 4..(i + 1) * 4].iter().zip(&expect) {
+                assert!((a - b).abs() < 1e-5);
+            }
+        }
+        assert!(g.run_batch_flat(&reg, &data, &[1, 3], 4).is_err());
+        assert!(g.run_batch_flat(&reg, &data, &[1, 3], 0).is_err());
+    }
+
+    // ---- MemoryView + BufferPool: runView = run, buffer didaur ulang ----
+    #[test]
+    fn compiled_graph_run_view_matches_run_and_recycles_buffers() {
+        use crate::memory::{BufferPool, MemoryView};
+        let (reg, input) = build_binary();
+        let g = reg.compile_graph(&binary_plan()).unwrap();
+        let expect = g.run(&reg, &input).unwrap();
+        let mut pool = BufferPool::new(None);
+        let mut inp = pool.acquire(&input.shape());
+        inp.put_tensor(input.clone(), &mut pool).unwrap();
+        let mut out = MemoryView::new(&[1]);
+        g.run_view(&reg, &mut pool, &mut inp, &mut out).unwrap();
+        assert_eq!(out.shape(), expect.shape());
+        let got = out.take_tensor(&mut pool).to_array();
+        for (a, b) in got.iter().zip(&expect.to_array()) {
+            assert!((a - b).abs() < 1e-6);
+        }
+        // input view tetap siap pakai dengan shape yang sama
+        assert_eq!(inp.shape(), input.shape());
+        assert_eq!(inp.len(), input.shape().iter().product::<usize>());
+        assert!(out.set_shape(&[3, 3]).is_err());
+    }
+    #[test]
+    fn buffer_pool_acquire_release_and_cap() {
+        use crate::memory::BufferPool;
+        let mut pool = BufferPool::new(Some(1));
+        let a = pool.acquire(&[2, 3]);
+        let b = pool.acquire(&[2, 3, 1, 1]);
+        assert_eq!(a.len(), 6);
+        assert_eq!(pool.pooled_count(&[2, 3]), 0);
+        let ptr = a.ptr();
+        pool.release(a);
+        pool.release(b); // melebihi max_per_shape -> dibuang
+        assert_eq!(pool.pooled_count(&[2, 3, 1, 1]), 1);
+        assert_eq!(pool.acquire(&[2, 3]).ptr(), ptr);
+        assert_eq!(pool.pooled_count(&[2, 3]), 0);
+        pool.clear();
+    }
+
+    // ---- threads: evaluasi paralel = serial, step = loop manual ----
+    #[test]
+    fn es_parallel_evaluate_matches_serial_and_step() {
+        let obj = make_obj();
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+        let mut es = EsOptimizer::new(1, 0, 7, Some(16), Some(0.2), Some(0.1));
+        let flat = es.ask();
+        let d = es.dim() as usize;
+        let serial: Vec<f32> = flat.chunks(d).map(|c| obj.fitness(c) as f32).collect();
+        assert_eq!(pool.install(|| es.evaluate(&obj)), serial);
+
+        let mut stepped = EsOptimizer::new(1, 0, 42, Some(16), Some(0.2), Some(0.1));
+        pool.install(|| {
+            for _ in 0..5 { let _ = stepped.step(&obj); }
+        });
+        assert_eq!((stepped.best(), stepped.report()), run_es(42));
+        assert!(crate::thread_count() >= 1);
+    }
+
+    // ---- packet stream (snapshot CLI) + GraphMseObjective ----
+    fn linear_packet(id: u32) -> Vec<u8> {
+        let p = [le_u32s(&[id, 3, 2]), vec![1]].concat();
+        [mk_header(LAYER_LINEAR, VARIANT_NONE, p.len()).to_bytes(), p].concat()
+    }
+    fn linear_plan() -> Vec<u8> {
+        let mut plan = le_u32s(&[1, 2]);
+        push_unary(&mut plan, LAYER_LINEAR, 1, 0, 1);
+        plan.push(1);
+        plan
+    }
+    #[test]
+    fn apply_packets_restores_snapshot_and_reports_bad_packet() {
+        let mut src = LayerRegistry::new();
+        src.apply_packets(&linear_packet(1)).unwrap();
+        let snapshot = [linear_packet(1), src.state_packet(1, LAYER_LINEAR).unwrap()].concat();
+        let mut dst = LayerRegistry::new();
+        assert_eq!(dst.apply_packets(&snapshot).unwrap(), 2);
+        assert_eq!(dst.get_weights_flat(1, LAYER_LINEAR).unwrap(), src.get_weights_flat(1, LAYER_LINEAR).unwrap());
+        assert_eq!(dst.total_params(), 8);
+
+        let err = dst.apply_packets(&[linear_packet(2), vec![0x09, 0, 0, 0, 0, 0, 0, 0]].concat()).unwrap_err();
+        assert!(err.contains("packet 1"), "{}", err);
+        assert!(dst.apply_packets(&linear_packet(3)[..10]).is_err());
+    }
+    #[test]
+    fn graph_mse_objective_fitness_and_es_step() {
+        use crate::es::objective::GraphMseObjective;
+        let mut reg = LayerRegistry::new();
+        reg.apply_packets(&linear_packet(1)).unwrap();
+        let g = reg.compile_graph(&linear_plan()).unwrap();
+        let input = WasmTensor::new(&[1.0, -1.0, 0.5], &[1, 3]);
+        let target = g.run(&reg, &input).unwrap().to_array();
+        let obj = GraphMseObjective::new(reg, g, input, target, &[(LAYER_LINEAR, 1)]).unwrap();
+        assert_eq!(obj.dim(), 8);
+        let w0 = obj.initial_params().unwrap();
+        assert!(obj.fitness(&w0).abs() < 1e-12);
+        assert!(obj.fitness(&w0.iter().map(|v| v + 0.5).collect::<Vec<_>>()) < 0.0);
+        assert_eq!(obj.fitness(&[0.0; 3]), f64::NEG_INFINITY);
+        let mut es = EsOptimizer::new(8, 0, 3, Some(8), Some(0.1), Some(0.05));
+        assert!(es.step(&obj).contains("\"evals\":8,"));
+    }
+
+    // ---- API Rust: add_* + GraphBuilder + burn::Tensor ----
+    #[test]
+    fn rust_api_builds_graph_matching_packet_path() {
+        use crate::api::{ActivationSpec, Binary, BinaryOp, GraphBuilder, LinearSpec, Registry, Tensor4};
+        let mut reg = Registry::new();
+        let fc1 = reg.add_linear(1, LinearSpec { d_in: 3, d_out: 4, bias: true }).unwrap();
+        let fc2 = reg.add_linear(2, LinearSpec { d_in: 3, d_out: 4, bias: true }).unwrap();
+        let relu = reg.add_activation(4, ActivationSpec::Relu).unwrap();
+        let add = reg.add_binary(3, Binary::new(BinaryOp::Add, 0)).unwrap();
+        assert_eq!(reg.total_params(), 32);
+
+        let mut g = GraphBuilder::new();
+        let x = g.input();
+        let (a, b) = (g.unary(fc1, x), g.unary(fc2, x));
+        let sum = g.binary(add, a, b);
+        let y = g.unary(relu, sum);
+        let graph = g.build(&reg, y).unwrap();
+        assert_eq!(graph.step_count(), 4);
+
+        let input = WasmTensor::new(&[1.0, 2.0, 3.0], &[1, 3, 1, 1]);
+        let out: Tensor4 = graph.forward(&reg, input.inner.clone()).unwrap();
+        let via_plan = reg.run_graph(&g.to_plan(y), &input).unwrap();
+        assert_eq!(out.into_data().to_vec::<f32>().unwrap(), via_plan.to_array());
+        let manual = reg.forward(relu, reg.forward_binary(add,
+            reg.forward(fc1, input.inner.clone()).unwrap(),
+            reg.forward(fc2, input.inner.clone()).unwrap()).unwrap()).unwrap();
+        assert_eq!(manual.into_data().to_vec::<f32>().unwrap(), via_plan.to_array());
+
+        // binary step dengan layer non-binary ditolak
+        let mut bad = GraphBuilder::new();
+        let i = bad.input();
+        let o = bad.binary(fc1, i, i);
+        assert!(bad.build(&reg, o).is_err());
+        assert!(reg.forward_binary(fc1, input.inner.clone(), input.inner.clone()).is_err());
+    }
+    #[test]
+    fn rust_api_validates_specs_and_es_config() {
+        use crate::api::{EsConfig, EsKind, PoolSpec, Resample, ResampleSize};
+        let mut reg = LayerRegistry::new();
+        assert!(reg.add_resample(1, Resample::Bilinear(ResampleSize::Scale(0.0, 2.0))).is_err());
+        assert!(reg.add_resample(1, Resample::PixelShuffle(0)).is_err());
+        assert!(reg.add_pool(2, PoolSpec::AdaptiveMax2d { output: [0, 1] }).is_err());
+        assert!(reg.add_pool(2, PoolSpec::AdaptiveAvg1d { output: 0 }).is_err());
+        assert!(reg.add_pool(2, PoolSpec::AdaptiveAvg2d { output: [1, 0] }).is_err());
+        let p = reg.add_pool(2, PoolSpec::MaxPool2d {
+            kernel: [2, 2], stride: None, padding: None, dilation: None, ceil_mode: false,
+        }).unwrap();
+        assert_eq!(reg.output_shape(p.id, p.layer_type, &[1, 3, 8, 8]).unwrap(), vec![1, 3, 4, 4]);
+
+        let mut cfg = EsConfig::new(1);
+        cfg.strategy = EsKind::OpenEs;
+        cfg.seed = 42;
+        cfg.population = 16;
+        cfg.sigma = 0.2;
+        cfg.lr = 0.1;
+        let mut es = cfg.build();
+        let obj = make_obj();
+        for _ in 0..5 { let _ = es.step(&obj); }
+        assert_eq!((es.best(), es.report()), run_es(42));
+    }
+
+    // ---- versi protokol: prefix magic v2, v1 tetap diterima, versi asing ditolak ----
+    fn v2_packet(layer_type: u8, variant: u8, payload: &[u8]) -> Vec<u8> {
+        use crate::protocol::PROTOCOL_VERSION;
+        let mut h = mk_header(layer_type, variant, payload.len());
+        h.version = PROTOCOL_VERSION;
+        [h.to_bytes(), payload.to_vec()].concat()
+    }
+    #[test]
+    fn versioned_packets_use_v2_layouts_and_reject_unknown_versions() {
+        use crate::protocol::{BINARY_CONCAT, NORM_GROUP, PACKET_MAGIC};
+        let mut reg = LayerRegistry::new();
+        let mut group = le_u32s(&[1, 2, 4]); // v2: id, groups, channels, eps?
+        group.push(0);
+        group.extend_from_slice(&0f64.to_le_bytes());
+        let stream = [
+            v2_packet(LAYER_NORM, NORM_GROUP, &group),
+            v2_packet(LAYER_BINARY, BINARY_ADD, &le_u32s(&[2])),
+            v2_packet(LAYER_BINARY, BINARY_CONCAT, &le_u32s(&[3, 1])),
+            linear_packet(4), // v1 tanpa prefix
+        ]
+        .concat();
+        assert_eq!(reg.apply_packets(&stream).unwrap(), 4);
+        assert_eq!(reg.get_weights_flat(1, LAYER_NORM).unwrap().len(), 8);
+        assert_eq!(reg.binary_output_shape(3, &[1, 2], &[1, 3]).unwrap(), vec![1, 5, 1, 1]);
+        assert!(reg.layer_exists(LAYER_LINEAR, 4));
+
+        let future = [PACKET_MAGIC.to_vec(), vec![99], mk_header(LAYER_LINEAR, VARIANT_NONE, 0).to_bytes()].concat();
+        assert!(reg.apply_packets(&future).unwrap_err().contains("v99"));
+        assert!(PacketHeader::from_packet(&[0x42, 1, 0, 0, 0, 0, 0, 0]).is_err());
+        let h = PacketHeader::from_packet(&v2_packet(LAYER_LINEAR, VARIANT_NONE, &[])).unwrap();
+        assert_eq!((h.version, h.encoded_len(), h.opcode), (2, 12, OP_INIT));
+    }
+    #[test]
+    fn capabilities_lists_version_layers_and_variants() {
+        use crate::protocol::{capabilities, protocol_version, LAYER_VARIANTS, PROTOCOL_VERSION};
+        let caps = capabilities();
+        assert_eq!(protocol_version(), PROTOCOL_VERSION);
+        assert!(caps.starts_with(&format!("{{\"protocol_version\":{},", PROTOCOL_VERSION)));
+        assert!(caps.contains("\"opcodes\":[1,4,5]"));
+        for (t, name, _) in LAYER_VARIANTS {
+            assert!(caps.contains(&format!("{{\"type\":{},\"name\":\"{}\"", t, name)));
+        }
+        assert!(caps.contains("\"name\":\"binary\",\"variants\":[0,1,2,3,4,5,6,7,8,9]"));
+    }
+    // ---- introspeksi: listLayers/describeLayer ----
+    #[test]
+    fn list_layers_describes_type_variant_config_and_layout() {
+        use crate::protocol::{BINARY_CONCAT, NORM_GROUP};
+        let mut reg = LayerRegistry::new();
+        let mut group = le_u32s(&[1, 2, 4]);
+        group.push(0);
+        group.extend_from_slice(&0f64.to_le_bytes());
+        let stream = [
+            v2_packet(LAYER_NORM, NORM_GROUP, &group),
+            v2_packet(LAYER_BINARY, BINARY_CONCAT, &le_u32s(&[3, 1])),
+            linear_packet(4),
+        ]
+        .concat();
+        reg.apply_packets(&stream).unwrap();
+
+        let list = reg.list_layers();
+        assert_eq!(
+            list,
+            "[{\"type\":1,\"name\":\"linear\",\"variant\":255,\"id\":4,\
+             \"config\":{\"d_in\":3,\"d_out\":2,\"bias\":true},\"num_params\":8,\
+             \"weight_layout\":[{\"name\":\"weight\",\"len\":6},{\"name\":\"bias\",\"len\":2}]},\
+             {\"type\":2,\"name\":\"norm\",\"variant\":1,\"id\":1,\
+             \"config\":{\"num_groups\":2,\"num_channels\":4,\"epsilon\":null},\"num_params\":8,\
+             \"weight_layout\":[{\"name\":\"gamma\",\"len\":4},{\"name\":\"beta\",\"len\":4}]},\
+             {\"type\":19,\"name\":\"binary\",\"variant\":4,\"id\":3,\
+             \"config\":{\"op\":\"concat\",\"dim\":1},\"num_params\":0,\"weight_layout\":null}]"
+        );
+        assert!(reg.destroy_layer(4, LAYER_LINEAR));
+        assert!(reg.describe_layer(LAYER_LINEAR, 4).is_err());
+        assert!(!reg.list_layers().contains("\"linear\""));
+    }
+    #[test]
+    fn describe_layer_reports_burn_config_for_conv_and_blocks() {
+        use crate::api::{ConvKind, ConvSpec, SeBlockConfig};
+        let mut reg = LayerRegistry::new();
+        let c = reg.add_conv(7, ConvKind::Conv2d, &ConvSpec::new(3, 8, 3, 3)).unwrap();
+        let d = reg.describe_layer(c.layer_type, c.id).unwrap();
+        assert!(d.starts_with("{\"type\":3,\"name\":\"conv\",\"variant\":1,\"id\":7,\"config\":{\"Conv2d\":{"));
+        assert!(d.contains("\"channels\":[3,8]") && d.contains("\"kernel_size\":[3,3]"));
+        assert!(!d.contains(char::is_whitespace));
+
+        let se = reg.add_seblock(8, &SeBlockConfig::new(16)).unwrap();
+        let d = reg.describe_layer(se.layer_type, se.id).unwrap();
+        assert!(d.contains("\"config\":{\"channels\":16,\"reduction\":16,"));
+        assert!(d.contains(&format!("\"num_params\":{}", reg.total_params() - 8 * 27 - 8)));
+    }
+    // ---- namespace id global: satu id satu tipe, forward/plan cukup id ----
+    #[test]
+    fn global_ids_reject_cross_type_reuse_and_resolve_by_id() {
+        use crate::api::{ActivationSpec, GraphBuilder, LinearSpec};
+        let mut per_type = LayerRegistry::new();
+        per_type.add_linear(1, LinearSpec { d_in: 3, d_out: 2, bias: true }).unwrap();
+        assert!(per_type.add_activation(1, ActivationSpec::Relu).is_ok());
+        assert!(per_type.forward_by_id(1, &WasmTensor::new(&[1.0; 3], &[1, 3])).is_err());
+
+        let mut reg = LayerRegistry::with_global_ids();
+        let fc = reg.add_linear(1, LinearSpec { d_in: 3, d_out: 2, bias: true }).unwrap();
+        let err = reg.add_activation(1, ActivationSpec::Relu).unwrap_err();
+        assert!(err.contains("already used by type 0x01"), "{}", err);
+        assert!(!reg.layer_exists(LAYER_ACTIVATION, 1));
+        let act = reg.add_activation(2, ActivationSpec::Relu).unwrap();
+        assert_eq!(reg.layer_type_of(2), Some(LAYER_ACTIVATION));
+
+        let mut g = GraphBuilder::new();
+        let h = g.unary(fc, g.input());
+        let y = g.unary(act, h);
+        let id_plan = g.to_id_plan(y);
+        assert_eq!(id_plan.len() + 2, g.to_plan(y).len());
+        let x = WasmTensor::new(&[0.5, -1.0, 2.0], &[1, 3]);
+        let typed = reg.compile_graph(&g.to_plan(y)).unwrap().run(&reg, &x).unwrap();
+        let by_id = reg.compile_graph_ids(&id_plan).unwrap().run(&reg, &x).unwrap();
+        assert_eq!(typed.to_array(), by_id.to_array());
+        assert_eq!(
+            reg.forward_by_id(1, &x).unwrap().to_array(),
+            reg.forward_layer(1, LAYER_LINEAR, &x).unwrap().to_array()
+        );
+        assert_eq!(reg.get_state_by_id(1).unwrap(), reg.get_layer_state(1, LAYER_LINEAR).unwrap());
+
+        // lookup bertipe ke id milik tipe lain ditolak lewat id_types
+        let err = reg.forward_layer(2, LAYER_LINEAR, &x).err().unwrap();
+        assert!(err.contains("is type 0x04, not 0x01"), "{}", err);
+        assert!(reg.get_weights_flat(2, LAYER_LINEAR).unwrap_err().contains("global id namespace"));
+        assert!(reg.get_layer_state(1, LAYER_ACTIVATION).is_err());
+        assert!(reg.output_shape(1, LAYER_NORM, &[1, 3]).is_err());
+        assert!(!reg.layer_exists(LAYER_LINEAR, 2));
+        assert!(!reg.destroy_layer(1, LAYER_ACTIVATION));
+        assert_eq!(reg.layer_type_of(1), Some(LAYER_LINEAR));
+
+        assert!(reg.destroy_by_id(1));
+        assert_eq!(reg.layer_type_of(1), None);
+        assert!(reg.compile_graph_ids(&id_plan).err().unwrap().contains("layer id 1 not found"));
+        assert!(reg.add_activation(1, ActivationSpec::Relu).is_ok());
+    }
+    // ---- profiler per step: shape, FLOP, byte output, Chrome trace ----
+    #[test]
+    fn compiled_graph_profiles_steps_and_exports_chrome_trace() {
+        use crate::api::{ActivationSpec, GraphBuilder, LinearSpec};
+        let mut reg = LayerRegistry::new();
+        let fc = reg.add_linear(1, LinearSpec { d_in: 3, d_out: 2, bias: true }).unwrap();
+        let act = reg.add_activation(2, ActivationSpec::Relu).unwrap();
+        let mut g = GraphBuilder::new();
+        let h = g.unary(fc, g.input());
+        let y = g.unary(act, h);
+        let mut graph = g.build(&reg, y).unwrap();
+        let x = WasmTensor::new(&[0.5, -1.0, 2.0], &[1, 3]);
+
+        graph.run(&reg, &x).unwrap();
+        assert!(graph.last_profile().ends_with("\"steps\":[]}"));
+
+        graph.set_profiling(true);
+        graph.run(&reg, &x).unwrap();
+        let p = graph.last_profile();
+        assert!(p.contains("\"total_flops\":14,\"total_out_bytes\":16,"), "{}", p);
+        assert!(p.contains(
+            "{\"step\":0,\"type\":1,\"name\":\"linear\",\"id\":1,\"inputs\":[[1,3,1,1]],\"output\":[1,2,1,1],\"time_us\":"
+        ), "{}", p);
+        assert!(p.contains("\"flops\":12,\"out_bytes\":8}"));
+
+        let trace = graph.chrome_trace();
+        assert!(trace.starts_with("{\"traceEvents\":[{\"name\":\"linear#1\",\"cat\":\"linear\",\"ph\":\"X\",\"ts\":"));
+        assert!(trace.contains("\"name\":\"activation#2\""));
+        graph.set_profiling(false);
+        assert!(graph.chrome_trace().starts_with("{\"traceEvents\":[]"));
+    }
+    // ---- debug run: statistik per slot, stop di non-finite pertama ----
+    #[test]
+    fn run_debug_reports_slot_stats_and_stops_at_first_non_finite() {
+        use crate::api::{ActivationSpec, Binary, BinaryOp, GraphBuilder, LinearSpec, TensorOp};
+        let mut reg = LayerRegistry::new();
+        let fc = reg.add_linear(1, LinearSpec { d_in: 3, d_out: 2, bias: true }).unwrap();
+        let zero = reg.add_tensor_op(2, TensorOp::ScaleBias { scale: 0.0, bias: 0.0 }).unwrap();
+        let div = reg.add_binary(3, Binary::new(BinaryOp::Div, 0)).unwrap();
+        let act = reg.add_activation(4, ActivationSpec::Relu).unwrap();
+        let mut g = GraphBuilder::new();
+        let h = g.unary(fc, g.input());
+        let z = g.unary(zero, h);
+        let q = g.binary(div, h, z);
+        let y = g.unary(act, q);
+        let graph = g.build(&reg, y).unwrap();
+        reg.set_weights_flat(1, LAYER_LINEAR, &[1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.5, -0.5]).unwrap();
+        let x = WasmTensor::new(&[1.0, 2.0, 3.0], &[1, 3]);
+
+        let dbg = graph.run_debug(&reg, &x, Some(true), None).unwrap();
+        assert_eq!(dbg.stopped_at(), Some(2));
+        assert!(dbg.output().is_none());
+        let r = dbg.report();
+        assert!(r.starts_with("{\"completed\":false,\"stopped_at\":2,\"flags\":[\"ACTIVATION_NON_FINITE\"],\
+            \"offender\":{\"step\":2,\"type\":19,\"name\":\"binary\",\"id\":3}"), "{}", r);
+        assert!(r.contains("{\"step\":0,\"type\":1,\"name\":\"linear\",\"id\":1,\"slot\":1,\"shape\":[1,2,1,1],\
+            \"count\":2,\"non_finite\":0,\"min\":1.5,\"max\":1.5,\"mean\":1.5,\"std\":0}"), "{}", r);
+        assert!(r.contains("\"count\":2,\"non_finite\":2,\"min\":null,"));
+        assert_eq!(dbg.step_tensor(1).unwrap().to_array(), vec![0.0, 0.0]);
+        assert!(dbg.step_tensor(3).is_none());
+
+        let full = graph.run_debug(&reg, &x, None, Some(false)).unwrap();
+        assert_eq!((full.stopped_at(), full.output().is_some()), (None, true));
+        assert!(full.report().contains("\"offender\":{\"step\":2,"));
+        assert!(full.step_tensor(0).is_none());
+        let clean = graph.run_debug(&reg, &x, None, None).unwrap();
+        assert!(clean.report().contains("\"flags\":[\"ACTIVATION_NON_FINITE\"]"));
+
+        let nan_in = WasmTensor::new(&[f32::NAN, 0.0, 0.0], &[1, 3]);
+        let d = graph.run_debug(&reg, &nan_in, None, None).unwrap();
+        assert!(d.report().contains("\"flags\":[\"INPUT_NON_FINITE\"],\"offender\":null"));
+    }
+    // ---- fusion: BN eval di-fold ke conv/linear, aktivasi ditempel ke producer ----
+    fn set_bn_stats(reg: &mut LayerRegistry, id: u32, mean: &[f32], var: &[f32]) {
+        use burn::module::{Module, RunningState};
+        use burn::nn::BatchNormConfig;
+        use burn::record::{BinBytesRecorder, FullPrecisionSettings, Recorder};
+        use burn::tensor::Tensor;
+        let dev = Default::default();
+        let mut bn = BatchNormConfig::new(mean.len()).init::<crate::WasmBackend>(&dev);
+        bn.running_mean = RunningState::new(Tensor::from_floats(mean, &dev));
+        bn.running_var = RunningState::new(Tensor::from_floats(var, &dev));
+        let rec = crate::layers::norm::Normalization::Batch(bn).into_record();
+        let bytes = BinBytesRecorder::<FullPrecisionSettings>::default().record(rec, ()).unwrap();
+        reg.load_layer_state(id, LAYER_NORM, &bytes).unwrap();
+    }
+    fn max_abs_diff(a: &[f32], b: &[f32]) -> f32 {
+        assert_eq!(a.len(), b.len());
+        a.iter().zip(b).map(|(x, y)| (x - y).abs()).fold(0.0, f32::max)
+    }
+    #[test]
+    fn fused_graph_folds_batchnorm_and_activation_within_tolerance() {
+        use crate::api::{ActivationSpec, ConvKind, ConvSpec, GraphBuilder, NormSpec};
+        let mut reg = LayerRegistry::new();
+        let mut spec = ConvSpec::new(3, 4, 3, 3);
+        spec.bias = Some(false);
+        let conv = reg.add_conv(1, ConvKind::Conv2d, &spec).unwrap();
+        let bn = reg.add_norm(2, NormSpec::Batch { num_features: 4, epsilon: None }).unwrap();
+        let relu = reg.add_activation(3, ActivationSpec::Relu).unwrap();
+        reg.set_weights_flat(2, LAYER_NORM, &[1.5, 0.5, -1.0, 2.0, 0.1, -0.2, 0.3, 0.0]).unwrap();
+        set_bn_stats(&mut reg, 2, &[0.3, -0.2, 0.1, 0.5], &[0.5, 2.0, 1.5, 0.8]);
+
+        let mut g = GraphBuilder::new();
+        let c = g.unary(conv, g.input());
+        let n = g.unary(bn, c);
+        let y = g.unary(relu, n);
+        let plain = g.build(&reg, y).unwrap();
+        let fused = g.build_fused(&reg, y).unwrap();
+        assert_eq!((plain.fused_step_count(), fused.fused_step_count()), (0, 2));
+        assert_eq!(fused.step_count(), 1);
+        assert_eq!(fused.fusion_report(), "[{\"step\":0,\"type\":3,\"name\":\"conv\",\"id\":1,\"folded_bn\":2,\"activations\":[3]}]");
+
+        let x: Vec<f32> = (0..2 * 3 * 6 * 6).map(|i| ((i * 37 % 101) as f32 / 50.0) - 1.0).collect();
+        let x = WasmTensor::new(&x, &[2, 3, 6, 6]);
+        let a = plain.run(&reg, &x).unwrap();
+        let b = fused.run(&reg, &x).unwrap();
+        assert_eq!(a.shape(), b.shape());
+        assert!(max_abs_diff(&a.to_array(), &b.to_array()) < 1e-4);
+    }
+    #[test]
+    fn fusion_folds_linear_bias_and_skips_shared_slots() {
+        use crate::api::{ActivationSpec, Binary, BinaryOp, GraphBuilder, LinearSpec, NormSpec};
+        let mut reg = LayerRegistry::new();
+        let fc = reg.add_linear(1, LinearSpec { d_in: 3, d_out: 4, bias: true }).unwrap();
+        let bn = reg.add_norm(2, NormSpec::Batch { num_features: 4, epsilon: None }).unwrap();
+        let gelu = reg.add_activation(3, ActivationSpec::Gelu).unwrap();
+        let add = reg.add_binary(4, Binary::new(BinaryOp::Add, 0)).unwrap();
+        let soft = reg.add_activation(5, ActivationSpec::Softmax { dim: 1 }).unwrap();
+        reg.set_weights_flat(2, LAYER_NORM, &[0.7, 1.2, -0.4, 1.0, 0.05, 0.0, -0.3, 0.2]).unwrap();
+        set_bn_stats(&mut reg, 2, &[0.1, 0.4, -0.5, 0.0], &[1.3, 0.6, 0.9, 2.5]);
+        let x = WasmTensor::new(&[0.5, -1.0, 2.0, 1.5, 0.25, -0.75], &[2, 3]);
+
+        // linear -> bn -> gelu: BN + GELU terserap
+        let mut g = GraphBuilder::new();
+        let h = g.unary(fc, g.input());
+        let n = g.unary(bn, h);
+        let y = g.unary(gelu, n);
+        let fused = g.build_fused(&reg, y).unwrap();
+        assert_eq!((fused.fused_step_count(), fused.step_count()), (2, 1));
+        let plain = g.build(&reg, y).unwrap();
+        let d = max_abs_diff(&plain.run(&reg, &x).unwrap().to_array(), &fused.run(&reg, &x).unwrap().to_array());
+        assert!(d < 1e-5, "{}", d);
+
+        // output linear juga dibaca add -> BN tidak di-fold, GELU menempel ke step BN; softmax tidak fusable
+        let mut g = GraphBuilder::new();
+        let h = g.unary(fc, g.input());
+        let n = g.unary(bn, h);
+        let a = g.unary(gelu, n);
+        let s = g.binary(add, a, h);
+        let y = g.unary(soft, s);
+        let fused = g.build_fused(&reg, y).unwrap();
+        assert_eq!((fused.fused_step_count(), fused.step_count()), (1, 4));
+        assert!(fused.fusion_report().contains("\"id\":2,\"folded_bn\":null,\"activations\":[3]"));
+        let plain = g.build(&reg, y).unwrap();
+        let d = max_abs_diff(&plain.run(&reg, &x).unwrap().to_array(), &fused.run(&reg, &x).unwrap().to_array());
+        assert!(d < 1e-6);
+    }
+    // ---- control flow: LOOP dengan slot loop-carried, IF/ELSE dan SELECT pada slot skalar ----
+    #[test]
+    fn loop_step_carries_slot_across_iterations() {
+        use crate::api::{GraphBuilder, LinearSpec};
+        let mut reg = LayerRegistry::new();
+        let fc = reg.add_linear(1, LinearSpec { d_in: 2, d_out: 2, bias: true }).unwrap();
+        reg.set_weights_flat(1, LAYER_LINEAR, &[0.5, 0.0, 0.0, 0.5, 1.0, 1.0]).unwrap();
+        let mut g = GraphBuilder::new();
+        let h = g.unary(fc, g.input());
+        g.repeat(3, |g| g.unary_into(fc, h, h));
+        let graph = g.build(&reg, h).unwrap();
+        assert_eq!(graph.step_count(), 3);
+
+        // h <- 0.5 h + 1, empat kali
+        let x = WasmTensor::new(&[0.0, 4.0], &[1, 2]);
+        assert_eq!(graph.run(&reg, &x).unwrap().to_array(), vec![1.875, 2.125]);
+        let dbg = graph.run_debug(&reg, &x, Some(true), None).unwrap();
+        assert_eq!(dbg.step_tensor(2).unwrap().to_array(), vec![1.75, 2.25]);
+        assert!(dbg.step_tensor(4).is_none());
+
+        // shape inference menelusuri body sebanyak iterasi
+        use crate::api::PoolSpec;
+        let pool = reg.add_pool(2, PoolSpec::AvgPool2d { kernel: [2, 2], stride: None, padding: None }).unwrap();
+        let mut g = GraphBuilder::new();
+        let p = g.unary(pool, g.input());
+        g.repeat(2, |g| g.unary_into(pool, p, p));
+        assert_eq!(g.build(&reg, p).unwrap().output_shape(&reg, &[1, 1, 16, 16]).unwrap(), vec![1, 1, 2, 2]);
+
+        // slot yang dibaca body loop harus sudah terisi sebelum loop
+        let mut g = GraphBuilder::new();
+        let h = g.slot();
+        g.repeat(2, |g| g.unary_into(fc, h, h));
+        assert!(g.build(&reg, h).err().unwrap().contains("input slot 1 is empty"));
+        // iterasi 0 / body kosong ditolak
+        let mut g = GraphBuilder::new();
+        let h = g.unary(fc, g.input());
+        g.repeat(0, |g| g.unary_into(fc, h, h));
+        assert!(g.build(&reg, h).is_err());
+        let mut g = GraphBuilder::new();
+        g.repeat(2, |_| {});
+        let h = g.unary(fc, g.input());
+        assert!(g.build(&reg, h).is_err());
+    }
+    #[test]
+    fn if_else_and_select_branch_on_scalar_slot() {
+        use crate::api::{ActivationSpec, GraphBuilder, LinearSpec};
+        let mut reg = LayerRegistry::new();
+        let diff = reg.add_linear(1, LinearSpec { d_in: 2, d_out: 1, bias: false }).unwrap();
+        let fc = reg.add_linear(2, LinearSpec { d_in: 2, d_out: 2, bias: false }).unwrap();
+        let neg = reg.add_activation(3, ActivationSpec::Relu).unwrap();
+        reg.set_weights_flat(1, LAYER_LINEAR, &[1.0, -1.0]).unwrap();
+        reg.set_weights_flat(2, LAYER_LINEAR, &[0.0, 1.0, 1.0, 0.0]).unwrap();
+
+        // cond = x0 - x1; then: relu(x), else: swap(x)
+        let mut g = GraphBuilder::new();
+        let cond = g.unary(diff, g.input());
+        let r = g.slot();
+        g.if_else(cond, |g| g.unary_into(neg, g.input(), r), |g| g.unary_into(fc, g.input(), r));
+        let graph = g.build(&reg, r).unwrap();
+        let run = |x: &[f32]| graph.run(&reg, &WasmTensor::new(x, &[1, 2])).unwrap().to_array();
+        assert_eq!(run(&[3.0, -1.0]), vec![3.0, 0.0]);
+        assert_eq!(run(&[-1.0, 3.0]), vec![3.0, -1.0]);
+
+        // select tanpa layer: cond ? x : swap(x)
+        let mut g = GraphBuilder::new();
+        let cond = g.unary(diff, g.input());
+        let sw = g.unary(fc, g.input());
+        let y = g.select(cond, g.input(), sw);
+        let graph = g.build(&reg, y).unwrap();
+        let run = |x: &[f32]| graph.run(&reg, &WasmTensor::new(x, &[1, 2])).unwrap().to_array();
+        assert_eq!(run(&[2.0, 1.0]), vec![2.0, 1.0]);
+        assert_eq!(run(&[1.0, 2.0]), vec![2.0, 1.0]);
+        // kondisi bukan skalar -> Err saat run
+        let mut g = GraphBuilder::new();
+        let y = g.select(g.input(), g.input(), g.input());
+        let graph = g.build(&reg, y).unwrap();
+        assert!(graph.run(&reg, &WasmTensor::new(&[1.0, 2.0], &[1, 2])).err().unwrap().contains("scalar"));
+
+        // slot yang hanya ditulis satu cabang tidak boleh dibaca setelah IF
+        let mut g = GraphBuilder::new();
+        let cond = g.unary(diff, g.input());
+        let r = g.slot();
+        g.if_else(cond, |g| g.unary_into(neg, g.input(), r), |_| {});
+        let y = g.unary(fc, r);
+        assert!(g.build(&reg, y).err().unwrap().contains("input slot 2 is empty"));
+    }
+    #[test]
+    fn output_shape_ignores_branch_temporaries_sharing_a_physical_slot() {
+        use crate::api::{GraphBuilder, LinearSpec};
+        let mut reg = LayerRegistry::new();
+        let mut lin = |id, d_in, d_out| reg.add_linear(id, LinearSpec { d_in, d_out, bias: true }).unwrap();
+        let (c, t1, t2, e1, e2) = (lin(1, 4, 1), lin(2, 4, 3), lin(3, 3, 2), lin(4, 4, 5), lin(5, 5, 2));
+        let mut g = GraphBuilder::new();
+        let cond = g.unary(c, g.input());
+        let r = g.slot();
+        g.if_else(
+            cond,
+            |g| {
+                let h = g.unary(t1, g.input());
+                g.unary_into(t2, h, r);
+            },
+            |g| {
+                let h = g.unary(e1, g.input());
+                g.unary_into(e2, h, r);
+            },
+        );
+        let graph = g.build(&reg, r).unwrap();
+        // temporer cabang else memakai ulang slot fisik input (0)
+        assert_eq!((graph.slot_count(), graph.physical_slot_count()), (5, 2));
+        let x = WasmTensor::new(&[0.5, -1.0, 2.0, 0.25], &[1, 4, 1, 1]);
+        assert_eq!(graph.run(&reg, &x).unwrap().shape(), vec![1, 2, 1, 1]);
+        assert_eq!(graph.output_shape(&reg, &[1, 4, 1, 1]).unwrap(), vec![1, 2, 1, 1]);
+    }
+    // ---- plan v2: slot u16, bitset tanpa batas 64, reuse slot berdasarkan liveness ----
+    #[test]
+    fn plan_v2_lifts_slot_limit_and_reuses_dead_slots() {
+        use crate::api::{ActivationSpec, Binary, BinaryOp, GraphBuilder, LinearSpec};
+        use crate::protocol::PLAN_MAGIC;
+        let mut reg = LayerRegistry::new();
+        let fc = reg.add_linear(1, LinearSpec { d_in: 2, d_out: 2, bias: true }).unwrap();
+        reg.set_weights_flat(1, LAYER_LINEAR, &[0.0, 1.0, 1.0, 0.0, 0.01, -0.01]).unwrap();
+        let x = WasmTensor::new(&[1.0, 2.0], &[1, 2]);
+
+        // 300 step berantai: 301 slot SSA, semuanya in-place di satu slot fisik
+        let mut g = GraphBuilder::new();
+        let mut h = g.input();
+        for _ in 0..300 {
+            h = g.unary(fc, h);
+        }
+        let plan = g.to_plan(h);
+        assert_eq!(plan[..4], [PLAN_MAGIC[0], PLAN_MAGIC[1], PLAN_MAGIC[2], 2]);
+        let graph = reg.compile_graph(&plan).unwrap();
+        assert_eq!((graph.slot_count(), graph.physical_slot_count(), graph.output_slot()), (301, 1, 300));
+        let out = graph.run(&reg, &x).unwrap().to_array();
+        assert_eq!(out, reg.run_graph(&plan, &x).unwrap().to_array());
+        assert!((out[0] - 1.0).abs() < 1e-4 && (out[1] - 2.0).abs() < 1e-4, "{:?}", out);
+
+        // diamond: dua cabang hidup bersamaan -> 2 slot fisik
+        let add = reg.add_binary(2, Binary::new(BinaryOp::Add, 0)).unwrap();
+        let relu = reg.add_activation(3, ActivationSpec::Relu).unwrap();
+        let mut g = GraphBuilder::new();
+        let a = g.unary(fc, g.input());
+        let b = g.unary(relu, g.input());
+        let c = g.binary(add, a, b);
+        let d = g.unary(relu, c);
+        let graph = g.build(&reg, d).unwrap();
+        assert_eq!((graph.slot_count(), graph.physical_slot_count()), (5, 2));
+        assert_eq!(graph.run(&reg, &x).unwrap().to_array(), reg.run_graph(&g.to_plan(d), &x).unwrap().to_array());
+
+        // v2 manual: slot u16 > 255; versi tak dikenal ditolak; v1 tetap diterima
+        let mut v2 = vec![PLAN_MAGIC[0], PLAN_MAGIC[1], PLAN_MAGIC[2], 2];
+        v2.extend_from_slice(&le_u32s(&[1, 1001]));
+        v2.extend_from_slice(&[crate::plan::ARITY_UNARY, LAYER_LINEAR, 1, 0, 0, 0, 0, 0, 0, 0, 0xE8, 0x03, 0xE8, 0x03]);
+        let graph = reg.compile_graph(&v2).unwrap();
+        assert_eq!((graph.output_slot(), graph.physical_slot_count()), (1000, 1));
+        assert_eq!(graph.run(&reg, &x).unwrap().to_array().len(), 2);
+        v2[3] = 3;
+        assert!(reg.compile_graph(&v2).err().unwrap().contains("plan version 3"));
+        assert!(reg.run_graph(&v2, &x).is_err());
+        assert_eq!(reg.compile_graph(&linear_plan()).unwrap().physical_slot_count(), 1);
+    }
+    // ---- runGraph = compile + run: satu parser/validator/executor ----
+    #[test]
+    fn run_graph_shares_parser_and_executor_with_compile_graph() {
+        use crate::api::{GraphBuilder, LinearSpec};
+        let mut reg = LayerRegistry::new();
+        let fc = reg.add_linear(1, LinearSpec { d_in: 2, d_out: 2, bias: true }).unwrap();
+        reg.set_weights_flat(1, LAYER_LINEAR, &[0.5, 0.0, 0.0, 0.5, 1.0, 1.0]).unwrap();
+        let x = WasmTensor::new(&[0.0, 4.0], &[1, 2]);
+
+        // control flow kini juga jalan lewat runGraph
+        let mut g = GraphBuilder::new();
+        let h = g.unary(fc, g.input());
+        g.repeat(3, |g| g.unary_into(fc, h, h));
+        assert_eq!(reg.run_graph(&g.to_plan(h), &x).unwrap().to_array(), vec![1.875, 2.125]);
+
+        // error validasi identik, beda prefix entry point saja
+        let mut bad = le_u32s(&[1, 3]);
+        push_unary(&mut bad, LAYER_LINEAR, 1, 2, 1);
+        bad.push(1);
+        let e1 = reg.run_graph(&bad, &x).err().unwrap();
+        let e2 = reg.compile_graph(&bad).err().unwrap();
+        assert_eq!(e1, "run_graph: input slot 2 is empty");
+        assert_eq!(e2, "compile_graph: input slot 2 is empty");
+        assert!(reg.run_graph(&linear_plan(), &x).is_ok());
+        assert!(reg.run_graph(&le_u32s(&[0, 1]), &x).err().unwrap().contains("plan has no steps"));
+    }
+    #[test]
+    fn bmodel_round_trips_layers_weights_plans_and_metadata() {
+        use crate::model::{GraphSignature, Model, ModelBuilder};
+        let mut src = LayerRegistry::new();
+        src.apply_packets(&linear_packet(1)).unwrap();
+        let mut b = ModelBuilder::new();
+        b.add_graph("main", &linear_plan(), &[0, 3], &[0, 2]).unwrap();
+        assert!(b.add_graph("main", &linear_plan(), &[], &[]).is_err());
+        b.set_meta("author", "tim \"riset\"");
+        let bytes = b.build(&src).unwrap();
+
+        let x = WasmTensor::new(&[0.5, -1.0, 2.0], &[1, 3]);
+        let want = src.compile_graph(&linear_plan()).unwrap().run(&src, &x).unwrap().to_array();
+        let (reg, graphs) = LayerRegistry::load_model(&bytes).unwrap();
+        assert_eq!(graphs["main"].run(&reg, &x).unwrap().to_array(), want);
+        assert_eq!(reg.total_params(), 8);
+
+        let model = Model::load(&bytes).unwrap();
+        assert_eq!(model.graph_names(), vec!["main"]);
+        assert_eq!(model.signature("main").unwrap(), &GraphSignature { input: vec![0, 3], output: vec![0, 2] });
+        assert_eq!(model.metadata_json(), r#"{"author":"tim \"riset\""}"#);
+        assert_eq!(model.run("main", &x).unwrap().to_array(), want);
+        assert!(model.run("other", &x).is_err());
+
+        assert!(Model::load(&bytes[..bytes.len() - 3]).is_err());
+        assert!(Model::load(b"nope").err().unwrap().contains("magic"));
+    }
+    #[test]
+    fn bmodel_rejects_unrepresentable_layers_and_bad_plans() {
+        use crate::api::{LinearSpec, SeBlockConfig};
+        use crate::model::ModelBuilder;
+        use crate::layers::activation::ActivationConfig;
+        use burn::nn::HardSigmoidConfig;
+        let mut reg = LayerRegistry::new();
+        reg.add_linear(1, LinearSpec { d_in: 3, d_out: 2, bias: true }).unwrap();
+        // gate HardSigmoid non-default tidak punya kode ACT_* -> tanpa paket init
+        let gate = ActivationConfig::HardSigmoid(HardSigmoidConfig::new().with_alpha(0.5));
+        reg.add_seblock(2, &SeBlockConfig::new(16).with_gate(gate)).unwrap();
+        let mut b = ModelBuilder::new();
+        b.add_graph("main", &linear_plan(), &[], &[]).unwrap();
+        assert!(b.build(&reg).err().unwrap().contains("no init packet"));
+
+        let mut reg = LayerRegistry::new();
+        reg.apply_packets(&linear_packet(2)).unwrap();
+        assert!(b.build(&reg).err().unwrap().contains("graph \"main\""));
+    }
+    #[test]
+    fn tied_linear_mirrors_embedding_transposed_and_counts_once() {
+        use crate::api::{EmbeddingSpec, LinearSpec};
+        let mut reg = LayerRegistry::new();
+        reg.add_embedding(1, EmbeddingSpec { vocab: 4, d_model: 2 }).unwrap();
+        reg.add_linear(2, LinearSpec { d_in: 2, d_out: 4, bias: true }).unwrap();
+        assert_eq!(reg.total_params(), 8 + 12);
+        assert!(reg.tie_weights(2, LAYER_EMBEDDING, 1, "bias", true).is_err());
+        assert!(reg.tie_weights(2, LAYER_EMBEDDING, 1, "weight", false).is_err()); // len cocok, dims [4, 2] != [2, 4]
+        reg.tie_weights(2, LAYER_EMBEDDING, 1, "weight", true).unwrap();
+        assert_eq!(reg.total_params(), 8 + 4);
+
+        // embedding [vocab=4, d_model=2] -> weight linear [2, 4] = transpos
+        let emb: Vec<f32> = (0..8).map(|v| v as f32).collect();
+        reg.set_weights_flat(1, LAYER_EMBEDDING, &emb).unwrap();
+        let lin = reg.get_weights_flat(2, LAYER_LINEAR).unwrap();
+        assert_eq!(&lin[..8], &[0.0, 2.0, 4.0, 6.0, 1.0, 3.0, 5.0, 7.0]);
+
+        // tulis dari sisi linear -> embedding ikut
+        let mut w = lin.clone();
+        w[1] = 20.0; // linear[0][1] = embedding[1][0]
+        reg.set_weights_flat(2, LAYER_LINEAR, &w).unwrap();
+        assert_eq!(reg.get_weights_flat(1, LAYER_EMBEDDING).unwrap()[2], 20.0);
+
+        let layout = reg.weight_layout(2, LAYER_LINEAR).unwrap();
+        assert!(layout.contains(r#""tied":{"type":"#) && layout.contains(r#""transpose":true"#), "{}", layout);
+        assert!(reg.destroy_layer(1, LAYER_EMBEDDING));
+        assert!(reg.weight_tie(2).is_none());
+        assert_eq!(reg.total_params(), 12);
+    }
+    #[test]
+    fn tie_source_changing_shape_drops_tie_instead_of_panicking() {
+        use crate::api::{EmbeddingSpec, LinearSpec};
+        let mut reg = LayerRegistry::new();
+        reg.add_embedding(1, EmbeddingSpec { vocab: 10, d_model: 4 }).unwrap();
+        reg.add_linear(2, LinearSpec { d_in: 4, d_out: 10, bias: false }).unwrap();
+        reg.tie_weights(2, LAYER_EMBEDDING, 1, "weight", true).unwrap();
+
+        let mut other = LayerRegistry::new();
+        other.add_embedding(1, EmbeddingSpec { vocab: 5, d_model: 4 }).unwrap();
+        let state = other.get_layer_state(1, LAYER_EMBEDDING).unwrap();
+        let err = reg.load_layer_state(1, LAYER_EMBEDDING, &state).err().unwrap();
+        assert!(err.contains("tie of linear 2 dropped"), "{}", err);
+        assert!(reg.weight_tie(2).is_none());
+        assert_eq!(reg.get_weights_flat(1, LAYER_EMBEDDING).unwrap().len(), 20);
+        // tanpa tie, menulis linear tidak lagi menyentuh embedding
+        reg.set_weights_flat(2, LAYER_LINEAR, &[0.5; 40]).unwrap();
+        assert_eq!(reg.get_weights_flat(1, LAYER_EMBEDDING).unwrap(), other.get_weights_flat(1, LAYER_EMBEDDING).unwrap());
+    }
+    #[test]
+    fn bmodel_checks_signature_and_keeps_frozen_segments() {
+        use crate::model::{Model, ModelBuilder};
+        let mut src = LayerRegistry::new();
+        src.apply_packets(&linear_packet(1)).unwrap();
+        let mut b = ModelBuilder::new();
+        b.add_graph("main", &linear_plan(), &[0, 3], &[0, 5]).unwrap();
+        assert!(b.build(&src).err().unwrap().contains("signature output [0, 5]"));
+
+        let mut b = ModelBuilder::new();
+        b.add_graph("main", &linear_plan(), &[0, 3], &[0, 2]).unwrap();
+        src.set_trainable(LAYER_LINEAR, 1, "bias", false).unwrap();
+        let model = Model::load(&b.build(&src).unwrap()).unwrap();
+        assert!(!model.registry().is_trainable(LAYER_LINEAR, 1, "bias"));
+        assert!(model.registry().is_trainable(LAYER_LINEAR, 1, "weight"));
+        assert_eq!(model.registry().trainable_len(), 6);
+
+        // dim 0 dinamis: batch 2 lolos, d_in salah ditolak sebelum forward
+        assert_eq!(model.run("main", &WasmTensor::new(&[0.0; 6], &[2, 3])).unwrap().shape(), vec![2, 2, 1, 1]);
+        let err = model.run("main", &WasmTensor::new(&[0.0; 4], &[1, 4])).err().unwrap();
+        assert!(err.contains("does not match signature"), "{}", err);
+    }
+    #[test]
+    fn bmodel_keeps_siamese_weight_tie() {
+        use crate::model::{Model, ModelBuilder};
+        let mut src = LayerRegistry::new();
+        src.apply_packets(&[linear_packet(1), linear_packet(2)].concat()).unwrap();
+        src.tie_weights(2, LAYER_LINEAR, 1, "weight", false).unwrap();
+        assert!(src.tie_weights(1, LAYER_LINEAR, 2, "weight", false).is_err()); // tanpa rantai
+        let bytes = ModelBuilder::new().build(&src).unwrap();
+
+        let model = Model::load(&bytes).unwrap();
+        let reg = model.registry();
+        assert_eq!(reg.weight_tie(2), src.weight_tie(2));
+        assert_eq!(reg.total_params(), 8 + 2);
+        let w1 = reg.get_weights_flat(1, LAYER_LINEAR).unwrap();
+        assert_eq!(&reg.get_weights_flat(2, LAYER_LINEAR).unwrap()[..6], &w1[..6]);
+    }
+    #[test]
+    fn trainable_mask_scatters_one_vector_into_unfrozen_segments() {
+        use crate::api::{LayerRef, LinearSpec};
+        use crate::es::objective::GraphMseObjective;
+        let mut reg = LayerRegistry::new();
+        reg.apply_packets(&linear_packet(1)).unwrap(); // backbone 3 -> 2
+        let head = reg.add_linear(2, LinearSpec { d_in: 2, d_out: 2, bias: true }).unwrap();
+        assert_eq!(reg.trainable_len(), 8 + 6);
+
+        reg.train_only(&[head]).unwrap();
+        assert_eq!(reg.trainable_len(), 6);
+        assert!(!reg.is_trainable(LAYER_LINEAR, 1, "weight"));
+        reg.set_trainable(LAYER_LINEAR, 1, "bias", true).unwrap();
+        assert!(reg.set_trainable(LAYER_LINEAR, 1, "gamma", true).is_err());
+        assert_eq!(
+            reg.trainable_layout(),
+            r#"[{"type":1,"id":1,"name":"bias","len":2,"offset":0},{"type":1,"id":2,"name":"weight","len":4,"offset":2},{"type":1,"id":2,"name":"bias","len":2,"offset":6}]"#
+        );
+
+        let backbone_w = reg.get_weights_flat(1, LAYER_LINEAR).unwrap();
+        let v: Vec<f32> = (0..8).map(|i| i as f32).collect();
+        reg.set_trainable_flat(&v).unwrap();
+        assert_eq!(reg.get_trainable_flat().unwrap(), v);
+        let w1 = reg.get_weights_flat(1, LAYER_LINEAR).unwrap();
+        assert_eq!(&w1[..6], &backbone_w[..6]);
+        assert_eq!(&w1[6..], &[0.0, 1.0]);
+        assert_eq!(reg.get_weights_flat(2, LAYER_LINEAR).unwrap(), &v[2..]);
+        assert!(reg.set_trainable_flat(&v[..7]).is_err());
+
+        let mut plan = le_u32s(&[2, 3]);
+        push_unary(&mut plan, LAYER_LINEAR, 1, 0, 1);
+        push_unary(&mut plan, LAYER_LINEAR, 2, 1, 2);
+        plan.push(2);
+        let g = reg.compile_graph(&plan).unwrap();
+        let input = WasmTensor::new(&[1.0, -1.0, 0.5], &[1, 3]);
+        let target = g.run(&reg, &input).unwrap().to_array();
+        reg.set_all_trainable(true);
+        reg.train_only(&[LayerRef { layer_type: LAYER_LINEAR, id: 2 }]).unwrap();
+        let obj = GraphMseObjective::trainable(reg, g, input, target);
+        assert_eq!(obj.dim(), 6);
+        assert!(obj.fitness(&obj.initial_params().unwrap()).abs() < 1e-12);
+    }
+    #[test]
+    fn init_scheme_trailer_gives_seeded_reproducible_weights() {
+        use crate::protocol::{FLAG_INIT_SCHEME, INIT_CONSTANT, INIT_KAIMING, INIT_ORTHOGONAL};
+        let packet = |id: u32, scheme: u8, seed: u32, param: f32| {
+            let mut p = [le_u32s(&[id, 3, 2]), vec![1], vec![scheme]].concat();
+            p.extend_from_slice(&seed.to_le_bytes());
+            p.extend_from_slice(&param.to_le_bytes());
+            let mut h = mk_header(LAYER_LINEAR, VARIANT_NONE, p.len());
+            h.flags |= FLAG_INIT_SCHEME;
+            [h.to_bytes(), p].concat()
+        };
+        let build = |stream: &[u8]| {
+            let mut reg = LayerRegistry::new();
+            reg.apply_packets(stream).unwrap();
+            reg
+        };
+        let stream = [packet(1, INIT_KAIMING, 7, 0.0), packet(2, INIT_KAIMING, 8, 0.0)].concat();
+        let (a, b) = (build(&stream), build(&stream));
+        let wa = a.get_weights_flat(1, LAYER_LINEAR).unwrap();
+        assert_eq!(wa, b.get_weights_flat(1, LAYER_LINEAR).unwrap());
+        assert_ne!(wa, a.get_weights_flat(2, LAYER_LINEAR).unwrap());
+        assert_eq!(&wa[6..], &[0.0, 0.0]); // bias dinolkan
+        assert_eq!(a.total_params(), 16);
+
+        let c = build(&packet(1, INIT_CONSTANT, 0, 0.25));
+        assert_eq!(&c.get_weights_flat(1, LAYER_LINEAR).unwrap()[..6], &[0.25; 6]);
+
+        // weight [3, 2], kolom ortonormal: W^T W = I
+        let o = build(&packet(1, INIT_ORTHOGONAL, 3, 0.0)).get_weights_flat(1, LAYER_LINEAR).unwrap();
+        for (p, q) in [(0, 0), (0, 1), (1, 1)] {
+            let dot: f32 = (0..3).map(|i| o[i * 2 + p] * o[i * 2 + q]).sum();
+            assert!((dot - if p == q { 1.0 } else { 0.0 }).abs() < 1e-5, "({}, {}) = {}", p, q, dot);
+        }
+    }
+    #[test]
+    fn init_scheme_failure_unregisters_the_layer() {
+        use crate::protocol::{FLAG_INIT_SCHEME, INIT_NORMAL};
+        let packet = |std: f32| {
+            let mut p = [le_u32s(&[1, 3, 2]), vec![1], vec![INIT_NORMAL]].concat();
+            p.extend_from_slice(&5u32.to_le_bytes());
+            p.extend_from_slice(&std.to_le_bytes());
+            let mut h = mk_header(LAYER_LINEAR, VARIANT_NONE, p.len());
+            h.flags |= FLAG_INIT_SCHEME;
+            (h, p)
+        };
+        let mut reg = LayerRegistry::new();
+        for std in [-1.0, f32::NAN] {
+            let (h, p) = packet(std);
+            let err = reg.init_layer(&h, &p).err().unwrap();
+            assert!(err.contains("normal std must be finite and >= 0"), "{}", err);
+            // skema gagal setelah layer dibuat: layer dilepas, tidak ada paket init / params tertinggal
+            assert!(!reg.layer_exists(LAYER_LINEAR, 1));
+            assert!(reg.describe_layer(LAYER_LINEAR, 1).is_err());
+            assert_eq!(reg.total_params(), 0);
+            assert!(reg.snapshot_packets().unwrap().is_empty());
+        }
+        let (h, p) = packet(0.5);
+        reg.init_layer(&h, &p).unwrap();
+        assert!(reg.snapshot_packets().unwrap().starts_with(&[h.to_bytes(), p].concat()));
+    }
+    #[test]
+    fn init_scheme_rejects_stateless_types_and_unknown_codes() {
+        use crate::protocol::{FLAG_INIT_SCHEME, INIT_XAVIER};
+        let trailer = |scheme: u8| [vec![scheme], 1u32.to_le_bytes().to_vec(), 0f32.to_le_bytes().to_vec()].concat();
+        let mut reg = LayerRegistry::new();
+        let p = [le_u32s(&[1]), trailer(INIT_XAVIER)].concat();
+        let mut h = mk_header(LAYER_ACTIVATION, ACT_RELU, p.len());
+        h.flags |= FLAG_INIT_SCHEME;
+        assert!(reg.init_layer(&h, &p).err().unwrap().contains("not supported"));
+        assert!(reg.layer_ids(LAYER_ACTIVATION).is_empty());
+
+        let p = [le_u32s(&[1, 3, 2]), vec![1], trailer(0x42)].concat();
+        let mut h = mk_header(LAYER_LINEAR, VARIANT_NONE, p.len());
+        h.flags |= FLAG_INIT_SCHEME;
+        assert!(reg.init_layer(&h, &p).is_err());
+        reg.init_layer(&mk_header(LAYER_LINEAR, VARIANT_NONE, 13), &p[..13]).unwrap();
+        reg.init_weights(1, LAYER_LINEAR, INIT_XAVIER, 0.0, 5).unwrap();
+        let bound = (6.0f32 / 5.0).sqrt();
+        assert!(reg.get_weights_flat(1, LAYER_LINEAR).unwrap()[..6].iter().all(|w| w.abs() <= bound));
+    }
+    #[test]
+    fn seblock_loads_baseline_state_bytes() {
+        use crate::layers::custom::seblock::WasmSeBlock;
+        // get_state() WasmSeBlock::new(16, Some(4)) dari versi sebelum opsi aktivasi/gate/max_squeeze
+        let legacy = include_bytes!("testdata/seblock_v0.bin");
+        let mut se = WasmSeBlock::new(16, Some(4), Some(true)).unwrap();
+        let fresh = se.get_weights_flat().unwrap();
+        se.load_state(legacy).unwrap();
+        let loaded = se.get_weights_flat().unwrap();
+        assert_eq!(loaded.len(), 148);
+        assert_ne!(loaded, fresh);
+
+        // state baru tetap bisa dibaca balik, opsi dari config tidak tertimpa
+        let mut avg = WasmSeBlock::new(16, Some(4), None).unwrap();
+        avg.load_state(&se.get_state().unwrap()).unwrap();
+        assert_eq!(avg.get_weights_flat().unwrap(), loaded);
+        let x = WasmTensor::new(&(0..64).map(|i| (i as f32 * 0.37).sin() * 3.0).collect::<Vec<_>>(), &[1, 16, 2, 2]);
+        assert_ne!(avg.forward(&x).to_array(), se.forward(&x).to_array());
+    }
+    #[test]
+    fn ghost_loads_baseline_state_bytes() {
+        use crate::layers::custom::ghost::WasmGhostModule;
+        // get_state() WasmGhostModule::new(4, 8, 1, 1, Some(2), ..) dari versi sebelum opsi BN/ReLU
+        let legacy = include_bytes!("testdata/ghost_v0.bin");
+        let new = |bn: Option<bool>| WasmGhostModule::new(4, 8, 1, 1, Some(2), None, None, None, None, None, bn, None).unwrap();
+        let x = WasmTensor::new(&(0..32).map(|i| (i as f32 * 0.37).sin()).collect::<Vec<_>>(), &[1, 4, 2, 4]);
+        let mut g = new(None);
+        let fresh = g.forward(&x).to_array();
+        g.load_state(legacy).unwrap();
+        let loaded = g.forward(&x).to_array();
+        assert_ne!(loaded, fresh);
+
+        // state baru (berprefix) round-trip; config yang tidak cocok -> Err, bukan panic
+        let mut again = new(None);
+        again.load_state(&g.get_state().unwrap()).unwrap();
+        assert_eq!(again.forward(&x).to_array(), loaded);
+        assert!(new(Some(true)).load_state(legacy).is_err());
+        assert!(WasmGhostModule::new(4, 8, 1, 1, Some(1), None, None, None, None, None, None, None)
+            .unwrap().load_state(legacy).is_err());
+    }
+    #[test]
+    fn graph_output_shape_covers_conv_norm_activation_linear() {
+        use crate::api::{ActivationSpec, ConvKind, ConvSpec, GraphBuilder, LinearSpec, NormSpec};
+        let mut reg = LayerRegistry::new();
+        let mut spec = ConvSpec::new(3, 4, 3, 3);
+        (spec.stride_h, spec.stride_w, spec.padding_h, spec.padding_w) = (Some(2), Some(2), Some(1), Some(1));
+        let conv = reg.add_conv(1, ConvKind::Conv2d, &spec).unwrap();
+        let bn = reg.add_norm(2, NormSpec::Batch { num_features: 4, epsilon: None }).unwrap();
+        let relu = reg.add_activation(3, ActivationSpec::Relu).unwrap();
+        let fc = reg.add_linear(4, LinearSpec { d_in: 4, d_out: 5, bias: true }).unwrap();
+        let mut g = GraphBuilder::new();
+        let c = g.unary(conv, g.input());
+        let n = g.unary(bn, c);
+        let a = g.unary(relu, n);
+        let y = g.unary(fc, a);
+        let graph = g.build(&reg, y).unwrap();
+
+        let x = WasmTensor::new(&(0..24).map(|i| i as f32 / 24.0).collect::<Vec<_>>(), &[2, 3, 2, 2]);
+        assert_eq!(graph.output_shape(&reg, &[2, 3, 2, 2]).unwrap(), vec![2, 5, 1, 1]);
+        assert_eq!(graph.run(&reg, &x).unwrap().shape(), vec![2, 5, 1, 1]);
+        // spatial 4x4 -> conv 2x2 -> linear tidak bisa reshape
+        assert!(graph.output_shape(&reg, &[2, 3, 4, 4]).err().unwrap().contains("linear"));
+
+        let mut spec = ConvSpec::new(4, 2, 3, 3);
+        (spec.stride_h, spec.stride_w, spec.padding_h, spec.padding_w) = (Some(2), Some(2), Some(1), Some(1));
+        reg.add_conv(5, ConvKind::ConvTranspose2d, &spec).unwrap();
+        let up = WasmTensor::new(&[0.5; 4 * 3 * 5], &[1, 4, 3, 5]);
+        let run = reg.forward_layer(5, LAYER_CONV, &up).unwrap().shape();
+        assert_eq!(reg.output_shape(5, LAYER_CONV, &[1, 4, 3, 5]).unwrap(), run);
+        assert!(reg.output_shape(5, LAYER_CONV, &[1, 3, 3, 5]).is_err());
+    }
+    #[test]
+    fn init_scheme_fills_conv_linear_segments_inside_custom_blocks() {
+        use crate::layers::custom::ghost::GhostModuleConfig;
+        use crate::layers::custom::seblock::SeBlockConfig;
+        use crate::protocol::{INIT_CONSTANT, INIT_KAIMING, LAYER_GHOST, LAYER_SEBLOCK};
+        let build = |seed: u32| {
+            let mut reg = LayerRegistry::new();
+            reg.add_seblock(1, &SeBlockConfig::new(16)).unwrap();
+            reg.add_ghost(2, &GhostModuleConfig::new(4, 8, [1, 1])).unwrap();
+            reg.init_weights(1, LAYER_SEBLOCK, INIT_KAIMING, 0.0, seed).unwrap();
+            reg.init_weights(2, LAYER_GHOST, INIT_KAIMING, 0.0, seed).unwrap();
+            reg
+        };
+        let (a, b, c) = (build(7), build(7), build(8));
+        for t in [LAYER_SEBLOCK, LAYER_GHOST] {
+            let id = if t == LAYER_SEBLOCK { 1 } else { 2 };
+            assert_eq!(a.get_weights_flat(id, t).unwrap(), b.get_weights_flat(id, t).unwrap());
+            assert_ne!(a.get_weights_flat(id, t).unwrap(), c.get_weights_flat(id, t).unwrap());
+        }
+
+        // tiap segmen *weight diisi skema, *bias dinolkan
+        let mut reg = build(7);
+        reg.init_weights(1, LAYER_SEBLOCK, INIT_CONSTANT, 0.5, 0).unwrap();
+        let flat = reg.get_weights_flat(1, LAYER_SEBLOCK).unwrap();
+        let mut offset = 0;
+        for (name, len) in reg.weight_segs(1, LAYER_SEBLOCK).unwrap() {
+            let want = if name.ends_with("bias") { 0.0 } else { 0.5 };
+            assert!(flat[offset..offset + len].iter().all(|&w| w == want), "{}", name);
+            offset += len;
+        }
+        assert_eq!(offset, flat.len());
+    }
+    #[test]
+    fn bmodel_round_trips_layers_added_via_rust_api() {
+        use crate::api::{ConvKind, ConvSpec, GraphBuilder, LinearSpec};
+        use crate::model::{Model, ModelBuilder};
+        let mut src = LayerRegistry::new();
+        let conv = src.add_conv(1, ConvKind::Conv2d, &ConvSpec::new(1, 2, 3, 3)).unwrap();
+        let fc = src.add_linear(2, LinearSpec { d_in: 2, d_out: 3, bias: true }).unwrap();
+        let mut g = GraphBuilder::new();
+        let h = g.unary(conv, g.input());
+        let y = g.unary(fc, h);
+        let plan = g.to_plan(y);
+        let mut b = ModelBuilder::new();
+        b.add_graph("main", &plan, &[], &[]).unwrap();
+        let bytes = b.build(&src).unwrap();
+
+        let x = WasmTensor::new(&(0..9).map(|i| (i as f32 * 0.3).sin()).collect::<Vec<_>>(), &[1, 1, 3, 3]);
+        let want = src.compile_graph(&plan).unwrap().run(&src, &x).unwrap().to_array();
+        let model = Model::load(&bytes).unwrap();
+        assert_eq!(model.run("main", &x).unwrap().to_array(), want);
+        assert_eq!(model.registry().list_layers(), src.list_layers());
+    }
+    #[test]
+    fn snapshot_packets_rebuilds_every_rust_api_layer_type() {
+        use crate::api::*;
+        use crate::protocol::{LAYER_CBAM, LAYER_GHOST, LAYER_SEBLOCK};
+        use burn::tensor::ops::PadMode;
+        let mut src = LayerRegistry::new();
+        let mut conv3d = ConvSpec::new(4, 2, 3, 3);
+        conv3d.kernel_d = 3;
+        conv3d.padding_mode = ConvPadding::Same;
+        conv3d.bias = Some(false);
+        src.add_conv(1, ConvKind::Conv3d, &conv3d).unwrap();
+        src.add_norm(2, NormSpec::Group { num_groups: 2, num_channels: 4, epsilon: Some(1e-3) }).unwrap();
+        src.add_activation(3, ActivationSpec::SwiGlu { d_input: 4, d_output: 3, bias: Some(false) }).unwrap();
+        src.add_embedding(4, EmbeddingSpec { vocab: 5, d_model: 2 }).unwrap();
+        let pool = PoolSpec::MaxPool2d { kernel: [2, 2], stride: Some([1, 2]), padding: None, dilation: None, ceil_mode: true };
+        src.add_pool(5, pool).unwrap();
+        src.add_resample(6, Resample::Bilinear(ResampleSize::Scale(1.5, 2.0))).unwrap();
+        src.add_shift(7, Shift::grouped(1, vec![ShiftDirection::Up, ShiftDirection::Left], ShiftFill::Constant(0.5))).unwrap();
+        src.add_binary(8, Binary::new(BinaryOp::Concat, 1)).unwrap();
+        src.add_tensor_op(9, TensorOp::Pad { pads: [1, 0, 2, 0], mode: PadMode::Constant(-1.0) }).unwrap();
+        src.add_ghost(10, &GhostModuleConfig::new(4, 8, [1, 1]).with_dw_kernel_size(3).with_relu(true)).unwrap();
+        src.add_seblock(11, &SeBlockConfig::new(16).with_max_squeeze(true)).unwrap();
+        src.add_eca(12, &EcaBlockConfig::new(8).with_kernel_size(Some(5))).unwrap();
+        src.add_cbam(13, &CbamBlockConfig::new(8).with_reduction(4)).unwrap();
+        src.add_spatial_attention(14, &SpatialAttentionConfig::new().with_kernel_size(3)).unwrap();
+
+        let mut dst = LayerRegistry::new();
+        dst.apply_packets(&src.snapshot_packets().unwrap()).unwrap();
+        assert_eq!(dst.list_layers(), src.list_layers());
+        for t in [LAYER_CONV, LAYER_NORM, LAYER_ACTIVATION, LAYER_GHOST, LAYER_SEBLOCK, LAYER_CBAM] {
+            for id in src.layer_ids(t) {
+                assert_eq!(dst.get_layer_state(id, t).unwrap(), src.get_layer_state(id, t).unwrap(), "{}", t);
+            }
+        }
+    }
+}